@@ -0,0 +1,14 @@
+-- Create tasks table
+-- IF NOT EXISTS: databases created before schema versioning already have it
+CREATE TABLE IF NOT EXISTS tasks (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT,
+    status TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    tags TEXT NOT NULL, -- JSON array as text
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    due_date TEXT,
+    completed_at TEXT
+);
@@ -0,0 +1,9 @@
+-- Store the remaining fields of the shared task model
+ALTER TABLE tasks ADD COLUMN started_at TEXT;
+ALTER TABLE tasks ADD COLUMN cancelled_at TEXT;
+ALTER TABLE tasks ADD COLUMN reopened_at TEXT;
+ALTER TABLE tasks ADD COLUMN assigned_to TEXT;
+ALTER TABLE tasks ADD COLUMN created_by TEXT NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000';
+
+-- Rows written by older versions used the display label
+UPDATE tasks SET status = 'InProgress' WHERE status = 'In Progress';
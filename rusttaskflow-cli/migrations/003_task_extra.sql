@@ -0,0 +1,2 @@
+-- Attributes imported from other tools that the task model has no field for
+ALTER TABLE tasks ADD COLUMN extra TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- Parent task of a subtask (NULL for top-level tasks)
+ALTER TABLE tasks ADD COLUMN parent_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id);
@@ -0,0 +1,2 @@
+-- Tasks that must be completed first, as a JSON array of ids (like tags)
+ALTER TABLE tasks ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- Recurring tasks: schedule as an RRULE and link to the first occurrence
+ALTER TABLE tasks ADD COLUMN recurrence TEXT;
+ALTER TABLE tasks ADD COLUMN series_id TEXT;
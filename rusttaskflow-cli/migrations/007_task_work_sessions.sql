@@ -0,0 +1,2 @@
+-- Time tracking: work sessions as a JSON array of {started_at, ended_at}
+ALTER TABLE tasks ADD COLUMN work_sessions TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,2 @@
+-- Projects: tasks shared with the web server keep their project
+ALTER TABLE tasks ADD COLUMN project_id TEXT;
@@ -0,0 +1,319 @@
+//! Sauvegardes horodatées du stockage
+//!
+//! Chaque sauvegarde est un fichier `backups/<nom>-<id>.<ext>` à côté du
+//! fichier de tâches, où `<id>` est l'horodatage UTC de sa création. Les
+//! backends se chargent de produire et de relire ces fichiers ; ce module
+//! gère leur nommage, leur liste, leur rotation et leur comparaison.
+
+use crate::storage::task_checksum;
+use crate::task::Task;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Format des identifiants de sauvegarde (horodatage UTC à la milliseconde)
+const ID_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// Politique de rétention des sauvegardes (section `[backup]` de config.toml)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupRetention {
+    /// Nombre maximum de sauvegardes conservées
+    pub keep: Option<usize>,
+    /// Âge maximum, en jours, des sauvegardes conservées
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        Self {
+            keep: Some(10),
+            max_age_days: None,
+        }
+    }
+}
+
+/// Une sauvegarde existante
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub id: String,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// Répertoire de sauvegardes d'un fichier de tâches
+#[derive(Debug, Clone)]
+pub struct BackupDir {
+    dir: PathBuf,
+    stem: String,
+    extension: String,
+}
+
+impl BackupDir {
+    /// Sauvegardes de `file_path`, rangées dans `backups/` à côté de lui
+    pub fn for_file(file_path: &Path) -> Self {
+        let dir = file_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("backups");
+        let stem = file_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "tasks".to_string());
+        let extension = file_path
+            .extension()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self { dir, stem, extension }
+    }
+
+    /// Crée une sauvegarde horodatée maintenant ; `write` produit le fichier
+    pub fn create<F>(&self, write: F) -> Result<Backup>
+    where
+        F: FnOnce(&Path) -> Result<()>,
+    {
+        fs::create_dir_all(&self.dir)?;
+        let now = Utc::now();
+        let id = now.format(ID_FORMAT).to_string();
+        let path = self.path_for(&id);
+        if path.exists() {
+            return Err(anyhow!("La sauvegarde '{}' existe déjà", id));
+        }
+
+        write(&path)?;
+        let size = fs::metadata(&path)?.len();
+        Ok(Backup {
+            id,
+            path,
+            created_at: now,
+            size,
+        })
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}-{}.{}", self.stem, id, self.extension))
+    }
+
+    fn id_from_file_name(&self, name: &str) -> Option<String> {
+        name.strip_prefix(&format!("{}-", self.stem))?
+            .strip_suffix(&format!(".{}", self.extension))
+            .map(|id| id.to_string())
+    }
+
+    /// Toutes les sauvegardes, de la plus récente à la plus ancienne
+    pub fn list(&self) -> Result<Vec<Backup>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(id) = self.id_from_file_name(&name) else {
+                continue;
+            };
+            let Ok(created_at) = NaiveDateTime::parse_from_str(&id, ID_FORMAT) else {
+                continue;
+            };
+            backups.push(Backup {
+                id,
+                path: entry.path(),
+                created_at: created_at.and_utc(),
+                size: entry.metadata()?.len(),
+            });
+        }
+
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        Ok(backups)
+    }
+
+    /// Retrouve une sauvegarde par son identifiant (ou un préfixe non ambigu)
+    pub fn find(&self, id: &str) -> Result<Backup> {
+        let matches: Vec<Backup> = self
+            .list()?
+            .into_iter()
+            .filter(|b| b.id.starts_with(id))
+            .collect();
+
+        match matches.len() {
+            0 => Err(anyhow!("Sauvegarde '{}' introuvable", id)),
+            1 => Ok(matches.into_iter().next().unwrap()),
+            n => Err(anyhow!("'{}' correspond à {} sauvegardes, précisez l'identifiant", id, n)),
+        }
+    }
+
+    /// Supprime les sauvegardes hors de la politique de rétention
+    pub fn prune(&self, retention: &BackupRetention) -> Result<Vec<Backup>> {
+        let cutoff = retention
+            .max_age_days
+            .map(|days| Utc::now() - Duration::days(days as i64));
+
+        let mut removed = Vec::new();
+        for (index, backup) in self.list()?.into_iter().enumerate() {
+            let too_many = retention.keep.is_some_and(|keep| index >= keep);
+            let too_old = cutoff.is_some_and(|cutoff| backup.created_at < cutoff);
+            if too_many || too_old {
+                fs::remove_file(&backup.path)?;
+                removed.push(backup);
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Nature d'une différence entre une sauvegarde et l'état courant
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// Tâche absente de la sauvegarde
+    Added,
+    /// Tâche présente dans la sauvegarde uniquement
+    Removed,
+    /// Tâche modifiée depuis la sauvegarde (noms des champs concernés)
+    Modified(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskChange {
+    pub id: Uuid,
+    pub title: String,
+    pub kind: ChangeKind,
+}
+
+/// Compare le contenu d'une sauvegarde avec l'état courant
+pub fn diff_tasks(backup: &HashMap<Uuid, Task>, current: &HashMap<Uuid, Task>) -> Result<Vec<TaskChange>> {
+    let mut changes = Vec::new();
+
+    for (id, task) in current {
+        match backup.get(id) {
+            None => changes.push(TaskChange {
+                id: *id,
+                title: task.title.clone(),
+                kind: ChangeKind::Added,
+            }),
+            Some(old) if task_checksum(old)? != task_checksum(task)? => changes.push(TaskChange {
+                id: *id,
+                title: task.title.clone(),
+                kind: ChangeKind::Modified(changed_fields(old, task)?),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (id, task) in backup {
+        if !current.contains_key(id) {
+            changes.push(TaskChange {
+                id: *id,
+                title: task.title.clone(),
+                kind: ChangeKind::Removed,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.title.cmp(&b.title));
+    Ok(changes)
+}
+
+fn changed_fields(old: &Task, new: &Task) -> Result<Vec<String>> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(new
+        .iter()
+        .filter(|(key, value)| old.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::new_task;
+    use tempfile::tempdir;
+
+    fn touch_backup(dir: &BackupDir, created_at: DateTime<Utc>) {
+        fs::create_dir_all(&dir.dir).unwrap();
+        let id = created_at.format(ID_FORMAT).to_string();
+        fs::write(dir.path_for(&id), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_list_and_find_backups() {
+        let temp_dir = tempdir().unwrap();
+        let dir = BackupDir::for_file(&temp_dir.path().join("tasks.json"));
+        assert!(dir.list().unwrap().is_empty());
+
+        let backup = dir
+            .create(|path| Ok(fs::write(path, "{}")?))
+            .unwrap();
+        assert_eq!(backup.size, 2);
+        touch_backup(&dir, Utc::now() - Duration::days(2));
+        // Fichiers étrangers ignorés
+        fs::write(temp_dir.path().join("backups").join("notes.txt"), "x").unwrap();
+
+        let backups = dir.list().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].id, backup.id);
+        assert_eq!(dir.find(&backup.id).unwrap().path, backup.path);
+        assert!(dir.find("1999").is_err());
+    }
+
+    #[test]
+    fn test_prune_backups() {
+        let temp_dir = tempdir().unwrap();
+        let dir = BackupDir::for_file(&temp_dir.path().join("tasks.db"));
+        for days in [0, 1, 2, 10, 40] {
+            touch_backup(&dir, Utc::now() - Duration::days(days));
+        }
+
+        let removed = dir
+            .prune(&BackupRetention { keep: None, max_age_days: Some(30) })
+            .unwrap();
+        assert_eq!(removed.len(), 1);
+
+        let removed = dir
+            .prune(&BackupRetention { keep: Some(2), max_age_days: None })
+            .unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(dir.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_diff_tasks() {
+        let kept = new_task("Kept".to_string());
+        let mut edited = new_task("Edited".to_string());
+        let removed = new_task("Removed".to_string());
+        let backup: HashMap<Uuid, Task> = [kept.clone(), edited.clone(), removed.clone()]
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+
+        edited.add_tag("work".to_string());
+        let added = new_task("Added".to_string());
+        let current: HashMap<Uuid, Task> = [kept, edited, added]
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+
+        let changes = diff_tasks(&backup, &current).unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].kind, ChangeKind::Added);
+        match &changes[1].kind {
+            ChangeKind::Modified(fields) => {
+                assert!(fields.contains(&"tags".to_string()));
+                assert!(fields.contains(&"updated_at".to_string()));
+            }
+            other => panic!("unexpected change: {:?}", other),
+        }
+        assert_eq!(changes[2].kind, ChangeKind::Removed);
+    }
+}
@@ -1,8 +1,13 @@
-use crate::task::{Priority, Status};
-use chrono::{DateTime, Utc};
+use crate::config::StorageType;
+use crate::formats::markdown::GroupBy;
+use crate::import::ImportMode;
+use crate::task::{Priority, TaskStatus};
+use crate::timezone::UserTimeZone;
+use chrono::{DateTime, Duration, Utc};
 use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 use uuid::Uuid;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
 #[derive(Parser)]
 #[command(name = "rtf")]
@@ -10,6 +15,15 @@ use anyhow::Result;
 #[command(version = "0.1.0")]
 #[command(author = "Jimmy Ramsamy-Naick")]
 pub struct Cli {
+    /// Backend de stockage (remplace `RTF_STORAGE` et le fichier de configuration)
+    #[arg(long, global = true, value_enum)]
+    pub storage: Option<CliStorage>,
+    /// Répertoire des fichiers de tâches (remplace `RTF_DATA_DIR`)
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+    /// Fuseau horaire, `system` ou nom IANA comme Europe/Paris (remplace `RTF_TIMEZONE`)
+    #[arg(long, global = true)]
+    pub timezone: Option<UserTimeZone>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -29,9 +43,15 @@ pub enum Commands {
         /// Tags pour la tâche (séparés par des virgules)
         #[arg(short, long)]
         tags: Option<String>,
-        /// Date d'échéance (format: YYYY-MM-DD ou YYYY-MM-DD HH:MM)
+        /// Date d'échéance: YYYY-MM-DD [HH:MM], today, "tomorrow 9am", "next friday", "in 3 days", +2w, eom…
         #[arg(short = 'u', long)]
         due: Option<String>,
+        /// ID de la tâche parente, pour créer une sous-tâche (peut être partiel)
+        #[arg(long)]
+        parent: Option<String>,
+        /// Récurrence: daily, weekly, monthly, yearly, "every 2 weeks on mon,thu", "monthly on day 31"…
+        #[arg(short, long)]
+        repeat: Option<String>,
     },
     /// Lister les tâches
     List {
@@ -47,6 +67,12 @@ pub enum Commands {
         /// Afficher seulement les tâches en retard
         #[arg(long)]
         overdue: bool,
+        /// Échéance au plus tard à cette date (mêmes formats que `add --due`)
+        #[arg(long)]
+        due_before: Option<String>,
+        /// Échéance après cette date (mêmes formats que `add --due`)
+        #[arg(long)]
+        due_after: Option<String>,
         /// Nombre maximum de tâches à afficher
         #[arg(short, long)]
         limit: Option<usize>,
@@ -60,12 +86,25 @@ pub enum Commands {
     Start {
         /// ID de la tâche (peut être partiel)
         id: String,
+        /// Démarrer même si des dépendances ne sont pas terminées
+        #[arg(short, long)]
+        force: bool,
     },
     /// Annuler une tâche
     Cancel {
         /// ID de la tâche (peut être partiel)
         id: String,
     },
+    /// Rouvrir une tâche terminée ou annulée
+    Reopen {
+        /// ID de la tâche (peut être partiel)
+        id: String,
+    },
+    /// Remettre une tâche en cours à l'état « À faire »
+    Reset {
+        /// ID de la tâche (peut être partiel)
+        id: String,
+    },
     /// Supprimer une tâche
     Delete {
         /// ID de la tâche (peut être partiel)
@@ -87,7 +126,7 @@ pub enum Commands {
         /// Nouvelle priorité
         #[arg(short, long, value_enum)]
         priority: Option<CliPriority>,
-        /// Nouvelle date d'échéance
+        /// Nouvelle date d'échéance (mêmes formats que `add --due`)
         #[arg(short = 'u', long)]
         due: Option<String>,
     },
@@ -109,6 +148,34 @@ pub enum Commands {
         #[command(subcommand)]
         action: TagCommands,
     },
+    /// Gérer les dépendances entre tâches
+    Depend {
+        #[command(subcommand)]
+        action: DependCommands,
+    },
+    /// Gérer les tâches récurrentes
+    Recur {
+        #[command(subcommand)]
+        action: RecurCommands,
+    },
+    /// Suivre le temps passé sur une tâche
+    Timer {
+        #[command(subcommand)]
+        action: TimerCommands,
+    },
+    /// Enregistrer du temps passé sur une tâche, par exemple `rtf log 1a2b 45m`
+    Log {
+        /// ID de la tâche (peut être partiel)
+        id: String,
+        /// Durée: 45m, 1h, 1h30, 1:30…
+        duration: String,
+    },
+    /// Exporter le graphe des dépendances au format DOT (Graphviz)
+    Graph {
+        /// Fichier de sortie
+        #[arg(short, long)]
+        output: Option<String>,
+    },
     /// Afficher les statistiques
     Stats,
     /// Lister tous les tags
@@ -121,6 +188,9 @@ pub enum Commands {
         /// Format d'export
         #[arg(short, long, value_enum, default_value = "json")]
         format: ExportFormat,
+        /// Regroupement des tâches en sections (format markdown)
+        #[arg(long, value_enum, default_value = "status")]
+        group_by: CliGroupBy,
     },
     /// Importer des tâches
     Import {
@@ -129,6 +199,15 @@ pub enum Commands {
         /// Format d'import
         #[arg(short, long, value_enum, default_value = "json")]
         format: ExportFormat,
+        /// Manière de combiner les tâches importées avec les tâches locales
+        #[arg(short, long, value_enum, default_value = "merge")]
+        mode: CliImportMode,
+        /// Version retenue quand une tâche existe des deux côtés (mode merge)
+        #[arg(long, value_enum, default_value = "newest")]
+        on_conflict: CliConflict,
+        /// Afficher le résumé sans rien enregistrer
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Nettoyer les tâches terminées
     Clean {
@@ -139,6 +218,46 @@ pub enum Commands {
         #[arg(short, long)]
         force: bool,
     },
+    /// Copier les tâches vers un autre backend de stockage
+    MigrateStorage {
+        /// Backend de destination
+        #[arg(long, value_enum)]
+        to: CliStorage,
+        /// Backend source (par défaut, le stockage configuré)
+        #[arg(long, value_enum)]
+        from: Option<CliStorage>,
+        /// Écraser la destination si elle contient déjà des tâches
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Gérer la base de données SQLite
+    Db {
+        #[command(subcommand)]
+        action: DbCommands,
+    },
+    /// Gérer les sauvegardes du stockage
+    Backup {
+        #[command(subcommand)]
+        action: BackupCommands,
+    },
+    /// Annuler les dernières opérations
+    Undo {
+        /// Nombre d'opérations à annuler
+        #[arg(default_value = "1")]
+        count: usize,
+    },
+    /// Rétablir les dernières opérations annulées
+    Redo {
+        /// Nombre d'opérations à rétablir
+        #[arg(default_value = "1")]
+        count: usize,
+    },
+    /// Afficher l'historique des opérations
+    History {
+        /// Nombre maximum d'opérations affichées
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
 }
 
 #[derive(Subcommand)]
@@ -159,6 +278,87 @@ pub enum TagCommands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum DependCommands {
+    /// Indiquer qu'une tâche dépend d'une autre
+    Add {
+        /// ID de la tâche bloquée
+        id: String,
+        /// ID de la tâche à terminer d'abord
+        depends_on: String,
+    },
+    /// Retirer une dépendance
+    Remove {
+        /// ID de la tâche bloquée
+        id: String,
+        /// ID de la tâche dont elle ne dépend plus
+        depends_on: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RecurCommands {
+    /// Rendre une tâche récurrente ou changer sa récurrence
+    Set {
+        /// ID de la tâche (peut être partiel)
+        id: String,
+        /// Récurrence: daily, weekly, monthly, yearly, "every 2 weeks on mon,thu", "monthly on day 31"…
+        rule: String,
+    },
+    /// Arrêter une série : terminer ses tâches ne crée plus d'occurrence
+    Stop {
+        /// ID d'une tâche de la série (peut être partiel)
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TimerCommands {
+    /// Démarrer le minuteur d'une tâche (un seul minuteur à la fois)
+    Start {
+        /// ID de la tâche (peut être partiel)
+        id: String,
+        /// Démarrer même si des dépendances ne sont pas terminées
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Arrêter le minuteur en cours
+    Stop,
+    /// Afficher le minuteur en cours
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Appliquer les migrations de schéma en attente
+    Migrate {
+        /// Afficher les migrations sans les appliquer
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// Créer une sauvegarde maintenant
+    Create,
+    /// Lister les sauvegardes, de la plus récente à la plus ancienne
+    List,
+    /// Restaurer une sauvegarde (l'état courant est sauvegardé avant)
+    Restore {
+        /// Identifiant de la sauvegarde (ou début non ambigu)
+        id: String,
+        /// Restaurer sans confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Comparer une sauvegarde avec l'état courant
+    Diff {
+        /// Identifiant de la sauvegarde (ou début non ambigu)
+        id: String,
+    },
+}
+
 #[derive(Clone, ValueEnum)]
 pub enum CliStatus {
     Todo,
@@ -167,24 +367,24 @@ pub enum CliStatus {
     Cancelled,
 }
 
-impl From<CliStatus> for Status {
+impl From<CliStatus> for TaskStatus {
     fn from(cli_status: CliStatus) -> Self {
         match cli_status {
-            CliStatus::Todo => Status::Todo,
-            CliStatus::InProgress => Status::InProgress,
-            CliStatus::Completed => Status::Completed,
-            CliStatus::Cancelled => Status::Cancelled,
+            CliStatus::Todo => TaskStatus::Todo,
+            CliStatus::InProgress => TaskStatus::InProgress,
+            CliStatus::Completed => TaskStatus::Completed,
+            CliStatus::Cancelled => TaskStatus::Cancelled,
         }
     }
 }
 
-impl From<Status> for CliStatus {
-    fn from(status: Status) -> Self {
+impl From<TaskStatus> for CliStatus {
+    fn from(status: TaskStatus) -> Self {
         match status {
-            Status::Todo => CliStatus::Todo,
-            Status::InProgress => CliStatus::InProgress,
-            Status::Completed => CliStatus::Completed,
-            Status::Cancelled => CliStatus::Cancelled,
+            TaskStatus::Todo => CliStatus::Todo,
+            TaskStatus::InProgress => CliStatus::InProgress,
+            TaskStatus::Completed => CliStatus::Completed,
+            TaskStatus::Cancelled => CliStatus::Cancelled,
         }
     }
 }
@@ -220,33 +420,124 @@ impl From<Priority> for CliPriority {
 }
 
 #[derive(Clone, ValueEnum)]
-pub enum ExportFormat {
+pub enum CliStorage {
     Json,
-    Csv,
+    Sqlite,
 }
 
-/// Utilitaires pour parser les dates
-pub fn parse_date(date_str: &str) -> Result<DateTime<Utc>> {
-    // Essaie d'abord le format avec heure
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M") {
-        return Ok(dt.and_utc());
+impl From<CliStorage> for StorageType {
+    fn from(cli_storage: CliStorage) -> Self {
+        match cli_storage {
+            CliStorage::Json => StorageType::Json,
+            CliStorage::Sqlite => StorageType::Sqlite,
+        }
     }
-    
-    // Essaie le format date seulement
-    if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-        return Ok(date.and_hms_opt(23, 59, 59).unwrap().and_utc());
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum CliImportMode {
+    Merge,
+    Replace,
+    SkipExisting,
+}
+
+impl From<CliImportMode> for ImportMode {
+    fn from(cli_mode: CliImportMode) -> Self {
+        match cli_mode {
+            CliImportMode::Merge => ImportMode::Merge,
+            CliImportMode::Replace => ImportMode::Replace,
+            CliImportMode::SkipExisting => ImportMode::SkipExisting,
+        }
     }
-    
-    // Essaie d'autres formats courants
-    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(date_str, "%d/%m/%Y %H:%M") {
-        return Ok(dt.and_utc());
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum CliConflict {
+    /// La version modifiée le plus récemment (`updated_at`)
+    Newest,
+    /// Toujours la version locale
+    Local,
+    /// Toujours la version importée
+    Incoming,
+    /// Demander pour chaque conflit
+    Ask,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    /// JSON de `task export` / `task import`
+    Taskwarrior,
+    /// iCalendar (VTODO), lisible par les applications d'agenda
+    Ical,
+    /// Liste de cases à cocher Markdown
+    Markdown,
+    /// Une tâche par ligne, conventions todo.txt
+    Todotxt,
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum CliGroupBy {
+    Status,
+    Tag,
+}
+
+impl From<CliGroupBy> for GroupBy {
+    fn from(cli_group_by: CliGroupBy) -> Self {
+        match cli_group_by {
+            CliGroupBy::Status => GroupBy::Status,
+            CliGroupBy::Tag => GroupBy::Tag,
+        }
     }
-    
-    if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%d/%m/%Y") {
-        return Ok(date.and_hms_opt(23, 59, 59).unwrap().and_utc());
+}
+
+/// Utilitaires pour parser les dates : formats absolus ou expressions
+/// relatives (`tomorrow 9am`, `next friday`, `+2w`…) dans le fuseau de
+/// l'utilisateur, voir `dates`
+pub fn parse_date(date_str: &str) -> Result<DateTime<Utc>> {
+    crate::dates::parse_date_at(date_str, &UserTimeZone::current().now())
+}
+
+/// Utilitaires pour parser les durées : `45m`, `90min`, `1h`, `1h30`,
+/// `1h 30m` ou `1:30`
+pub fn parse_duration(duration_str: &str) -> Result<Duration> {
+    let invalid = || anyhow!("Durée invalide: '{}'. Exemples: 45m, 1h, 1h30, 1:30", duration_str.trim());
+    let number = |value: &str| -> Option<i64> {
+        if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        value.parse().ok()
+    };
+    let minutes_of = |value: &str| -> Option<i64> {
+        let value = value.strip_suffix("min").or_else(|| value.strip_suffix('m')).unwrap_or(value);
+        if value.is_empty() {
+            Some(0)
+        } else {
+            number(value).filter(|m| *m < 60)
+        }
+    };
+
+    let value: String = duration_str.to_lowercase().split_whitespace().collect();
+    let minutes = if let Some((hours, minutes)) = value.split_once(':') {
+        number(hours)
+            .zip(number(minutes).filter(|m| *m < 60))
+            .and_then(|(h, m)| h.checked_mul(60)?.checked_add(m))
+    } else if let Some((hours, minutes)) = value.split_once('h') {
+        number(hours)
+            .zip(minutes_of(minutes))
+            .and_then(|(h, m)| h.checked_mul(60)?.checked_add(m))
+    } else {
+        value
+            .strip_suffix("min")
+            .or_else(|| value.strip_suffix('m'))
+            .and_then(number)
+    };
+
+    match minutes {
+        Some(minutes) if minutes > 0 => Duration::try_minutes(minutes).ok_or_else(invalid),
+        _ => Err(invalid()),
     }
-    
-    Err(anyhow::anyhow!("Format de date invalide. Utilisez DD/MM/YYYY ou DD/MM/YYYY HH:MM"))
 }
 
 /// Utilitaires pour parser les tags
@@ -294,6 +585,23 @@ mod tests {
         assert!(parse_date("invalid-date").is_err());
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45m").unwrap(), Duration::minutes(45));
+        assert_eq!(parse_duration("90min").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_duration("1h30").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("1h 05m").unwrap(), Duration::minutes(65));
+        assert_eq!(parse_duration("1:30").unwrap(), Duration::minutes(90));
+        for invalid in ["", "45", "0m", "1h75", "-5m", "1.5h", "une heure"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+        // Trop grandes pour une durée
+        for invalid in ["999999999999999999m", "999999999999999999h", "99999999999999999:00"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+    }
+
     #[test]
     fn test_parse_tags() {
         let tags = parse_tags("work, urgent, important");
@@ -0,0 +1,226 @@
+//! Configuration du CLI
+//!
+//! Les réglages sont lus dans cet ordre, chaque source écrasant la précédente :
+//! valeurs par défaut, fichier `config.toml`, variables d'environnement `RTF_*`,
+//! puis options globales de la ligne de commande (`--storage`, `--data-dir`,
+//! `--timezone`).
+
+use crate::backup::BackupRetention;
+use crate::timezone::UserTimeZone;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Variable d'environnement désignant un autre fichier de configuration
+pub const ENV_CONFIG: &str = "RTF_CONFIG";
+/// Variable d'environnement choisissant le backend de stockage
+pub const ENV_STORAGE: &str = "RTF_STORAGE";
+/// Variable d'environnement choisissant le répertoire de données
+pub const ENV_DATA_DIR: &str = "RTF_DATA_DIR";
+/// Variable d'environnement choisissant le fuseau horaire
+pub const ENV_TIMEZONE: &str = "RTF_TIMEZONE";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageType {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StorageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageType::Json => "json",
+            StorageType::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl std::str::FromStr for StorageType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(StorageType::Json),
+            "sqlite" => Ok(StorageType::Sqlite),
+            _ => Err(anyhow!("Type de stockage non supporté: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Backend de stockage (`json` ou `sqlite`)
+    pub storage: StorageType,
+    /// Répertoire des fichiers de tâches (par défaut, le répertoire de données du système)
+    pub data_dir: Option<PathBuf>,
+    /// Fuseau horaire des dates saisies et affichées (`system` ou nom IANA
+    /// comme `Europe/Paris`) ; les dates restent stockées en UTC
+    pub timezone: UserTimeZone,
+    /// Rétention des sauvegardes automatiques (section `[backup]`)
+    pub backup: BackupRetention,
+}
+
+impl Config {
+    /// Chemin par défaut du fichier de configuration (`~/.config/rusttaskflow/config.toml`)
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rusttaskflow").join("config.toml"))
+    }
+
+    /// Lit un fichier de configuration ; un fichier absent donne la configuration par défaut
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Impossible de lire '{}'", path.display()))?;
+        toml::from_str(&content)
+            .map_err(|e| anyhow!("Configuration invalide dans '{}': {}", path.display(), e))
+    }
+
+    /// Applique les variables `RTF_*` fournies par `lookup`
+    pub fn apply_env<F>(&mut self, lookup: F) -> Result<()>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(storage) = lookup(ENV_STORAGE) {
+            self.storage = storage.parse()?;
+        }
+        if let Some(data_dir) = lookup(ENV_DATA_DIR) {
+            self.data_dir = Some(PathBuf::from(data_dir));
+        }
+        if let Some(timezone) = lookup(ENV_TIMEZONE) {
+            self.timezone = timezone.parse()?;
+        }
+        Ok(())
+    }
+
+    /// Construit la configuration effective à partir de toutes les sources
+    pub fn resolve(
+        storage: Option<StorageType>,
+        data_dir: Option<PathBuf>,
+        timezone: Option<UserTimeZone>,
+    ) -> Result<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        let mut config = match env(ENV_CONFIG).map(PathBuf::from).or_else(Self::default_path) {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+
+        if let Some(storage) = storage {
+            config.storage = storage;
+        }
+        if let Some(data_dir) = data_dir {
+            config.data_dir = Some(data_dir);
+        }
+        if let Some(timezone) = timezone {
+            config.timezone = timezone;
+        }
+
+        Ok(config)
+    }
+
+    /// Répertoire de données effectif, créé au besoin
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        let dir = match &self.data_dir {
+            Some(dir) => dir.clone(),
+            None => dirs::data_dir()
+                .ok_or_else(|| anyhow!("Impossible de trouver le répertoire de données"))?
+                .join("rusttaskflow"),
+        };
+
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+
+        Ok(dir)
+    }
+
+    /// Chemin du fichier JSON des tâches
+    pub fn json_path(&self) -> Result<PathBuf> {
+        Ok(self.data_dir()?.join("tasks.json"))
+    }
+
+    /// Chemin de la base SQLite des tâches
+    pub fn sqlite_path(&self) -> Result<PathBuf> {
+        Ok(self.data_dir()?.join("tasks.db"))
+    }
+
+    /// Chemin du fichier du backend configuré
+    pub fn storage_path(&self) -> Result<PathBuf> {
+        match self.storage {
+            StorageType::Json => self.json_path(),
+            StorageType::Sqlite => self.sqlite_path(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_missing_file_gives_defaults() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config::from_file(&temp_dir.path().join("config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.storage, StorageType::Json);
+    }
+
+    #[test]
+    fn test_read_config_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(&path, "storage = \"sqlite\"\ndata_dir = \"/tmp/rtf\"\n").unwrap();
+
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.storage, StorageType::Sqlite);
+        assert_eq!(config.data_dir, Some(PathBuf::from("/tmp/rtf")));
+
+        fs::write(&path, "timezone = \"Europe/Paris\"\n").unwrap();
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.timezone, UserTimeZone::Named(chrono_tz::Europe::Paris));
+
+        fs::write(&path, "timezone = \"Europe/Lutece\"\n").unwrap();
+        assert!(Config::from_file(&path).is_err());
+
+        fs::write(&path, "[backup]\nkeep = 3\nmax_age_days = 30\n").unwrap();
+        let config = Config::from_file(&path).unwrap();
+        assert_eq!(config.backup.keep, Some(3));
+        assert_eq!(config.backup.max_age_days, Some(30));
+
+        fs::write(&path, "stockage = \"sqlite\"\n").unwrap();
+        assert!(Config::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = Config {
+            storage: StorageType::Sqlite,
+            data_dir: Some(PathBuf::from("/from/file")),
+            ..Config::default()
+        };
+        config
+            .apply_env(|key| match key {
+                ENV_STORAGE => Some("JSON".to_string()),
+                ENV_DATA_DIR => Some("/from/env".to_string()),
+                ENV_TIMEZONE => Some("UTC".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.storage, StorageType::Json);
+        assert_eq!(config.data_dir, Some(PathBuf::from("/from/env")));
+        assert_eq!(config.timezone, UserTimeZone::Named(chrono_tz::UTC));
+
+        assert!(config
+            .apply_env(|key| (key == ENV_STORAGE).then(|| "xml".to_string()))
+            .is_err());
+    }
+}
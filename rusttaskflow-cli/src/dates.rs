@@ -0,0 +1,230 @@
+//! Saisie des dates d'échéance
+//!
+//! En plus des formats absolus (`2024-01-15`, `2024-01-15 14:30`,
+//! `15/01/2024`, `15/01/2024 14:30`), `parse_date_at` comprend des
+//! expressions relatives : `today`, `tomorrow 9am`, `next friday`,
+//! `in 3 days`, `+2w`, `in 2 hours`, `eod`, `eow`, `eom`, `eoy`.
+//!
+//! Les dates sont interprétées dans le fuseau de `now` puis converties en
+//! UTC. Un jour sans heure vaut jusqu'à 23:59:59 ; un décalage en heures ou
+//! en minutes donne une heure exacte.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+
+/// Heure retenue pour une échéance donnée sans heure
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+fn parse_unit(value: &str) -> Option<Unit> {
+    match value {
+        "min" | "mins" | "minute" | "minutes" => Some(Unit::Minute),
+        "h" | "hour" | "hours" => Some(Unit::Hour),
+        "d" | "day" | "days" => Some(Unit::Day),
+        "w" | "week" | "weeks" => Some(Unit::Week),
+        "m" | "month" | "months" => Some(Unit::Month),
+        "y" | "year" | "years" => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+/// Décalage relatif : `in 3 days`, `in 2 hours` ou `+2w`
+fn parse_offset(words: &[&str]) -> Option<(u32, Unit)> {
+    match words {
+        ["in", count, unit] => Some((count.parse().ok()?, parse_unit(unit)?)),
+        [offset] => {
+            let offset = offset.strip_prefix('+')?;
+            let split = offset.find(|c: char| !c.is_ascii_digit())?;
+            let (count, unit) = offset.split_at(split);
+            Some((count.parse().ok()?, parse_unit(unit)?))
+        }
+        _ => None,
+    }
+}
+
+/// Heure : `14:30`, `14h`, `14h30`, `9am`, `9:30pm`
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    let (clock, meridiem) = if let Some(clock) = value.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = value.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (value, None)
+    };
+
+    let (hour, minute) = match clock.split_once([':', 'h']) {
+        Some((hour, "")) => (hour, "0"),
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute),
+        Some(_) => return None,
+        // Une heure nue n'est acceptée qu'avec am/pm
+        None if meridiem.is_some() => (clock, "0"),
+        None => return None,
+    };
+    if hour.is_empty() || !hour.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+
+    if let Some(pm) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (h, true) => h + 12,
+            (h, false) => h,
+        };
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Jour désigné par les mots restants, relatif à `today`
+fn parse_day(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    match words {
+        [] | ["today"] | ["eod"] => Some(today),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        ["yesterday"] => today.checked_sub_days(Days::new(1)),
+        // Dimanche de la semaine en cours
+        ["eow"] => today.checked_add_days(Days::new(6 - u64::from(today.weekday().num_days_from_monday()))),
+        ["eom"] => today
+            .with_day(1)?
+            .checked_add_months(Months::new(1))?
+            .checked_sub_days(Days::new(1)),
+        ["eoy"] => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        // `friday` et `next friday` désignent le prochain vendredi, jamais aujourd'hui
+        [day] | ["next", day] if day.parse::<Weekday>().is_ok() => {
+            let weekday: Weekday = day.parse().ok()?;
+            let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+            today.checked_add_days(Days::new(if ahead == 0 { 7 } else { ahead.into() }))
+        }
+        _ => match parse_offset(words) {
+            Some((count, Unit::Day)) => today.checked_add_days(Days::new(count.into())),
+            Some((count, Unit::Week)) => today.checked_add_days(Days::new(7 * u64::from(count))),
+            Some((count, Unit::Month)) => today.checked_add_months(Months::new(count)),
+            Some((count, Unit::Year)) => today.checked_add_months(Months::new(count.checked_mul(12)?)),
+            Some((_, Unit::Minute | Unit::Hour)) => None,
+            None => match words {
+                [date] => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .or_else(|_| NaiveDate::parse_from_str(date, "%d/%m/%Y"))
+                    .ok(),
+                _ => None,
+            },
+        },
+    }
+}
+
+/// Convertit une heure locale du fuseau `tz` en UTC. Une heure ambiguë
+/// (retour à l'heure d'hiver) prend la première occurrence ; une heure
+/// sautée (passage à l'heure d'été) est décalée d'une heure.
+pub(crate) fn local_to_utc<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&local.checked_add_signed(Duration::hours(1))?).earliest())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+fn parse<Tz: TimeZone>(input: &str, now: &DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    // Un décalage en heures ou en minutes part de l'instant présent
+    if let Some((count, unit @ (Unit::Minute | Unit::Hour))) = parse_offset(&words) {
+        let minutes = if unit == Unit::Hour { 60 * i64::from(count) } else { count.into() };
+        return now.with_timezone(&Utc).checked_add_signed(Duration::try_minutes(minutes)?);
+    }
+
+    let (last, rest) = words.split_last()?;
+    let (day_words, time) = match parse_time(last) {
+        Some(time) => (rest.strip_suffix(&["at"]).unwrap_or(rest), Some(time)),
+        None => (words.as_slice(), None),
+    };
+    let day = parse_day(day_words, now.date_naive())?;
+    local_to_utc(&now.timezone(), day.and_time(time.unwrap_or_else(end_of_day)))
+}
+
+/// Lit une date d'échéance, relative à `now` et dans son fuseau horaire
+pub fn parse_date_at<Tz: TimeZone>(input: &str, now: &DateTime<Tz>) -> Result<DateTime<Utc>> {
+    parse(&input.trim().to_lowercase(), now).ok_or_else(|| {
+        anyhow!(
+            "Format de date invalide: '{}'. Exemples: 2024-01-15, 2024-01-15 14:30, 15/01/2024, today, tomorrow 9am, next friday, in 3 days, +2w, eow, eom",
+            input.trim()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    /// Mercredi 15 mai 2024, 10:00 à UTC+2
+    fn now() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 5, 15, 10, 0, 0)
+            .unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn parse(input: &str) -> DateTime<Utc> {
+        parse_date_at(input, &now()).unwrap()
+    }
+
+    #[test]
+    fn test_absolute_dates_are_local() {
+        assert_eq!(parse("2024-05-01 09:00"), utc("2024-05-01T07:00:00Z"));
+        assert_eq!(parse("2024-05-01"), utc("2024-05-01T21:59:59Z"));
+        assert_eq!(parse("01/05/2024 9am"), utc("2024-05-01T07:00:00Z"));
+        assert_eq!(parse("01/05/2024"), utc("2024-05-01T21:59:59Z"));
+    }
+
+    #[test]
+    fn test_relative_days() {
+        assert_eq!(parse("today"), utc("2024-05-15T21:59:59Z"));
+        assert_eq!(parse("Tomorrow 9am"), utc("2024-05-16T07:00:00Z"));
+        assert_eq!(parse("tomorrow at 14h30"), utc("2024-05-16T12:30:00Z"));
+        assert_eq!(parse("next friday"), utc("2024-05-17T21:59:59Z"));
+        assert_eq!(parse("fri 12pm"), utc("2024-05-17T10:00:00Z"));
+        assert_eq!(parse("wednesday"), utc("2024-05-22T21:59:59Z"));
+        assert_eq!(parse("in 3 days"), utc("2024-05-18T21:59:59Z"));
+        assert_eq!(parse("+2w"), utc("2024-05-29T21:59:59Z"));
+        assert_eq!(parse("+1m"), utc("2024-06-15T21:59:59Z"));
+        assert_eq!(parse("eow"), utc("2024-05-19T21:59:59Z"));
+        assert_eq!(parse("eom"), utc("2024-05-31T21:59:59Z"));
+        assert_eq!(parse("12am"), utc("2024-05-14T22:00:00Z"));
+    }
+
+    #[test]
+    fn test_relative_times() {
+        assert_eq!(parse("in 2 hours"), utc("2024-05-15T10:00:00Z"));
+        assert_eq!(parse("+90min"), utc("2024-05-15T09:30:00Z"));
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        for input in ["", "next", "in 3", "+2", "+2q", "tomorrow 25:00", "13pm", "in 2 hours 9am", "banana"] {
+            assert!(parse_date_at(input, &now()).is_err(), "{input}");
+        }
+        // Au-delà des dates représentables
+        for input in ["in 4294967295 hours", "+4294967295h", "in 4294967295 days", "+4294967295w", "in 4294967295 years"] {
+            assert!(parse_date_at(input, &now()).is_err(), "{input}");
+        }
+        let message = parse_date_at("banana", &now()).unwrap_err().to_string();
+        assert!(message.contains("2024-01-15") && message.contains("tomorrow 9am"));
+    }
+}
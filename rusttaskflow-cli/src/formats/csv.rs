@@ -0,0 +1,357 @@
+//! Import et export CSV (RFC 4180)
+//!
+//! L'export écrit une ligne d'en-tête suivie d'une ligne par tâche ; les
+//! champs contenant des virgules, guillemets ou retours à la ligne sont
+//! entourés de guillemets. L'import se repère aux noms de colonnes, dans
+//! n'importe quel ordre : seule `Title` est obligatoire. Les tags et les
+//! dépendances (`DependsOn`) sont séparés par `;` et les dates sont au
+//! format RFC 3339. Les sessions de travail (`WorkSessions`) sont des
+//! intervalles ISO 8601 `début/fin` séparés par `;`, sans fin pour un
+//! minuteur en cours.
+
+use super::{ParsedTasks, RowError};
+use crate::task::{new_task, Priority, Task, TaskStatus, WorkSession, LOCAL_USER};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use uuid::Uuid;
+
+/// Colonnes écrites par l'export, dans l'ordre
+pub const HEADERS: &[&str] = &[
+    "ID",
+    "Title",
+    "Description",
+    "Status",
+    "Priority",
+    "Tags",
+    "Created",
+    "Updated",
+    "Due",
+    "Started",
+    "Completed",
+    "Cancelled",
+    "Reopened",
+    "AssignedTo",
+    "CreatedBy",
+    "Parent",
+    "DependsOn",
+    "Recurrence",
+    "Series",
+    "WorkSessions",
+];
+
+fn format_date(date: Option<DateTime<Utc>>) -> String {
+    date.map(|d| d.to_rfc3339()).unwrap_or_default()
+}
+
+/// Écrit les tâches en CSV, triées par date de création
+pub fn write_tasks<W: Write>(tasks: &[&Task], writer: W) -> Result<()> {
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|t| t.created_at);
+
+    let mut csv = ::csv::Writer::from_writer(writer);
+    csv.write_record(HEADERS)?;
+
+    for task in tasks {
+        csv.write_record([
+            task.id.to_string(),
+            task.title.clone(),
+            task.description.clone().unwrap_or_default(),
+            task.status.as_str().to_string(),
+            task.priority.as_str().to_string(),
+            task.tags.join(";"),
+            task.created_at.to_rfc3339(),
+            task.updated_at.to_rfc3339(),
+            format_date(task.due_date),
+            format_date(task.started_at),
+            format_date(task.completed_at),
+            format_date(task.cancelled_at),
+            format_date(task.reopened_at),
+            task.assigned_to.map(|id| id.to_string()).unwrap_or_default(),
+            task.created_by.to_string(),
+            task.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+            task.depends_on.iter().map(Uuid::to_string).collect::<Vec<_>>().join(";"),
+            task.recurrence.as_ref().map(|r| r.to_string()).unwrap_or_default(),
+            task.series_id.map(|id| id.to_string()).unwrap_or_default(),
+            task.work_sessions
+                .iter()
+                .map(|s| format!("{}/{}", s.started_at.to_rfc3339(), format_date(s.ended_at)))
+                .collect::<Vec<_>>()
+                .join(";"),
+        ])?;
+    }
+
+    csv.flush()?;
+    Ok(())
+}
+
+/// Position des colonnes reconnues dans l'en-tête
+struct Columns(HashMap<&'static str, usize>);
+
+impl Columns {
+    fn from_headers(headers: &::csv::StringRecord) -> Result<Self> {
+        let mut columns = HashMap::new();
+        for (index, header) in headers.iter().enumerate() {
+            let header = header.trim();
+            // `TaskStatus` : en-tête écrit à tort par les builds de développement
+            // entre le passage au modèle de rusttaskflow-core et la refonte des
+            // formats d'import/export ; les versions publiées écrivent `Status`
+            let name = if header.eq_ignore_ascii_case("TaskStatus") {
+                Some("Status")
+            } else {
+                HEADERS.iter().copied().find(|h| h.eq_ignore_ascii_case(header))
+            };
+            if let Some(name) = name {
+                columns.insert(name, index);
+            }
+        }
+
+        if !columns.contains_key("Title") {
+            return Err(anyhow!("Colonne 'Title' absente de l'en-tête CSV"));
+        }
+        Ok(Self(columns))
+    }
+
+    /// Valeur non vide d'une colonne
+    fn get<'r>(&self, record: &'r ::csv::StringRecord, name: &str) -> Option<&'r str> {
+        self.0
+            .get(name)
+            .and_then(|&index| record.get(index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+fn parse_date(column: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map(|d| d.with_timezone(&Utc))
+                .map_err(|_| anyhow!("date invalide dans '{}': {}", column, v))
+        })
+        .transpose()
+}
+
+/// Lit une session `début/fin` ; la fin est vide si le minuteur tourne
+fn parse_work_session(value: &str) -> Result<WorkSession> {
+    let (start, end) = value
+        .split_once('/')
+        .ok_or_else(|| anyhow!("session de travail invalide: {}", value))?;
+    Ok(WorkSession {
+        started_at: parse_date("WorkSessions", Some(start))?
+            .ok_or_else(|| anyhow!("session de travail invalide: {}", value))?,
+        ended_at: parse_date("WorkSessions", Some(end).filter(|e| !e.is_empty()))?,
+    })
+}
+
+fn parse_uuid(column: &str, value: Option<&str>) -> Result<Option<Uuid>> {
+    value
+        .map(|v| Uuid::parse_str(v).map_err(|_| anyhow!("UUID invalide dans '{}': {}", column, v)))
+        .transpose()
+}
+
+/// Accepte les identifiants stables (`InProgress`) et les libellés affichés (`En cours`)
+fn parse_status(value: &str) -> Result<TaskStatus> {
+    [
+        TaskStatus::Todo,
+        TaskStatus::InProgress,
+        TaskStatus::Completed,
+        TaskStatus::Cancelled,
+    ]
+    .into_iter()
+    .find(|s| s.as_str().eq_ignore_ascii_case(value) || s.to_string() == value)
+    .ok_or_else(|| anyhow!("statut inconnu: {}", value))
+}
+
+fn parse_priority(value: &str) -> Result<Priority> {
+    [Priority::Low, Priority::Medium, Priority::High, Priority::Critical]
+        .into_iter()
+        .find(|p| p.as_str().eq_ignore_ascii_case(value) || p.to_string() == value)
+        .ok_or_else(|| anyhow!("priorité inconnue: {}", value))
+}
+
+fn task_from_record(columns: &Columns, record: &::csv::StringRecord) -> Result<Task> {
+    let title = columns
+        .get(record, "Title")
+        .ok_or_else(|| anyhow!("titre vide"))?;
+
+    let mut task = new_task(title.to_string());
+    if let Some(id) = parse_uuid("ID", columns.get(record, "ID"))? {
+        task.id = id;
+    }
+    task.description = columns.get(record, "Description").map(str::to_string);
+    if let Some(status) = columns.get(record, "Status") {
+        task.status = parse_status(status)?;
+    }
+    if let Some(priority) = columns.get(record, "Priority") {
+        task.priority = parse_priority(priority)?;
+    }
+    task.tags = columns
+        .get(record, "Tags")
+        .map(|tags| {
+            tags.split(';')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if let Some(created_at) = parse_date("Created", columns.get(record, "Created"))? {
+        task.created_at = created_at;
+        task.updated_at = created_at;
+    }
+    if let Some(updated_at) = parse_date("Updated", columns.get(record, "Updated"))? {
+        task.updated_at = updated_at;
+    }
+    task.due_date = parse_date("Due", columns.get(record, "Due"))?;
+    task.started_at = parse_date("Started", columns.get(record, "Started"))?;
+    task.completed_at = parse_date("Completed", columns.get(record, "Completed"))?;
+    task.cancelled_at = parse_date("Cancelled", columns.get(record, "Cancelled"))?;
+    task.reopened_at = parse_date("Reopened", columns.get(record, "Reopened"))?;
+    task.assigned_to = parse_uuid("AssignedTo", columns.get(record, "AssignedTo"))?;
+    task.created_by = parse_uuid("CreatedBy", columns.get(record, "CreatedBy"))?.unwrap_or(LOCAL_USER);
+    task.parent_id = parse_uuid("Parent", columns.get(record, "Parent"))?;
+    task.depends_on = columns
+        .get(record, "DependsOn")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .filter_map(|id| parse_uuid("DependsOn", Some(id)).transpose())
+        .collect::<Result<_>>()?;
+    task.recurrence = columns
+        .get(record, "Recurrence")
+        .map(|r| r.parse().map_err(|_| anyhow!("récurrence invalide: {}", r)))
+        .transpose()?;
+    task.series_id = parse_uuid("Series", columns.get(record, "Series"))?;
+    task.work_sessions = columns
+        .get(record, "WorkSessions")
+        .unwrap_or_default()
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(parse_work_session)
+        .collect::<Result<_>>()?;
+
+    Ok(task)
+}
+
+/// Lit des tâches depuis un CSV avec en-tête ; une ligne invalide est
+/// signalée dans `errors` sans empêcher la lecture des suivantes
+pub fn read_tasks<R: Read>(reader: R) -> Result<ParsedTasks> {
+    let mut csv = ::csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let columns = Columns::from_headers(csv.headers()?)?;
+    let mut parsed = ParsedTasks::default();
+
+    for result in csv.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                parsed.errors.push(RowError {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line());
+
+        match task_from_record(&columns, &record) {
+            Ok(task) if parsed.tasks.contains_key(&task.id) => parsed.errors.push(RowError {
+                line,
+                message: format!("ID en double: {}", task.id),
+            }),
+            Ok(task) => {
+                parsed.tasks.insert(task.id, task);
+            }
+            Err(e) => parsed.errors.push(RowError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::task_checksum;
+
+    #[test]
+    fn test_round_trip_with_special_characters() {
+        let mut task = new_task("Appeler Dupont, \"urgent\"".to_string());
+        task.set_description(Some("Ligne 1\nLigne 2, avec virgule".to_string()));
+        task.add_tag("travail".to_string());
+        task.add_tag("client".to_string());
+        task.set_due_date(Some(Utc::now()));
+        task.start().unwrap();
+        let other = new_task("Simple".to_string());
+        task.parent_id = Some(other.id);
+        task.depends_on = vec![other.id, Uuid::new_v4()];
+        task.recurrence = Some("every 2 weeks on mon,thu".parse().unwrap());
+        task.series_id = Some(task.id);
+        task.log_time(chrono::Duration::minutes(45), Utc::now()).unwrap();
+        task.start_timer(Utc::now()).unwrap();
+
+        let mut buffer = Vec::new();
+        write_tasks(&[&task, &other], &mut buffer).unwrap();
+
+        let parsed = read_tasks(buffer.as_slice()).unwrap();
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.tasks.len(), 2);
+        // RFC 3339 garde la précision à la nanoseconde : la tâche est identique
+        assert_eq!(
+            task_checksum(&parsed.tasks[&task.id]).unwrap(),
+            task_checksum(&task).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_export_header() {
+        let mut buffer = Vec::new();
+        write_tasks(&[&new_task("Seule".to_string())], &mut buffer).unwrap();
+        let csv = String::from_utf8(buffer).unwrap();
+        assert!(csv.starts_with("ID,Title,Description,Status,Priority,Tags,Created,Updated,Due,"));
+    }
+
+    #[test]
+    fn test_header_driven_import() {
+        let csv = "priority,title,tags\nHigh,Première,a; b\n,Seconde,\n";
+        let parsed = read_tasks(csv.as_bytes()).unwrap();
+        assert!(parsed.errors.is_empty());
+
+        let mut tasks: Vec<&Task> = parsed.tasks.values().collect();
+        tasks.sort_by_key(|t| t.title.clone());
+        assert_eq!(tasks[0].title, "Première");
+        assert_eq!(tasks[0].priority, Priority::High);
+        assert_eq!(tasks[0].tags, vec!["a", "b"]);
+        assert_eq!(tasks[1].priority, Priority::Medium);
+
+        assert!(read_tasks("id,description\n1,x\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_bad_rows_are_reported() {
+        let id = Uuid::new_v4();
+        let csv = format!(
+            "ID,Title,Status,Due\n\
+             {id},Valide,Todo,\n\
+             ,Statut,Ailleurs,\n\
+             ,Date,Todo,demain\n\
+             ,,Todo,\n\
+             {id},Doublon,Todo,\n\
+             ,Ancien format,En cours,\n"
+        );
+        let parsed = read_tasks(csv.as_bytes()).unwrap();
+
+        assert_eq!(parsed.tasks.len(), 2);
+        let lines: Vec<u64> = parsed.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![3, 4, 5, 6]);
+        assert!(parsed.errors[0].to_string().contains("statut inconnu"));
+    }
+}
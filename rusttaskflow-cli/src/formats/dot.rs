@@ -0,0 +1,112 @@
+//! Export du graphe des dépendances au format DOT (Graphviz)
+//!
+//! Export seul : `rtf graph | dot -Tsvg > graphe.svg`. Seules les tâches
+//! reliées par une dépendance apparaissent. Une flèche va de la tâche à
+//! terminer d'abord vers la tâche qui en dépend ; les tâches bloquées sont
+//! entourées de rouge.
+
+use crate::task::{Task, TaskStatus};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use uuid::Uuid;
+
+/// Échappe une chaîne entre guillemets DOT
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn short_id(id: &Uuid) -> String {
+    id.to_string()[..8].to_uppercase()
+}
+
+fn is_open(task: &Task) -> bool {
+    matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress)
+}
+
+/// Attributs d'un nœud selon le statut de la tâche
+fn node_style(task: &Task, blocked: bool) -> &'static str {
+    match task.status {
+        TaskStatus::Completed => r#"style=filled, fillcolor="palegreen""#,
+        TaskStatus::Cancelled => r#"style=dashed, fontcolor="gray50""#,
+        TaskStatus::InProgress if blocked => r#"style=filled, fillcolor="lightblue", color="red", penwidth=2"#,
+        TaskStatus::InProgress => r#"style=filled, fillcolor="lightblue""#,
+        TaskStatus::Todo if blocked => r#"color="red", penwidth=2"#,
+        TaskStatus::Todo => "",
+    }
+}
+
+/// Écrit le graphe des dépendances entre les tâches données ; les
+/// dépendances vers des tâches absentes sont ignorées
+pub fn write_graph<W: Write>(tasks: &[&Task], mut writer: W) -> Result<()> {
+    let by_id: HashMap<Uuid, &Task> = tasks.iter().map(|t| (t.id, *t)).collect();
+    let mut edges: Vec<(&Task, &Task)> = Vec::new();
+    for task in tasks {
+        for dependency in task.depends_on.iter().filter_map(|d| by_id.get(d)) {
+            edges.push((dependency, task));
+        }
+    }
+    edges.sort_by_key(|(from, to)| (from.created_at, from.id, to.created_at, to.id));
+
+    let linked: HashSet<Uuid> = edges.iter().flat_map(|(from, to)| [from.id, to.id]).collect();
+    let blocked: HashSet<Uuid> = edges
+        .iter()
+        .filter(|(from, to)| is_open(from) && is_open(to))
+        .map(|(_, to)| to.id)
+        .collect();
+    let mut nodes: Vec<&Task> = tasks.iter().copied().filter(|t| linked.contains(&t.id)).collect();
+    nodes.sort_by_key(|t| (t.created_at, t.id));
+
+    writeln!(writer, "digraph dependances {{")?;
+    writeln!(writer, "    rankdir=LR;")?;
+    writeln!(writer, "    node [shape=box, fontname=\"Helvetica\"];")?;
+    for task in &nodes {
+        let style = node_style(task, blocked.contains(&task.id));
+        writeln!(
+            writer,
+            "    \"{}\" [label=\"{}\\n{}\"{}{}];",
+            task.id,
+            short_id(&task.id),
+            escape(&task.title),
+            if style.is_empty() { "" } else { ", " },
+            style
+        )?;
+    }
+    for (from, to) in &edges {
+        writeln!(writer, "    \"{}\" -> \"{}\";", from.id, to.id)?;
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::new_task;
+
+    #[test]
+    fn test_write_graph() {
+        let mut design = new_task("Maquette \"v2\"".to_string());
+        design.complete().unwrap();
+        let mut build = new_task("Développer".to_string());
+        let mut release = new_task("Publier".to_string());
+        let alone = new_task("Sans lien".to_string());
+        build.depends_on.push(design.id);
+        release.depends_on.push(build.id);
+        release.depends_on.push(Uuid::new_v4());
+
+        let mut buffer = Vec::new();
+        write_graph(&[&release, &alone, &build, &design], &mut buffer).unwrap();
+        let dot = String::from_utf8(buffer).unwrap();
+
+        let (d, b, r) = (design.id, build.id, release.id);
+        assert!(dot.starts_with("digraph dependances {"));
+        assert!(dot.contains(&format!("\"{d}\" [label=\"{}\\nMaquette \\\"v2\\\"\", style=filled", short_id(&d))));
+        assert!(dot.contains(&format!("\"{b}\" [label=\"{}\\nDévelopper\"];", short_id(&b))));
+        assert!(dot.contains(&format!("\"{r}\" [label=\"{}\\nPublier\", color=\"red\", penwidth=2];", short_id(&r))));
+        assert!(dot.contains(&format!("\"{d}\" -> \"{b}\";")));
+        assert!(dot.contains(&format!("\"{b}\" -> \"{r}\";")));
+        assert_eq!(dot.matches("->").count(), 2);
+        assert!(!dot.contains("Sans lien"));
+    }
+}
@@ -0,0 +1,351 @@
+//! Import et export iCalendar (RFC 5545, composants VTODO)
+//!
+//! L'export est celui de `rusttaskflow_core::ical`, partagé avec le flux
+//! `.ics` du serveur web. L'import lit les VTODO d'un fichier `.ics` et
+//! ignore les autres composants (VEVENT, VTIMEZONE…). Comme pour les dates
+//! saisies en ligne de commande, les heures sans `Z` sont prises en UTC.
+
+use super::{ParsedTasks, RowError};
+use crate::task::{new_task, Priority, Task, TaskStatus};
+use crate::timezone::UserTimeZone;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusttaskflow_core::ical;
+use std::io::Write;
+use uuid::Uuid;
+
+/// Écrit les tâches dans un calendrier iCalendar
+pub fn write_tasks<W: Write>(tasks: &[&Task], mut writer: W) -> Result<()> {
+    writer.write_all(ical::write_calendar(tasks, "RustTaskFlow").as_bytes())?;
+    Ok(())
+}
+
+/// Propriété d'une ligne de contenu : nom, paramètres bruts (`RELTYPE=PARENT`),
+/// valeur brute
+struct Property {
+    name: String,
+    params: Vec<String>,
+    value: String,
+}
+
+impl Property {
+    /// Valeur d'un paramètre, sans ses guillemets éventuels
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"'))
+        })
+    }
+}
+
+/// Déplie les lignes repliées et renvoie chaque ligne logique avec son numéro
+fn unfold(content: &str) -> Vec<(u64, String)> {
+    let mut lines: Vec<(u64, String)> = Vec::new();
+    for (number, line) in (1..).zip(content.lines()) {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, previous))) => previous.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push((number, line.to_string())),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // Le premier `:` hors guillemets sépare le nom (et ses paramètres) de la valeur
+    let mut quoted = false;
+    let split = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == ':' && !quoted
+    })?;
+    let (head, value) = (&line[..split.0], &line[split.0 + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    Some(Property {
+        name,
+        params: parts.map(str::to_string).collect(),
+        value: value.to_string(),
+    })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+/// Découpe une liste de valeurs TEXT sur les virgules non échappées
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    items.push(current);
+    items
+        .iter()
+        .map(|item| unescape_text(item).trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Lit une date UTC (`…Z`) ; une heure « flottante » sans `Z` et une date
+/// seule sont dans le fuseau de l'utilisateur (RFC 5545, 3.3.5)
+fn parse_date(name: &str, value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    let local = match value.strip_suffix('Z') {
+        Some(utc) => {
+            if let Ok(date_time) = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S") {
+                return Ok(date_time.and_utc());
+            }
+            None
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            // Une date seule vaut jusqu'à la fin de la journée, comme pour `rtf add --due`
+            .or_else(|| NaiveDate::parse_from_str(value, "%Y%m%d").ok()?.and_hms_opt(23, 59, 59)),
+    };
+    local
+        .and_then(|local| crate::dates::local_to_utc(&UserTimeZone::current(), local))
+        .ok_or_else(|| anyhow!("date invalide dans {}: {}", name, value))
+}
+
+fn parse_priority(value: &str) -> Result<Priority> {
+    match value.trim().parse::<u8>() {
+        Ok(1) => Ok(Priority::Critical),
+        Ok(2..=4) => Ok(Priority::High),
+        Ok(0) | Ok(5) => Ok(Priority::Medium),
+        Ok(6..=9) => Ok(Priority::Low),
+        _ => Err(anyhow!("priorité invalide: {}", value)),
+    }
+}
+
+/// Les UID d'autres applications donnent toujours le même UUID, pour qu'un
+/// nouvel import du même fichier fusionne au lieu de dupliquer
+fn uid_to_uuid(uid: &str) -> Uuid {
+    Uuid::parse_str(uid.trim())
+        .unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_OID, uid.trim().as_bytes()))
+}
+
+fn task_from_properties(properties: &[Property]) -> Result<Task> {
+    let get = |name: &str| properties.iter().find(|p| p.name == name).map(|p| p.value.as_str());
+
+    let title = get("SUMMARY")
+        .map(unescape_text)
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| anyhow!("SUMMARY absent"))?;
+    let mut task = new_task(title);
+
+    if let Some(uid) = get("UID") {
+        task.id = uid_to_uuid(uid);
+    }
+    // RELTYPE vaut PARENT par défaut ; DEPENDS-ON vient de RFC 9253 et les
+    // autres relations (CHILD, SIBLING…) sont ignorées
+    for related in properties.iter().filter(|p| p.name == "RELATED-TO") {
+        let reltype = related.param("RELTYPE").unwrap_or("PARENT").to_uppercase();
+        match reltype.as_str() {
+            "PARENT" if task.parent_id.is_none() => task.parent_id = Some(uid_to_uuid(&related.value)),
+            "DEPENDS-ON" => task.depends_on.push(uid_to_uuid(&related.value)),
+            _ => {}
+        }
+    }
+    task.description = get("DESCRIPTION").map(unescape_text).filter(|d| !d.is_empty());
+    if let Some(priority) = get("PRIORITY") {
+        task.priority = parse_priority(priority)?;
+    }
+    task.tags = properties
+        .iter()
+        .filter(|p| p.name == "CATEGORIES")
+        .flat_map(|p| split_text_list(&p.value))
+        .collect();
+
+    if let Some(created) = get("CREATED") {
+        task.created_at = parse_date("CREATED", created)?;
+    }
+    task.updated_at = match get("LAST-MODIFIED") {
+        Some(modified) => parse_date("LAST-MODIFIED", modified)?,
+        None => task.created_at,
+    };
+    task.due_date = get("DUE").map(|d| parse_date("DUE", d)).transpose()?;
+
+    match get("STATUS").map(|s| s.trim().to_uppercase()).as_deref() {
+        None | Some("NEEDS-ACTION") => task.status = TaskStatus::Todo,
+        Some("IN-PROCESS") => {
+            task.status = TaskStatus::InProgress;
+            task.started_at = get("DTSTART").map(|d| parse_date("DTSTART", d)).transpose()?;
+        }
+        Some("COMPLETED") => {
+            task.status = TaskStatus::Completed;
+            task.completed_at = match get("COMPLETED") {
+                Some(completed) => Some(parse_date("COMPLETED", completed)?),
+                None => Some(task.updated_at),
+            };
+        }
+        Some("CANCELLED") => {
+            task.status = TaskStatus::Cancelled;
+            task.cancelled_at = Some(task.updated_at);
+        }
+        Some(other) => return Err(anyhow!("STATUS inconnu: {}", other)),
+    }
+
+    Ok(task)
+}
+
+/// Lit les VTODO d'un calendrier ; un VTODO invalide est signalé avec le
+/// numéro de sa ligne `BEGIN:VTODO`
+pub fn read_tasks(content: &str) -> Result<ParsedTasks> {
+    let lines = unfold(content);
+    if !lines
+        .first()
+        .is_some_and(|(_, l)| l.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err(anyhow!("BEGIN:VCALENDAR attendu en début de fichier"));
+    }
+
+    let mut parsed = ParsedTasks::default();
+    let mut current: Option<(u64, Vec<Property>)> = None;
+
+    for (number, line) in lines {
+        let Some(property) = parse_property(&line) else {
+            if current.is_some() {
+                parsed.errors.push(RowError {
+                    line: number,
+                    message: format!("ligne de contenu invalide: {}", line),
+                });
+            }
+            continue;
+        };
+        let value = property.value.trim().to_uppercase();
+
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VTODO" => current = Some((number, Vec::new())),
+            ("END", Some(_)) if value == "VTODO" => {
+                let (start, properties) = current.take().unwrap();
+                match task_from_properties(&properties) {
+                    Ok(task) if parsed.tasks.contains_key(&task.id) => parsed.errors.push(RowError {
+                        line: start,
+                        message: format!("UID en double: {}", task.id),
+                    }),
+                    Ok(task) => {
+                        parsed.tasks.insert(task.id, task);
+                    }
+                    Err(e) => parsed.errors.push(RowError {
+                        line: start,
+                        message: e.to_string(),
+                    }),
+                }
+            }
+            (_, Some((_, properties))) => properties.push(property),
+            _ => {}
+        }
+    }
+
+    if let Some((start, _)) = current {
+        parsed.errors.push(RowError {
+            line: start,
+            message: "VTODO non terminé (END:VTODO absent)".to_string(),
+        });
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::task_checksum;
+    use chrono::Timelike;
+
+    /// Les dates iCalendar sont à la seconde
+    fn truncate(date: DateTime<Utc>) -> DateTime<Utc> {
+        date.with_nanosecond(0).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut task =
+            new_task("Réunion; budget, « Q1 » avec un titre assez long pour être replié".to_string());
+        task.set_description(Some("Ordre du jour:\n1. chiffres\\bilan".to_string()));
+        task.set_priority(Priority::Critical);
+        task.add_tag("travail".to_string());
+        task.add_tag("a,b".to_string());
+        task.set_due_date(Some(Utc::now()));
+        task.complete().unwrap();
+        task.parent_id = Some(Uuid::new_v4());
+        task.depends_on = vec![Uuid::new_v4(), Uuid::new_v4()];
+        task.created_at = truncate(task.created_at);
+        task.updated_at = truncate(task.updated_at);
+        task.due_date = task.due_date.map(truncate);
+        task.completed_at = task.completed_at.map(truncate);
+
+        let mut buffer = Vec::new();
+        write_tasks(&[&task], &mut buffer).unwrap();
+        let parsed = read_tasks(std::str::from_utf8(&buffer).unwrap()).unwrap();
+
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert_eq!(
+            task_checksum(&parsed.tasks[&task.id]).unwrap(),
+            task_checksum(&task).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_foreign_calendar() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   VERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:event-1\r\n\
+                   SUMMARY:Pas une tâche\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VTODO\r\n\
+                   UID:todo-1@example.com\r\n\
+                   SUMMARY:Acheter du \r\n \x20pain\r\n\
+                   DUE;VALUE=DATE:20240120\r\n\
+                   PRIORITY:7\r\n\
+                   CATEGORIES:maison,courses\r\n\
+                   RELATED-TO;RELTYPE=CHILD:todo-3@example.com\r\n\
+                   RELATED-TO:todo-0@example.com\r\n\
+                   END:VTODO\r\n\
+                   BEGIN:VTODO\r\n\
+                   UID:todo-2@example.com\r\n\
+                   STATUS:NEEDS-ACTION\r\n\
+                   END:VTODO\r\n\
+                   END:VCALENDAR\r\n";
+        let parsed = read_tasks(ics).unwrap();
+
+        assert_eq!(parsed.tasks.len(), 1);
+        let task = parsed.tasks.values().next().unwrap();
+        assert_eq!(task.title, "Acheter du  pain");
+        assert_eq!(task.priority, Priority::Low);
+        assert_eq!(task.tags, vec!["maison", "courses"]);
+        assert_eq!(crate::formats::local_time(task.due_date.unwrap()), "2024-01-20 23:59:59");
+        assert_eq!(task.id, Uuid::new_v5(&Uuid::NAMESPACE_OID, b"todo-1@example.com"));
+        assert_eq!(
+            task.parent_id,
+            Some(Uuid::new_v5(&Uuid::NAMESPACE_OID, b"todo-0@example.com"))
+        );
+
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line, 17);
+        assert!(read_tasks("BEGIN:VTODO\r\n").is_err());
+    }
+}
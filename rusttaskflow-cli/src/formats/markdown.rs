@@ -0,0 +1,350 @@
+//! Import et export de listes de cases à cocher Markdown
+//!
+//! Chaque tâche est une case à cocher (`- [ ]`, `- [x]`, `- [/]` en cours,
+//! `- [-]` annulée) suivie de ses métadonnées, selon les conventions du
+//! plugin Tasks d'Obsidian : priorité (🔺 ⏫ 🔽), échéance `📅 AAAA-MM-JJ`,
+//! dates de création `➕`, de fin `✅` et d'annulation `❌`, tags `#tag` et
+//! identifiant `🆔`. Les lignes indentées sous une tâche forment sa
+//! description. Les titres de section ne servent qu'à la lecture et sont
+//! ignorés à l'import.
+
+use super::{format_day, parse_day, ParsedTasks, RowError};
+use crate::task::{new_task, Priority, Task, TaskStatus};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
+use std::io::Write;
+use uuid::Uuid;
+
+const DUE: &str = "📅";
+const CREATED: &str = "➕";
+const DONE: &str = "✅";
+const CANCELLED: &str = "❌";
+const ID: &str = "🆔";
+
+/// Regroupement des tâches en sections
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GroupBy {
+    /// Une section par statut
+    #[default]
+    Status,
+    /// Une section par premier tag, les tâches sans tag à la fin
+    Tag,
+}
+
+fn checkbox(status: &TaskStatus) -> char {
+    match status {
+        TaskStatus::Todo => ' ',
+        TaskStatus::InProgress => '/',
+        TaskStatus::Completed => 'x',
+        TaskStatus::Cancelled => '-',
+    }
+}
+
+/// Marqueur de priorité ; la priorité moyenne, par défaut, n'en a pas
+fn priority_marker(priority: &Priority) -> Option<&'static str> {
+    match priority {
+        Priority::Critical => Some("🔺"),
+        Priority::High => Some("⏫"),
+        Priority::Medium => None,
+        Priority::Low => Some("🔽"),
+    }
+}
+
+fn parse_priority_marker(word: &str) -> Option<Priority> {
+    match word {
+        "🔺" => Some(Priority::Critical),
+        "⏫" => Some(Priority::High),
+        "🔼" => Some(Priority::Medium),
+        "🔽" | "⏬" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+/// Un tag Markdown s'arrête au premier espace
+fn tag_word(tag: &str) -> String {
+    tag.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn write_task<W: Write>(writer: &mut W, task: &Task) -> Result<()> {
+    let mut line = format!("- [{}] {}", checkbox(&task.status), task.title.trim());
+    if let Some(marker) = priority_marker(&task.priority) {
+        line.push_str(&format!(" {}", marker));
+    }
+    if let Some(due) = task.due_date {
+        line.push_str(&format!(" {} {}", DUE, format_day(due)));
+    }
+    line.push_str(&format!(" {} {}", CREATED, format_day(task.created_at)));
+    if let Some(completed_at) = task.completed_at.filter(|_| task.status == TaskStatus::Completed) {
+        line.push_str(&format!(" {} {}", DONE, format_day(completed_at)));
+    }
+    if let Some(cancelled_at) = task.cancelled_at.filter(|_| task.status == TaskStatus::Cancelled) {
+        line.push_str(&format!(" {} {}", CANCELLED, format_day(cancelled_at)));
+    }
+    for tag in &task.tags {
+        line.push_str(&format!(" #{}", tag_word(tag)));
+    }
+    line.push_str(&format!(" {} {}", ID, task.id));
+    writeln!(writer, "{}", line)?;
+
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        for description_line in description.trim_end().lines() {
+            writeln!(writer, "  {}", description_line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Écrit une liste de cases à cocher, triée par date de création dans chaque section
+pub fn write_tasks<W: Write>(tasks: &[&Task], group_by: GroupBy, mut writer: W) -> Result<()> {
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|t| t.created_at);
+
+    let groups: Vec<(String, Vec<&Task>)> = match group_by {
+        GroupBy::Status => [
+            TaskStatus::Todo,
+            TaskStatus::InProgress,
+            TaskStatus::Completed,
+            TaskStatus::Cancelled,
+        ]
+        .iter()
+        .map(|status| {
+            let tasks = tasks.iter().copied().filter(|t| &t.status == status).collect();
+            (status.to_string(), tasks)
+        })
+        .collect(),
+        GroupBy::Tag => {
+            let mut by_tag: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+            let mut untagged = Vec::new();
+            for task in &tasks {
+                match task.tags.first() {
+                    Some(tag) => by_tag.entry(format!("#{}", tag_word(tag))).or_default().push(task),
+                    None => untagged.push(*task),
+                }
+            }
+            by_tag.into_iter().chain([("Sans tag".to_string(), untagged)]).collect()
+        }
+    };
+
+    writeln!(writer, "# Tâches")?;
+    for (heading, tasks) in groups.into_iter().filter(|(_, tasks)| !tasks.is_empty()) {
+        writeln!(writer)?;
+        writeln!(writer, "## {}", heading)?;
+        writeln!(writer)?;
+        for task in tasks {
+            write_task(&mut writer, task)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reconnaît une case à cocher (`- [x] texte` ou `* [x] texte`), même indentée
+fn parse_item(line: &str) -> Option<(char, &str)> {
+    let trimmed = line.trim_start();
+    let rest = trimmed
+        .strip_prefix("- [")
+        .or_else(|| trimmed.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let state = chars.next()?;
+    let text = chars.as_str().strip_prefix("] ")?;
+    Some((state, text))
+}
+
+fn task_from_item(state: char, text: &str) -> Result<Task> {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let (mut due, mut created, mut done, mut cancelled, mut id) = (None, None, None, None, None);
+    let mut priority = None;
+    let mut tags = Vec::new();
+
+    // Les métadonnées sont en fin de ligne : on les retire en partant de la fin
+    while let Some(&last) = words.last() {
+        let marker = words.len().checked_sub(2).map_or("", |i| words[i]);
+        match marker {
+            DUE => due = Some(parse_day(last, true)?),
+            CREATED => created = Some(parse_day(last, false)?),
+            DONE => done = Some(parse_day(last, false)?),
+            CANCELLED => cancelled = Some(parse_day(last, false)?),
+            ID => id = Some(Uuid::parse_str(last).map_err(|_| anyhow!("UUID invalide: {}", last))?),
+            _ => {
+                // Comme dans Obsidian, `#42` n'est pas un tag : un titre peut finir par un numéro
+                if let Some(tag) = last
+                    .strip_prefix('#')
+                    .filter(|t| !t.chars().all(|c| c.is_ascii_digit()))
+                {
+                    tags.insert(0, tag.to_string());
+                } else if let Some(marker_priority) = parse_priority_marker(last) {
+                    priority = Some(marker_priority);
+                } else {
+                    break;
+                }
+                words.pop();
+                continue;
+            }
+        }
+        words.truncate(words.len() - 2);
+    }
+
+    if words.is_empty() {
+        return Err(anyhow!("titre vide"));
+    }
+
+    let mut task = new_task(words.join(" "));
+    if let Some(id) = id {
+        task.id = id;
+    }
+    task.priority = priority.unwrap_or_default();
+    task.tags = tags;
+    task.created_at = created.unwrap_or_else(Utc::now);
+    task.updated_at = task.created_at;
+    task.due_date = due;
+
+    match state {
+        ' ' => task.status = TaskStatus::Todo,
+        '/' => {
+            task.status = TaskStatus::InProgress;
+            task.started_at = Some(task.updated_at);
+        }
+        'x' | 'X' => {
+            task.status = TaskStatus::Completed;
+            task.completed_at = done.or(Some(task.updated_at));
+        }
+        '-' => {
+            task.status = TaskStatus::Cancelled;
+            task.cancelled_at = cancelled.or(Some(task.updated_at));
+        }
+        other => return Err(anyhow!("case à cocher inconnue: [{}]", other)),
+    }
+
+    Ok(task)
+}
+
+/// Tâche en cours de lecture : ligne de la case, tâche et lignes de description
+type Item = (u64, Task, Vec<String>);
+
+fn finish_item(parsed: &mut ParsedTasks, item: Option<Item>) {
+    let Some((line, mut task, description)) = item else {
+        return;
+    };
+    let description = description.join("\n").trim_end().to_string();
+    task.description = Some(description).filter(|d| !d.is_empty());
+
+    match parsed.tasks.entry(task.id) {
+        Entry::Occupied(_) => parsed.errors.push(RowError {
+            line,
+            message: format!("ID en double: {}", task.id),
+        }),
+        Entry::Vacant(entry) => {
+            entry.insert(task);
+        }
+    }
+}
+
+/// Lit les cases à cocher d'un document Markdown ; le reste du texte est ignoré
+pub fn read_tasks(content: &str) -> ParsedTasks {
+    let mut parsed = ParsedTasks::default();
+    let mut current: Option<Item> = None;
+
+    for (number, line) in (1..).zip(content.lines()) {
+        if let Some((state, text)) = parse_item(line) {
+            finish_item(&mut parsed, current.take());
+            match task_from_item(state, text) {
+                Ok(task) => current = Some((number, task, Vec::new())),
+                Err(e) => parsed.errors.push(RowError {
+                    line: number,
+                    message: e.to_string(),
+                }),
+            }
+            continue;
+        }
+
+        let indented = line.strip_prefix("  ").or_else(|| line.strip_prefix('\t'));
+        match (current.as_mut(), indented) {
+            (Some((_, _, description)), Some(text)) => description.push(text.to_string()),
+            _ => finish_item(&mut parsed, current.take()),
+        }
+    }
+    finish_item(&mut parsed, current);
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::reconcile_plain_text;
+    use crate::storage::task_checksum;
+    use std::collections::HashMap;
+
+    fn sample() -> HashMap<Uuid, Task> {
+        let mut report = new_task("Écrire le rapport #1".to_string());
+        report.set_priority(Priority::High);
+        report.set_description(Some("Plan:\n- intro\n\n- chiffres".to_string()));
+        report.add_tag("travail".to_string());
+        report.set_due_date(Some(Utc::now()));
+        report.start().unwrap();
+
+        let mut bill = new_task("Payer la facture".to_string());
+        bill.complete().unwrap();
+
+        [report, bill].into_iter().map(|t| (t.id, t)).collect()
+    }
+
+    #[test]
+    fn test_round_trip_keeps_unchanged_tasks() {
+        let current = sample();
+        let tasks: Vec<&Task> = current.values().collect();
+
+        let mut buffer = Vec::new();
+        write_tasks(&tasks, GroupBy::Status, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains("## En cours\n\n- [/] Écrire le rapport #1 ⏫ 📅"));
+        assert!(text.contains("## Terminé\n\n- [x] Payer la facture"));
+
+        let mut parsed = read_tasks(&text);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        reconcile_plain_text(&mut parsed.tasks, &current, true).unwrap();
+        for (id, task) in &current {
+            assert_eq!(task_checksum(&parsed.tasks[id]).unwrap(), task_checksum(task).unwrap());
+        }
+
+        // Cocher une case dans le fichier : la version importée devient la plus récente
+        let report = current.values().find(|t| t.status == TaskStatus::InProgress).unwrap();
+        let mut parsed = read_tasks(&text.replace("- [/]", "- [x]"));
+        reconcile_plain_text(&mut parsed.tasks, &current, true).unwrap();
+        let edited = &parsed.tasks[&report.id];
+        assert_eq!(edited.status, TaskStatus::Completed);
+        assert!(edited.updated_at > report.updated_at);
+        assert_eq!(edited.description, report.description);
+    }
+
+    #[test]
+    fn test_group_by_tag_and_handwritten_notes() {
+        let current = sample();
+        let tasks: Vec<&Task> = current.values().collect();
+        let mut buffer = Vec::new();
+        write_tasks(&tasks, GroupBy::Tag, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let travail = text.find("## #travail").unwrap();
+        assert!(travail < text.find("## Sans tag").unwrap());
+
+        let notes = "# Réunion\n\
+                     Texte libre ignoré.\n\
+                     - [ ] Relancer le client 🔺 📅 2024-01-20 #client\n  \
+                       Après l'appel\n\
+                     * [X] Réserver la salle\n\
+                     - [?] Case inconnue\n\
+                     - [ ] 📅 demain\n";
+        let parsed = read_tasks(notes);
+
+        assert_eq!(parsed.tasks.len(), 2);
+        let client = parsed.tasks.values().find(|t| t.title == "Relancer le client").unwrap();
+        assert_eq!(client.priority, Priority::Critical);
+        assert_eq!(client.tags, vec!["client"]);
+        assert_eq!(client.description.as_deref(), Some("Après l'appel"));
+        assert_eq!(crate::formats::local_time(client.due_date.unwrap()), "2024-01-20 23:59:59");
+
+        let lines: Vec<u64> = parsed.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![6, 7]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Formats d'import et d'export des tâches
+//!
+//! Chaque format lit un fichier vers un `ParsedTasks` et écrit des tâches
+//! vers un `Write`. Les lignes invalides sont signalées individuellement
+//! plutôt que de faire échouer toute la lecture. `dot` est en écriture seule.
+
+pub mod csv;
+pub mod dot;
+pub mod ical;
+pub mod markdown;
+pub mod taskwarrior;
+pub mod todotxt;
+
+use crate::storage::task_checksum;
+use crate::task::Task;
+use crate::timezone::UserTimeZone;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// Ligne rejetée lors d'un import
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ligne {}: {}", self.line, self.message)
+    }
+}
+
+/// Résultat d'une lecture : tâches valides et lignes rejetées
+#[derive(Debug, Default)]
+pub struct ParsedTasks {
+    pub tasks: HashMap<Uuid, Task>,
+    pub errors: Vec<RowError>,
+}
+
+/// Format des dates des formats texte (`2024-01-15`)
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Jour de `date` dans le fuseau de l'utilisateur
+fn format_day(date: DateTime<Utc>) -> String {
+    date.with_timezone(&UserTimeZone::current()).format(DAY_FORMAT).to_string()
+}
+
+/// Lit une date `AAAA-MM-JJ` dans le fuseau de l'utilisateur ; `end_of_day`
+/// la place à 23:59:59 comme une échéance saisie avec `rtf add --due`,
+/// sinon à minuit
+fn parse_day(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value, DAY_FORMAT)
+        .map_err(|_| anyhow!("date invalide: {}", value))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    crate::dates::local_to_utc(&UserTimeZone::current(), time.unwrap())
+        .ok_or_else(|| anyhow!("date invalide: {}", value))
+}
+
+/// Date et heure de `date` dans le fuseau de l'utilisateur
+#[cfg(test)]
+fn local_time(date: DateTime<Utc>) -> String {
+    date.with_timezone(&UserTimeZone::current()).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Garde la date locale si le fichier indique le même jour
+fn same_day(incoming: Option<DateTime<Utc>>, local: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let timezone = UserTimeZone::current();
+    match (incoming, local) {
+        (Some(i), Some(l)) if i.with_timezone(&timezone).date_naive() == l.with_timezone(&timezone).date_naive() => Some(l),
+        _ => incoming,
+    }
+}
+
+/// Complète les tâches lues dans un format texte (Markdown, todo.txt) avec
+/// ce que ce format ne sait pas représenter : heures exactes, assignation,
+/// tâche parente, dépendances, récurrence, temps passé, attributs
+/// supplémentaires et, si le format n'en a pas, la description.
+///
+/// Une tâche identique à sa version locale garde sa date de modification et
+/// l'import la voit inchangée ; une tâche modifiée dans le fichier reçoit la
+/// date courante et l'emporte donc avec `--on-conflict newest`.
+pub fn reconcile_plain_text(
+    incoming: &mut HashMap<Uuid, Task>,
+    current: &HashMap<Uuid, Task>,
+    carries_description: bool,
+) -> Result<()> {
+    for task in incoming.values_mut() {
+        let Some(local) = current.get(&task.id) else {
+            continue;
+        };
+
+        task.created_at = local.created_at;
+        task.created_by = local.created_by;
+        task.assigned_to = local.assigned_to;
+        task.parent_id = local.parent_id;
+        task.depends_on = local.depends_on.clone();
+        task.recurrence = local.recurrence.clone();
+        task.series_id = local.series_id;
+        task.work_sessions = local.work_sessions.clone();
+        task.reopened_at = local.reopened_at;
+        task.started_at = local.started_at.or(task.started_at);
+        task.due_date = same_day(task.due_date, local.due_date);
+        task.completed_at = same_day(task.completed_at, local.completed_at);
+        task.cancelled_at = same_day(task.cancelled_at, local.cancelled_at);
+        if !carries_description {
+            task.description = local.description.clone();
+        }
+        let mut extra = local.extra.clone();
+        extra.append(&mut task.extra);
+        task.extra = extra;
+
+        task.updated_at = local.updated_at;
+        if task_checksum(task)? != task_checksum(local)? {
+            task.updated_at = Utc::now();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,328 @@
+//! Import et export au format JSON de Taskwarrior (`task export` / `task import`)
+//!
+//! Les attributs standards sont convertis vers les champs de `Task` ; tous
+//! les autres (annotations, projet, UDA, récurrence…) sont conservés tels
+//! quels dans `Task::extra` et réécrits à l'export. Les attributs calculés
+//! par Taskwarrior (`id`, `urgency`) sont ignorés.
+
+use super::{ParsedTasks, RowError};
+use crate::task::{new_task, Priority, Task, TaskStatus};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::io::Write;
+use uuid::Uuid;
+
+/// Format des dates de Taskwarrior (`20240115T100000Z`)
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Attributs convertis vers des champs de `Task`
+const MAPPED: &[&str] = &[
+    "uuid",
+    "description",
+    "status",
+    "priority",
+    "tags",
+    "entry",
+    "modified",
+    "start",
+    "end",
+    "due",
+    "depends",
+];
+
+/// Attributs recalculés par Taskwarrior, inutiles à conserver
+const COMPUTED: &[&str] = &["id", "urgency"];
+
+fn parse_date(object: &Map<String, Value>, key: &str) -> Result<Option<DateTime<Utc>>> {
+    let Some(value) = object.get(key) else {
+        return Ok(None);
+    };
+    let text = value
+        .as_str()
+        .ok_or_else(|| anyhow!("'{}' n'est pas une date", key))?;
+    NaiveDateTime::parse_from_str(text, DATE_FORMAT)
+        .map(|d| Some(d.and_utc()))
+        .or_else(|_| DateTime::parse_from_rfc3339(text).map(|d| Some(d.with_timezone(&Utc))))
+        .map_err(|_| anyhow!("date invalide dans '{}': {}", key, text))
+}
+
+fn format_date(date: DateTime<Utc>) -> Value {
+    Value::String(date.format(DATE_FORMAT).to_string())
+}
+
+fn task_from_object(object: Map<String, Value>) -> Result<Task> {
+    let description = object
+        .get("description")
+        .and_then(Value::as_str)
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| anyhow!("description absente"))?;
+
+    let mut task = new_task(description.to_string());
+    if let Some(uuid) = object.get("uuid").and_then(Value::as_str) {
+        task.id = Uuid::parse_str(uuid).map_err(|_| anyhow!("UUID invalide: {}", uuid))?;
+    }
+
+    task.priority = match object.get("priority").and_then(Value::as_str) {
+        Some("H") => Priority::High,
+        Some("L") => Priority::Low,
+        Some("M") | None => Priority::Medium,
+        Some(other) => return Err(anyhow!("priorité inconnue: {}", other)),
+    };
+    task.tags = object
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    // Tableau d'UUID, ou chaîne séparée par des virgules avant Taskwarrior 2.6
+    task.depends_on = match object.get("depends") {
+        None => Vec::new(),
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .map(|id| id.as_str().and_then(|id| Uuid::parse_str(id).ok()))
+            .collect::<Option<_>>()
+            .ok_or_else(|| anyhow!("depends invalide"))?,
+        Some(Value::String(ids)) => ids
+            .split(',')
+            .filter(|id| !id.trim().is_empty())
+            .map(|id| Uuid::parse_str(id.trim()).map_err(|_| anyhow!("UUID invalide dans depends: {}", id)))
+            .collect::<Result<_>>()?,
+        Some(_) => return Err(anyhow!("depends invalide")),
+    };
+
+    if let Some(entry) = parse_date(&object, "entry")? {
+        task.created_at = entry;
+    }
+    task.updated_at = parse_date(&object, "modified")?.unwrap_or(task.created_at);
+    task.due_date = parse_date(&object, "due")?;
+    task.started_at = parse_date(&object, "start")?;
+    let end = parse_date(&object, "end")?;
+
+    match object.get("status").and_then(Value::as_str).unwrap_or("pending") {
+        "completed" => {
+            task.status = TaskStatus::Completed;
+            task.completed_at = end;
+        }
+        "deleted" => {
+            task.status = TaskStatus::Cancelled;
+            task.cancelled_at = end;
+        }
+        // Une tâche démarrée a un attribut `start` mais reste `pending`
+        "pending" | "waiting" | "recurring" if task.started_at.is_some() => {
+            task.status = TaskStatus::InProgress;
+        }
+        "pending" | "waiting" | "recurring" => task.status = TaskStatus::Todo,
+        other => return Err(anyhow!("statut inconnu: {}", other)),
+    }
+
+    task.extra = object
+        .into_iter()
+        .filter(|(key, _)| !MAPPED.contains(&key.as_str()) && !COMPUTED.contains(&key.as_str()))
+        .collect();
+
+    Ok(task)
+}
+
+/// Lit la sortie de `task export` : un tableau JSON, ou un objet par ligne
+/// pour les anciennes versions. Les erreurs portent le rang de la tâche
+/// (ou le numéro de ligne).
+pub fn read_tasks(content: &str) -> Result<ParsedTasks> {
+    let objects: Vec<(u64, Value)> = if content.trim_start().starts_with('[') {
+        let values: Vec<Value> = serde_json::from_str(content)
+            .map_err(|e| anyhow!("JSON Taskwarrior invalide: {}", e))?;
+        (1..).zip(values).collect()
+    } else {
+        let mut objects = Vec::new();
+        for (line, text) in (1..).zip(content.lines()) {
+            let text = text.trim().trim_end_matches(',');
+            if !text.is_empty() {
+                let value = serde_json::from_str(text)
+                    .map_err(|e| anyhow!("JSON Taskwarrior invalide à la ligne {}: {}", line, e))?;
+                objects.push((line, value));
+            }
+        }
+        objects
+    };
+
+    let mut parsed = ParsedTasks::default();
+    for (line, value) in objects {
+        let result = match value {
+            Value::Object(object) => task_from_object(object),
+            _ => Err(anyhow!("objet JSON attendu")),
+        };
+        match result {
+            Ok(task) if parsed.tasks.contains_key(&task.id) => parsed.errors.push(RowError {
+                line,
+                message: format!("UUID en double: {}", task.id),
+            }),
+            Ok(task) => {
+                parsed.tasks.insert(task.id, task);
+            }
+            Err(e) => parsed.errors.push(RowError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn task_to_object(task: &Task) -> Value {
+    let mut object = Map::new();
+    object.insert("uuid".to_string(), json!(task.id.to_string()));
+    object.insert("description".to_string(), json!(task.title));
+
+    let status = match task.status {
+        TaskStatus::Todo | TaskStatus::InProgress => "pending",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Cancelled => "deleted",
+    };
+    object.insert("status".to_string(), json!(status));
+
+    let priority = match task.priority {
+        Priority::Low => "L",
+        Priority::Medium => "M",
+        Priority::High | Priority::Critical => "H",
+    };
+    object.insert("priority".to_string(), json!(priority));
+
+    if !task.tags.is_empty() {
+        object.insert("tags".to_string(), json!(task.tags));
+    }
+    if !task.depends_on.is_empty() {
+        let depends: Vec<String> = task.depends_on.iter().map(Uuid::to_string).collect();
+        object.insert("depends".to_string(), json!(depends));
+    }
+    object.insert("entry".to_string(), format_date(task.created_at));
+    object.insert("modified".to_string(), format_date(task.updated_at));
+    if let Some(due) = task.due_date {
+        object.insert("due".to_string(), format_date(due));
+    }
+    if task.status == TaskStatus::InProgress {
+        if let Some(start) = task.started_at {
+            object.insert("start".to_string(), format_date(start));
+        }
+    }
+    if let Some(end) = task.completed_at.or(task.cancelled_at) {
+        object.insert("end".to_string(), format_date(end));
+    }
+
+    for (key, value) in &task.extra {
+        object.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+
+    // Taskwarrior n'a pas de description longue : elle devient une annotation
+    if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+        let annotations = object
+            .entry("annotations".to_string())
+            .or_insert_with(|| json!([]));
+        if let Some(list) = annotations.as_array_mut() {
+            let present = list
+                .iter()
+                .any(|a| a.get("description").and_then(Value::as_str) == Some(description));
+            if !present {
+                list.push(json!({
+                    "entry": format_date(task.created_at),
+                    "description": description,
+                }));
+            }
+        }
+    }
+
+    Value::Object(object)
+}
+
+/// Écrit les tâches au format accepté par `task import`
+pub fn write_tasks<W: Write>(tasks: &[&Task], mut writer: W) -> Result<()> {
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|t| t.created_at);
+
+    let objects: Vec<Value> = tasks.into_iter().map(task_to_object).collect();
+    serde_json::to_writer_pretty(&mut writer, &objects)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"[
+        {"id":1,"description":"Écrire le rapport","entry":"20240115T100000Z","modified":"20240116T080000Z",
+         "status":"pending","uuid":"a3c1f0d2-5b6e-4f7a-8b9c-0d1e2f3a4b5c","priority":"H","tags":["travail"],
+         "depends":"0b6a4f3e-1c2d-4e5f-8a9b-7c6d5e4f3a2b",
+         "due":"20240120T170000Z","start":"20240116T080000Z","project":"Q1","estimate":"2h",
+         "annotations":[{"entry":"20240115T110000Z","description":"voir Marie"}],"urgency":12.3},
+        {"id":0,"description":"Payer la facture","entry":"20240110T090000Z","end":"20240111T090000Z",
+         "status":"completed","uuid":"0b6a4f3e-1c2d-4e5f-8a9b-7c6d5e4f3a2b"}
+    ]"#;
+
+    #[test]
+    fn test_import_maps_fields_and_keeps_extras() {
+        let parsed = read_tasks(EXPORT).unwrap();
+        assert!(parsed.errors.is_empty());
+
+        let id = Uuid::parse_str("a3c1f0d2-5b6e-4f7a-8b9c-0d1e2f3a4b5c").unwrap();
+        let task = &parsed.tasks[&id];
+        assert_eq!(task.title, "Écrire le rapport");
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(task.tags, vec!["travail"]);
+        assert_eq!(task.created_at.to_rfc3339(), "2024-01-15T10:00:00+00:00");
+        assert!(task.due_date.is_some());
+        assert_eq!(task.extra["project"], json!("Q1"));
+        assert_eq!(task.extra["estimate"], json!("2h"));
+        assert!(task.extra.contains_key("annotations"));
+        assert!(!task.extra.contains_key("urgency"));
+        assert_eq!(
+            task.depends_on,
+            vec![Uuid::parse_str("0b6a4f3e-1c2d-4e5f-8a9b-7c6d5e4f3a2b").unwrap()]
+        );
+
+        let done = parsed.tasks.values().find(|t| t.title == "Payer la facture").unwrap();
+        assert_eq!(done.status, TaskStatus::Completed);
+        assert_eq!(done.priority, Priority::Medium);
+        assert!(done.completed_at.is_some());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let parsed = read_tasks(EXPORT).unwrap();
+        let tasks: Vec<&Task> = parsed.tasks.values().collect();
+
+        let mut buffer = Vec::new();
+        write_tasks(&tasks, &mut buffer).unwrap();
+        let exported: Vec<Value> = serde_json::from_slice(&buffer).unwrap();
+
+        let first = exported
+            .iter()
+            .find(|o| o["uuid"] == "a3c1f0d2-5b6e-4f7a-8b9c-0d1e2f3a4b5c")
+            .unwrap();
+        assert_eq!(first["status"], "pending");
+        assert_eq!(first["start"], "20240116T080000Z");
+        assert_eq!(first["due"], "20240120T170000Z");
+        assert_eq!(first["project"], "Q1");
+        assert_eq!(first["annotations"][0]["description"], "voir Marie");
+
+        let reparsed = read_tasks(std::str::from_utf8(&buffer).unwrap()).unwrap();
+        assert_eq!(reparsed.tasks.len(), 2);
+        for (id, task) in &parsed.tasks {
+            assert_eq!(reparsed.tasks[id].extra, task.extra);
+            assert_eq!(reparsed.tasks[id].status, task.status);
+            assert_eq!(reparsed.tasks[id].depends_on, task.depends_on);
+        }
+    }
+
+    #[test]
+    fn test_line_format_and_bad_tasks() {
+        let content = "{\"description\":\"ok\",\"status\":\"pending\"},\n\
+                       {\"description\":\"\",\"status\":\"pending\"},\n\
+                       {\"description\":\"x\",\"due\":\"demain\"}\n";
+        let parsed = read_tasks(content).unwrap();
+        assert_eq!(parsed.tasks.len(), 1);
+        let lines: Vec<u64> = parsed.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+    }
+}
@@ -0,0 +1,314 @@
+//! Import et export au format todo.txt
+//!
+//! Une tâche par ligne : `x` et date de fin pour une tâche terminée,
+//! priorité `(A)`, date de création, puis le titre avec ses `+projet`,
+//! `@contexte` et paires `clé:valeur` (`due:2024-01-20`). Les projets
+//! deviennent des tags et les contextes des tags commençant par `@`.
+//! RustTaskFlow ajoute `id:` (UUID), `status:` pour les tâches en cours ou
+//! annulées et `pri:` pour garder la priorité d'une tâche terminée. Les
+//! autres paires (`rec:`, `t:`…) sont conservées dans `Task::extra`.
+//! todo.txt n'a pas de description longue : elle n'est pas exportée.
+
+use super::{format_day, parse_day, ParsedTasks, RowError};
+use crate::task::{new_task, Priority, Task, TaskStatus};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde_json::Value;
+use std::io::Write;
+use uuid::Uuid;
+
+/// Clés `clé:valeur` interprétées par RustTaskFlow
+const RESERVED_KEYS: &[&str] = &["due", "id", "status", "pri"];
+
+/// Lettre de priorité ; la priorité moyenne, par défaut, n'en a pas
+fn priority_letter(priority: &Priority) -> Option<char> {
+    match priority {
+        Priority::Critical => Some('A'),
+        Priority::High => Some('B'),
+        Priority::Medium => None,
+        Priority::Low => Some('C'),
+    }
+}
+
+fn parse_priority_letter(letter: char) -> Option<Priority> {
+    match letter {
+        'A' => Some(Priority::Critical),
+        'B' => Some(Priority::High),
+        'C'..='Z' => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+/// `(A)` en tête de ligne
+fn parse_priority_word(word: &str) -> Option<Priority> {
+    let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+    let mut chars = letter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => parse_priority_letter(letter),
+        _ => None,
+    }
+}
+
+/// Paire `clé:valeur` ; les URL (`https://…`) et les heures (`10:30`) n'en sont pas
+fn split_pair(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    (valid_key && !value.is_empty() && !value.starts_with('/')).then_some((key, value))
+}
+
+/// Un projet ou un contexte s'arrête au premier espace
+fn tag_word(tag: &str) -> String {
+    let word = tag.split_whitespace().collect::<Vec<_>>().join("_");
+    if word.starts_with('@') {
+        word
+    } else {
+        format!("+{}", word)
+    }
+}
+
+fn task_line(task: &Task) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let letter = priority_letter(&task.priority);
+    let finished = matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled);
+
+    if finished {
+        let end = task.completed_at.or(task.cancelled_at).unwrap_or(task.updated_at);
+        parts.push("x".to_string());
+        parts.push(format_day(end));
+    } else if let Some(letter) = letter {
+        parts.push(format!("({})", letter));
+    }
+    parts.push(format_day(task.created_at));
+    parts.push(task.title.split_whitespace().collect::<Vec<_>>().join(" "));
+    parts.extend(task.tags.iter().map(|tag| tag_word(tag)));
+
+    if let Some(due) = task.due_date {
+        parts.push(format!("due:{}", format_day(due)));
+    }
+    match task.status {
+        TaskStatus::InProgress => parts.push("status:in-progress".to_string()),
+        TaskStatus::Cancelled => parts.push("status:cancelled".to_string()),
+        _ => {}
+    }
+    // La convention est de retirer la priorité d'une tâche terminée
+    if let Some(letter) = letter.filter(|_| finished) {
+        parts.push(format!("pri:{}", letter));
+    }
+    for (key, value) in &task.extra {
+        if let Some(value) = value.as_str() {
+            let pair = format!("{}:{}", key, value);
+            if !RESERVED_KEYS.contains(&key.as_str()) && split_pair(&pair) == Some((key, value)) {
+                parts.push(pair);
+            }
+        }
+    }
+    parts.push(format!("id:{}", task.id));
+
+    parts.join(" ")
+}
+
+/// Écrit une tâche par ligne, triées par date de création
+pub fn write_tasks<W: Write>(tasks: &[&Task], mut writer: W) -> Result<()> {
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|t| t.created_at);
+
+    for task in tasks {
+        writeln!(writer, "{}", task_line(task))?;
+    }
+    Ok(())
+}
+
+fn task_from_line(line: &str) -> Result<Task> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut rest = words.as_slice();
+
+    let finished = rest.first() == Some(&"x");
+    if finished {
+        rest = &rest[1..];
+    }
+    let mut priority = None;
+    if !finished {
+        if let Some(word_priority) = rest.first().and_then(|w| parse_priority_word(w)) {
+            priority = Some(word_priority);
+            rest = &rest[1..];
+        }
+    }
+    // Une tâche terminée a une date de fin puis, éventuellement, de création
+    let mut dates = Vec::new();
+    while dates.len() < if finished { 2 } else { 1 } {
+        match rest.first().and_then(|w| parse_day(w, false).ok()) {
+            Some(date) => dates.push(date),
+            None => break,
+        }
+        rest = &rest[1..];
+    }
+    let (end, created) = if finished {
+        (dates.first().copied(), dates.get(1).copied())
+    } else {
+        (None, dates.first().copied())
+    };
+
+    let mut title = Vec::new();
+    let mut task = new_task(String::new());
+    let mut status = None;
+
+    for &word in rest {
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            if !task.tags.iter().any(|t| t == project) {
+                task.tags.push(project.to_string());
+            }
+        } else if word.len() > 1 && word.starts_with('@') {
+            if !task.tags.iter().any(|t| t == word) {
+                task.tags.push(word.to_string());
+            }
+        } else if let Some((key, value)) = split_pair(word) {
+            match key {
+                "due" => task.due_date = Some(parse_day(value, true)?),
+                "id" => task.id = Uuid::parse_str(value).map_err(|_| anyhow!("UUID invalide: {}", value))?,
+                "status" => status = Some(value),
+                "pri" => {
+                    let mut chars = value.chars();
+                    priority = match (chars.next(), chars.next()) {
+                        (Some(letter), None) => parse_priority_letter(letter),
+                        _ => None,
+                    };
+                    if priority.is_none() {
+                        return Err(anyhow!("priorité invalide: {}", value));
+                    }
+                }
+                _ => {
+                    task.extra.insert(key.to_string(), Value::String(value.to_string()));
+                }
+            }
+        } else {
+            title.push(word);
+        }
+    }
+
+    if title.is_empty() {
+        return Err(anyhow!("titre vide"));
+    }
+    task.title = title.join(" ");
+    task.priority = priority.unwrap_or_default();
+    task.created_at = created.unwrap_or_else(Utc::now);
+    task.updated_at = task.created_at;
+
+    match (finished, status) {
+        (false, None) => task.status = TaskStatus::Todo,
+        (false, Some("in-progress")) => {
+            task.status = TaskStatus::InProgress;
+            task.started_at = Some(task.updated_at);
+        }
+        (true, None) => {
+            task.status = TaskStatus::Completed;
+            task.completed_at = end.or(Some(task.updated_at));
+        }
+        (_, Some("cancelled")) => {
+            task.status = TaskStatus::Cancelled;
+            task.cancelled_at = end.or(Some(task.updated_at));
+        }
+        (_, Some(other)) => return Err(anyhow!("statut inconnu: {}", other)),
+    }
+
+    Ok(task)
+}
+
+/// Lit un fichier todo.txt ; les lignes vides sont ignorées
+pub fn read_tasks(content: &str) -> ParsedTasks {
+    let mut parsed = ParsedTasks::default();
+
+    for (line, text) in (1..).zip(content.lines()) {
+        if text.trim().is_empty() {
+            continue;
+        }
+        match task_from_line(text) {
+            Ok(task) if parsed.tasks.contains_key(&task.id) => parsed.errors.push(RowError {
+                line,
+                message: format!("ID en double: {}", task.id),
+            }),
+            Ok(task) => {
+                parsed.tasks.insert(task.id, task);
+            }
+            Err(e) => parsed.errors.push(RowError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::reconcile_plain_text;
+    use crate::storage::task_checksum;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_read_todotxt_conventions() {
+        let content = "(A) 2024-01-10 Appeler maman +famille @téléphone due:2024-01-20 rec:1w\n\
+                       \n\
+                       x 2024-01-12 2024-01-08 Lire https://example.com à 10:30 pri:B\n\
+                       (B) Rapport status:in-progress\n\
+                       Mauvaise date due:demain\n\
+                       x 2024-01-12 status:cancelled +seul\n";
+        let parsed = read_tasks(content);
+
+        assert_eq!(parsed.tasks.len(), 3);
+        let call = parsed.tasks.values().find(|t| t.title == "Appeler maman").unwrap();
+        assert_eq!(call.priority, Priority::Critical);
+        assert_eq!(call.tags, vec!["famille", "@téléphone"]);
+        assert_eq!(crate::formats::local_time(call.created_at), "2024-01-10 00:00:00");
+        assert_eq!(crate::formats::local_time(call.due_date.unwrap()), "2024-01-20 23:59:59");
+        assert_eq!(call.extra["rec"], Value::String("1w".to_string()));
+
+        let read = parsed.tasks.values().find(|t| t.title.starts_with("Lire")).unwrap();
+        assert_eq!(read.title, "Lire https://example.com à 10:30");
+        assert_eq!(read.status, TaskStatus::Completed);
+        assert_eq!(read.priority, Priority::High);
+        assert_eq!(crate::formats::local_time(read.completed_at.unwrap()), "2024-01-12 00:00:00");
+
+        let report = parsed.tasks.values().find(|t| t.title == "Rapport").unwrap();
+        assert_eq!(report.status, TaskStatus::InProgress);
+
+        let lines: Vec<u64> = parsed.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![5, 6]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut call = new_task("Appeler le client".to_string());
+        call.set_priority(Priority::Critical);
+        call.add_tag("travail".to_string());
+        call.add_tag("@téléphone".to_string());
+        call.set_due_date(Some(Utc::now()));
+        call.set_description(Some("Non exportée".to_string()));
+        call.extra.insert("rec".to_string(), Value::String("1w".to_string()));
+        call.start().unwrap();
+        let mut old = new_task("Ancienne".to_string());
+        old.set_priority(Priority::High);
+        old.cancel().unwrap();
+
+        let current: HashMap<Uuid, Task> = [call, old].into_iter().map(|t| (t.id, t)).collect();
+        let tasks: Vec<&Task> = current.values().collect();
+        let mut buffer = Vec::new();
+        write_tasks(&tasks, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+
+        let today = format_day(Utc::now());
+        assert!(text.contains(&format!(
+            "(A) {today} Appeler le client +travail @téléphone due:{today} status:in-progress rec:1w id:"
+        )));
+        assert!(text.contains(&format!("x {today} {today} Ancienne status:cancelled pri:B id:")));
+
+        let mut parsed = read_tasks(&text);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        reconcile_plain_text(&mut parsed.tasks, &current, false).unwrap();
+        for (id, task) in &current {
+            assert_eq!(task_checksum(&parsed.tasks[id]).unwrap(), task_checksum(task).unwrap());
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Fusion des tâches importées avec les tâches locales
+//!
+//! L'import est préparé en deux temps : `plan_import` compare les tâches lues
+//! avec l'état courant et décide du sort de chacune, sans rien modifier ;
+//! `TaskManager::apply_import` applique ensuite le plan. Le plan sert aussi
+//! de résumé pour `rtf import --dry-run`.
+
+use crate::storage::task_checksum;
+use crate::task::Task;
+use anyhow::Result;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Manière de combiner les tâches importées avec les tâches locales
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ImportMode {
+    /// Ajoute les nouvelles tâches et résout les conflits d'UUID
+    #[default]
+    Merge,
+    /// Remplace toutes les tâches locales par les tâches importées
+    Replace,
+    /// Ajoute les nouvelles tâches et ignore celles qui existent déjà
+    SkipExisting,
+}
+
+/// Version retenue quand une tâche existe des deux côtés avec un contenu différent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    KeepLocal,
+    TakeIncoming,
+}
+
+/// Conflit rencontré en mode fusion
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub id: Uuid,
+    pub title: String,
+    pub resolution: Resolution,
+}
+
+/// Ce que l'import va faire
+#[derive(Debug, Default)]
+pub struct ImportPlan {
+    /// Tâches absentes localement
+    pub added: Vec<Task>,
+    /// Versions importées remplaçant des tâches locales
+    pub updated: Vec<Task>,
+    /// Tâches locales supprimées (mode remplacement uniquement)
+    pub removed: Vec<Uuid>,
+    /// Tâches identiques des deux côtés
+    pub unchanged: usize,
+    /// Tâches existantes ignorées
+    pub skipped: usize,
+    /// Conflits d'UUID et leur résolution
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ImportPlan {
+    /// Indique si l'import ne change rien
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Résolution par date de modification : la version la plus récente gagne,
+/// la version locale en cas d'égalité
+pub fn newest_wins(local: &Task, incoming: &Task) -> Result<Resolution> {
+    Ok(if incoming.updated_at > local.updated_at {
+        Resolution::TakeIncoming
+    } else {
+        Resolution::KeepLocal
+    })
+}
+
+/// Prépare l'import de `incoming` dans `current` ; `resolve` tranche les conflits du mode fusion
+pub fn plan_import<F>(
+    current: &HashMap<Uuid, Task>,
+    incoming: HashMap<Uuid, Task>,
+    mode: ImportMode,
+    mut resolve: F,
+) -> Result<ImportPlan>
+where
+    F: FnMut(&Task, &Task) -> Result<Resolution>,
+{
+    let mut plan = ImportPlan::default();
+
+    if mode == ImportMode::Replace {
+        plan.removed = current
+            .keys()
+            .filter(|id| !incoming.contains_key(id))
+            .copied()
+            .collect();
+    }
+
+    let mut incoming: Vec<Task> = incoming.into_values().collect();
+    incoming.sort_by_key(|t| t.created_at);
+
+    for task in incoming {
+        let Some(local) = current.get(&task.id) else {
+            plan.added.push(task);
+            continue;
+        };
+
+        if task_checksum(local)? == task_checksum(&task)? {
+            plan.unchanged += 1;
+            continue;
+        }
+
+        match mode {
+            ImportMode::Replace => plan.updated.push(task),
+            ImportMode::SkipExisting => plan.skipped += 1,
+            ImportMode::Merge => {
+                let resolution = resolve(local, &task)?;
+                plan.conflicts.push(Conflict {
+                    id: task.id,
+                    title: task.title.clone(),
+                    resolution,
+                });
+                if resolution == Resolution::TakeIncoming {
+                    plan.updated.push(task);
+                }
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::new_task;
+
+    fn to_map(tasks: &[&Task]) -> HashMap<Uuid, Task> {
+        tasks.iter().map(|t| (t.id, (*t).clone())).collect()
+    }
+
+    /// Tâches locales (a, b, c) et importées (a modifiée plus tard, b inchangée, d nouvelle)
+    fn fixture() -> (HashMap<Uuid, Task>, HashMap<Uuid, Task>) {
+        let a = new_task("A".to_string());
+        let b = new_task("B".to_string());
+        let c = new_task("C".to_string());
+        let mut a_edited = a.clone();
+        a_edited.set_title("A edited".to_string());
+        a_edited.updated_at = a.updated_at + chrono::Duration::minutes(1);
+        let d = new_task("D".to_string());
+        (to_map(&[&a, &b, &c]), to_map(&[&a_edited, &b, &d]))
+    }
+
+    #[test]
+    fn test_merge_resolves_by_updated_at() {
+        let (current, incoming) = fixture();
+        let plan = plan_import(&current, incoming, ImportMode::Merge, newest_wins).unwrap();
+
+        assert_eq!(plan.added.len(), 1);
+        assert_eq!(plan.updated.len(), 1);
+        assert_eq!(plan.updated[0].title, "A edited");
+        assert_eq!(plan.unchanged, 1);
+        assert!(plan.removed.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].resolution, Resolution::TakeIncoming);
+    }
+
+    #[test]
+    fn test_merge_can_keep_local() {
+        let (current, incoming) = fixture();
+        let plan = plan_import(&current, incoming, ImportMode::Merge, |_, _| {
+            Ok(Resolution::KeepLocal)
+        })
+        .unwrap();
+
+        assert!(plan.updated.is_empty());
+        assert_eq!(plan.conflicts[0].resolution, Resolution::KeepLocal);
+    }
+
+    #[test]
+    fn test_replace_and_skip_existing() {
+        let (current, incoming) = fixture();
+        let plan = plan_import(&current, incoming.clone(), ImportMode::Replace, newest_wins).unwrap();
+        assert_eq!((plan.added.len(), plan.updated.len(), plan.removed.len()), (1, 1, 1));
+        assert!(plan.conflicts.is_empty());
+
+        let plan = plan_import(&current, incoming, ImportMode::SkipExisting, newest_wins).unwrap();
+        assert_eq!((plan.added.len(), plan.updated.len(), plan.skipped), (1, 0, 1));
+    }
+}
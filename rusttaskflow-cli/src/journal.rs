@@ -0,0 +1,223 @@
+//! Journal des opérations pour `rtf undo`, `rtf redo` et `rtf history`
+//!
+//! Chaque commande qui modifie des tâches enregistre une opération : l'état
+//! de chaque tâche touchée avant et après la commande. L'inverse d'une
+//! opération échange simplement ces deux états. Le journal est conservé dans
+//! `journal.json`, à côté des données, pour survivre d'une invocation à
+//! l'autre.
+
+use crate::storage::{task_checksum, write_atomic};
+use crate::task::Task;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Nom du fichier de journal dans le répertoire de données
+pub const JOURNAL_FILE: &str = "journal.json";
+
+/// Nombre maximum d'opérations conservées dans le journal
+pub const MAX_OPERATIONS: usize = 100;
+
+/// État d'une tâche avant et après une opération (`None` : tâche absente)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskChange {
+    pub id: Uuid,
+    pub before: Option<Task>,
+    pub after: Option<Task>,
+}
+
+/// Une commande enregistrée dans le journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub label: String,
+    pub recorded_at: DateTime<Utc>,
+    pub changes: Vec<TaskChange>,
+}
+
+impl Operation {
+    /// Opération qui défait celle-ci
+    pub fn inverse(&self) -> Operation {
+        Operation {
+            label: self.label.clone(),
+            recorded_at: self.recorded_at,
+            changes: self
+                .changes
+                .iter()
+                .map(|change| TaskChange {
+                    id: change.id,
+                    before: change.after.clone(),
+                    after: change.before.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Vérifie que les tâches sont encore dans l'état `before` de l'opération,
+    /// pour ne pas écraser une modification faite hors du journal
+    pub fn check_applicable<'a, F>(&self, current: F) -> Result<()>
+    where
+        F: Fn(&Uuid) -> Option<&'a Task>,
+    {
+        for change in &self.changes {
+            let expected = change.before.as_ref().map(task_checksum).transpose()?;
+            let actual = current(&change.id).map(task_checksum).transpose()?;
+            if expected != actual {
+                return Err(anyhow!(
+                    "La tâche {} a été modifiée depuis « {} » ; opération impossible",
+                    change.id.to_string()[..8].to_uppercase(),
+                    self.label
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Historique des opérations faites et défaites
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Journal {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Opérations pouvant être annulées, la plus récente en dernier
+    done: Vec<Operation>,
+    /// Opérations annulées pouvant être rétablies, la plus récente en dernier
+    undone: Vec<Operation>,
+}
+
+impl Journal {
+    /// Charge le journal du répertoire `data_dir` (vide s'il n'existe pas)
+    pub fn load(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(JOURNAL_FILE);
+        let mut journal: Journal = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Impossible de lire '{}'", path.display()))?;
+            serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Journal invalide dans '{}': {}", path.display(), e))?
+        } else {
+            Journal::default()
+        };
+        journal.path = Some(path);
+        Ok(journal)
+    }
+
+    /// Enregistre le journal sur disque (sans effet pour un journal en mémoire)
+    pub fn save(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => write_atomic(path, serde_json::to_string(self)?.as_bytes()),
+            None => Ok(()),
+        }
+    }
+
+    /// Ajoute une nouvelle opération ; l'historique des opérations défaites est perdu
+    pub fn record(&mut self, operation: Operation) {
+        self.done.push(operation);
+        self.undone.clear();
+        if self.done.len() > MAX_OPERATIONS {
+            let excess = self.done.len() - MAX_OPERATIONS;
+            self.done.drain(..excess);
+        }
+    }
+
+    /// Dernière opération pouvant être annulée
+    pub fn last_done(&self) -> Option<&Operation> {
+        self.done.last()
+    }
+
+    /// Dernière opération annulée pouvant être rétablie
+    pub fn last_undone(&self) -> Option<&Operation> {
+        self.undone.last()
+    }
+
+    /// Déplace la dernière opération faite vers les opérations défaites
+    pub fn mark_undone(&mut self) {
+        if let Some(operation) = self.done.pop() {
+            self.undone.push(operation);
+        }
+    }
+
+    /// Déplace la dernière opération défaite vers les opérations faites
+    pub fn mark_redone(&mut self) {
+        if let Some(operation) = self.undone.pop() {
+            self.done.push(operation);
+        }
+    }
+
+    /// Opérations faites, de la plus récente à la plus ancienne
+    pub fn history(&self) -> impl Iterator<Item = &Operation> {
+        self.done.iter().rev()
+    }
+
+    /// Nombre d'opérations pouvant être rétablies
+    pub fn redo_count(&self) -> usize {
+        self.undone.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::new_task;
+    use tempfile::tempdir;
+
+    fn operation(label: &str) -> Operation {
+        let task = new_task(label.to_string());
+        Operation {
+            label: label.to_string(),
+            recorded_at: Utc::now(),
+            changes: vec![TaskChange {
+                id: task.id,
+                before: None,
+                after: Some(task),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_journal_persists_across_loads() {
+        let temp_dir = tempdir().unwrap();
+
+        let mut journal = Journal::load(temp_dir.path()).unwrap();
+        journal.record(operation("first"));
+        journal.record(operation("second"));
+        journal.mark_undone();
+        journal.save().unwrap();
+
+        let journal = Journal::load(temp_dir.path()).unwrap();
+        assert_eq!(journal.last_done().unwrap().label, "first");
+        assert_eq!(journal.last_undone().unwrap().label, "second");
+    }
+
+    #[test]
+    fn test_record_clears_redo_and_caps_history() {
+        let mut journal = Journal::default();
+        journal.record(operation("first"));
+        journal.mark_undone();
+        assert_eq!(journal.redo_count(), 1);
+
+        journal.record(operation("second"));
+        assert_eq!(journal.redo_count(), 0);
+
+        for i in 0..MAX_OPERATIONS {
+            journal.record(operation(&i.to_string()));
+        }
+        assert_eq!(journal.history().count(), MAX_OPERATIONS);
+        assert_eq!(journal.history().last().unwrap().label, "0");
+    }
+
+    #[test]
+    fn test_inverse_swaps_states() {
+        let op = operation("add");
+        let inverse = op.inverse();
+        assert!(inverse.changes[0].before.is_some());
+        assert!(inverse.changes[0].after.is_none());
+
+        // L'opération s'applique tant que la tâche est absente
+        assert!(op.check_applicable(|_| None).is_ok());
+        let task = op.changes[0].after.clone().unwrap();
+        assert!(op.check_applicable(|_| Some(&task)).is_err());
+        assert!(inverse.check_applicable(|_| Some(&task)).is_ok());
+    }
+}
@@ -13,13 +13,23 @@
 pub mod task;
 pub mod task_manager;
 pub mod storage;
+pub mod backup;
+pub mod journal;
+pub mod import;
+pub mod formats;
+pub mod lock;
+pub mod migrations;
 pub mod cli;
+pub mod dates;
+pub mod config;
+pub mod timezone;
 pub mod ui;
 
-pub use task::{Task, Priority, Status};
-pub use task_manager::{TaskManager, TaskFilter, TaskStats};
+pub use task::{Task, Priority, TaskStatus};
+pub use task_manager::{TaskManager, TaskFilter, TaskStats, TimeSpent};
 pub use storage::{Storage, JsonStorage, SqliteStorage, create_storage};
 pub use cli::{Cli, Commands};
+pub use config::{Config, StorageType};
 pub use ui::*;
 
 /// Result type used throughout the application
@@ -0,0 +1,70 @@
+//! Verrou consultatif sur le répertoire de données
+//!
+//! `rtf` charge toutes les tâches, les modifie puis les réenregistre. Deux
+//! processus lancés en même temps (par exemple depuis un hook du shell)
+//! pourraient entrelacer ces étapes ; le verrou est donc pris avant le
+//! chargement et relâché à la fin de la commande.
+
+use anyhow::{anyhow, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// Nom du fichier de verrou dans le répertoire de données
+pub const LOCK_FILE: &str = "rtf.lock";
+
+/// Verrou exclusif, relâché quand la valeur est détruite
+#[derive(Debug)]
+pub struct StorageLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl StorageLock {
+    /// Prend le verrou du répertoire `data_dir` sans attendre
+    pub fn acquire(data_dir: &Path) -> Result<Self> {
+        let path = data_dir.join(LOCK_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        file.try_lock_exclusive().map_err(|_| {
+            anyhow!(
+                "Une autre instance de rtf utilise déjà '{}'. Réessayez quand elle sera terminée.",
+                data_dir.display()
+            )
+        })?;
+
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StorageLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let temp_dir = tempdir().unwrap();
+
+        let lock = StorageLock::acquire(temp_dir.path()).unwrap();
+        assert!(lock.path().exists());
+        assert!(StorageLock::acquire(temp_dir.path()).is_err());
+
+        drop(lock);
+        assert!(StorageLock::acquire(temp_dir.path()).is_ok());
+    }
+}
@@ -1,17 +1,22 @@
-mod cli;
-mod storage;
-mod task;
-mod task_manager;
-mod ui;
-
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use cli::{parse_date, parse_tags, find_task_by_partial_id, Cli, Commands, TagCommands};
 use colored::*;
-use storage::{create_storage};
-use task::{Status};
-use task_manager::{TaskFilter, TaskManager};
-use ui::TaskDisplay;
+use rusttaskflow_cli::backup::{diff_tasks, Backup, ChangeKind};
+use rusttaskflow_cli::cli::{self, parse_date, parse_duration, parse_tags, find_task_by_partial_id, BackupCommands, Cli, CliConflict, Commands, CliStorage, DbCommands, DependCommands, RecurCommands, TagCommands, TimerCommands};
+use rusttaskflow_cli::migrations;
+use rusttaskflow_cli::config::Config;
+use rusttaskflow_cli::formats::{self, ParsedTasks};
+use rusttaskflow_cli::import::{newest_wins, plan_import, Resolution};
+use rusttaskflow_cli::journal::Journal;
+use rusttaskflow_cli::lock::StorageLock;
+use rusttaskflow_cli::storage::{create_storage, transfer_tasks, Storage};
+use rusttaskflow_cli::task::{Recurrence, Task, TaskStatus};
+use rusttaskflow_cli::task_manager::{TaskFilter, TaskManager};
+use rusttaskflow_cli::timezone::UserTimeZone;
+use rusttaskflow_cli::ui::{format_time_spent, TaskDisplay};
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use uuid::Uuid;
 
 fn main() {
     if let Err(e) = run() {
@@ -20,27 +25,269 @@ fn main() {
     }
 }
 
+/// Sauvegarde automatique avant une opération destructrice, suivie de la
+/// rotation selon la section `[backup]` de la configuration
+fn backup_before(storage: &dyn Storage, config: &Config) -> Result<Backup> {
+    let backup = storage.backup()?;
+    storage.backup_dir().prune(&config.backup)?;
+    Ok(backup)
+}
+
+/// Écrit un export dans le fichier demandé, ou sur la sortie standard
+fn write_export(output: Option<String>, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    let Some(file_path) = output else {
+        return write(&mut std::io::stdout().lock());
+    };
+    
+    let mut file = BufWriter::new(std::fs::File::create(&file_path)?);
+    write(&mut file)?;
+    file.flush()?;
+    println!(
+        "{} Tâches exportées vers '{}'",
+        "💾".green().bold(),
+        file_path.bright_blue()
+    );
+    Ok(())
+}
+
+/// Refuse un import dont certaines lignes sont invalides, après les avoir listées
+fn accept_parsed(parsed: ParsedTasks, file: &str) -> Result<HashMap<Uuid, Task>> {
+    if parsed.errors.is_empty() {
+        return Ok(parsed.tasks);
+    }
+    
+    for error in &parsed.errors {
+        eprintln!("  {} {}", "✗".red().bold(), error);
+    }
+    Err(anyhow!(
+        "{} ligne(s) invalide(s) dans '{}', rien n'a été importé",
+        parsed.errors.len(),
+        file
+    ))
+}
+
+/// Refuse de démarrer une tâche dont des dépendances ne sont pas terminées,
+/// sauf avec `--force`, après les avoir listées
+fn check_blocking(task_manager: &TaskManager, task_id: &Uuid, force: bool) -> Result<()> {
+    let blocking = task_manager.get_blocking_tasks(task_id);
+    if blocking.is_empty() {
+        return Ok(());
+    }
+    
+    let message = format!("{} dépendance(s) non terminée(s):", blocking.len());
+    if force {
+        TaskDisplay::warning(&message);
+    } else {
+        TaskDisplay::error(&message);
+    }
+    for dependency in &blocking {
+        println!(
+            "    [{}] {}",
+            dependency.id.to_string()[..8].to_uppercase().bright_blue(),
+            dependency.title
+        );
+    }
+    if !force {
+        return Err(anyhow!("Tâche bloquée, utilisez --force pour la démarrer quand même"));
+    }
+    Ok(())
+}
+
+/// Demande quelle version garder quand une tâche importée diffère de la locale
+fn ask_resolution(local: &Task, incoming: &Task) -> Result<Resolution> {
+    use std::io::{self, Write};
+    
+    println!(
+        "{} Conflit sur [{}] '{}'",
+        "⚠".yellow().bold(),
+        local.id.to_string()[..8].to_uppercase(),
+        local.title
+    );
+    let timezone = UserTimeZone::current();
+    println!("  locale:   '{}' modifiée le {}", local.title, local.updated_at.with_timezone(&timezone).format("%d/%m/%Y %H:%M"));
+    println!("  importée: '{}' modifiée le {}", incoming.title, incoming.updated_at.with_timezone(&timezone).format("%d/%m/%Y %H:%M"));
+    print!("Garder la version [l]ocale ou [i]mportée ? (l/i): ");
+    io::stdout().flush()?;
+    
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    
+    if input.trim().to_lowercase().starts_with('i') {
+        Ok(Resolution::TakeIncoming)
+    } else {
+        Ok(Resolution::KeepLocal)
+    }
+}
+
+/// Copie les tâches d'un backend vers un autre ; chaque stockage est ouvert ici
+fn migrate_storage(to: CliStorage, from: Option<CliStorage>, force: bool, config: &Config) -> Result<()> {
+    let source_config = Config {
+        storage: from.map(Into::into).unwrap_or(config.storage),
+        ..config.clone()
+    };
+    let destination_config = Config {
+        storage: to.into(),
+        ..config.clone()
+    };
+
+    if source_config.storage == destination_config.storage {
+        return Err(anyhow!(
+            "La source et la destination sont identiques ({})",
+            source_config.storage.as_str()
+        ));
+    }
+
+    let source = create_storage(&source_config)?;
+    let destination = create_storage(&destination_config)?;
+    let destination_path = destination_config.storage_path()?.to_string_lossy().to_string();
+
+    let existing = destination.load_tasks()?.len();
+    if existing > 0 {
+        if !force {
+            return Err(anyhow!(
+                "'{}' contient déjà {} tâche(s). Utilisez --force pour l'écraser",
+                destination_path,
+                existing
+            ));
+        }
+        backup_before(destination.as_ref(), config)?;
+    }
+
+    let count = transfer_tasks(source.as_ref(), destination.as_ref())?;
+
+    println!(
+        "{} {} tâche(s) copiée(s) de {} vers '{}' (nombre et empreintes vérifiés)",
+        "🔄".green().bold(),
+        count,
+        source_config.storage.as_str(),
+        destination_path.bright_blue()
+    );
+    println!(
+        "  L'ancien stockage '{}' est conservé comme sauvegarde.",
+        source_config.storage_path()?.to_string_lossy()
+    );
+    println!(
+        "  Utilisez --storage {} ou `storage = \"{}\"` dans config.toml pour basculer.",
+        destination_config.storage.as_str(),
+        destination_config.storage.as_str()
+    );
+    
+    Ok(())
+}
+
+/// `rtf db` : ouvre la base sans la migrer, pour que `--dry-run` n'applique rien
+fn db_command(action: DbCommands, config: &Config) -> Result<()> {
+    match action {
+        DbCommands::Migrate { dry_run } => {
+            let path = config.sqlite_path()?;
+            let conn = if !dry_run {
+                rusqlite::Connection::open(&path)?
+            } else if path.exists() {
+                rusqlite::Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?
+            } else {
+                // Une base absente est une base vierge : ne pas créer le fichier
+                rusqlite::Connection::open_in_memory()?
+            };
+            let db_path = path.to_string_lossy().to_string();
+            let current = migrations::current_version(&conn)?;
+
+            let migrations_list = if dry_run {
+                migrations::pending_migrations(&conn)?
+            } else {
+                migrations::run_migrations(&conn)?
+            };
+
+            if migrations_list.is_empty() {
+                println!(
+                    "{} Base '{}' à jour (version {})",
+                    "✓".green().bold(),
+                    db_path.bright_blue(),
+                    current
+                );
+                return Ok(());
+            }
+
+            let verb = if dry_run { "à appliquer" } else { "appliquée(s)" };
+            println!(
+                "{} {} migration(s) {} sur '{}' (version actuelle: {})",
+                "🗄".bright_blue().bold(),
+                migrations_list.len(),
+                verb,
+                db_path.bright_blue(),
+                current
+            );
+            for migration in migrations_list {
+                println!(
+                    "  • {:03} {}",
+                    migration.version,
+                    migration.description
+                );
+            }
+        }
+    }
+    
+    Ok(())
+}
+
 fn run() -> Result<()> {
     let cli = Cli::parse();
     
-    // Créer le stockage (par défaut JSON)
-    let storage = create_storage("json")?;
+    // Créer le stockage choisi (fichier de configuration, RTF_*, options globales)
+    let config = Config::resolve(cli.storage.clone().map(Into::into), cli.data_dir.clone(), cli.timezone)?;
+    UserTimeZone::set_current(config.timezone);
+    
+    // Verrouiller le répertoire de données jusqu'à la fin de la commande
+    let data_dir = config.data_dir()?;
+    let _lock = StorageLock::acquire(&data_dir)?;
+    
+    // Ces commandes ouvrent leurs stockages elles-mêmes : ouvrir une base
+    // SQLite applique ses migrations
+    let command = match cli.command {
+        Commands::MigrateStorage { to, from, force } => return migrate_storage(to, from, force, &config),
+        Commands::Db { action } => return db_command(action, &config),
+        command => command,
+    };
+    let storage = create_storage(&config)?;
     
-    // Charger les tâches existantes
+    // Charger les tâches existantes et le journal des opérations
     let mut task_manager = TaskManager::new();
     let tasks = storage.load_tasks()?;
     task_manager.load_tasks(tasks);
+    task_manager.set_journal(Journal::load(&data_dir)?);
     
     // Traiter la commande
-    match cli.command {
+    match command {
         Commands::Add {
             title,
             description,
             priority,
             tags,
             due,
+            parent,
+            repeat,
         } => {
+            let recurrence = repeat
+                .map(|rule| rule.parse::<Recurrence>())
+                .transpose()
+                .map_err(|_| anyhow!("Récurrence invalide (daily, weekly, monthly, yearly ou \"every 2 weeks on mon,thu\")"))?;
+
+            // La tâche parente est résolue avant la création de la sous-tâche
+            let parent_id = match parent {
+                Some(parent) => {
+                    let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+                    Some(
+                        find_task_by_partial_id(&parent, &task_ids)
+                            .ok_or_else(|| anyhow!("Tâche parente avec l'ID '{}' non trouvée", parent))?,
+                    )
+                }
+                None => None,
+            };
+
             let task_id = task_manager.add_task(title.clone());
+            task_manager.set_task_parent(&task_id, parent_id)?;
+            if recurrence.is_some() {
+                task_manager.set_task_recurrence(&task_id, recurrence)?;
+            }
             
             // Ajouter la description si fournie
             if let Some(desc) = description {
@@ -62,12 +309,11 @@ fn run() -> Result<()> {
             
             // Ajouter la date d'échéance si fournie
             if let Some(due_str) = due {
-                let due_date = parse_date(&due_str)
-                    .map_err(|_| anyhow!("Format de date invalide: {}", due_str))?;
+                let due_date = parse_date(&due_str)?;
                 task_manager.set_task_due_date(&task_id, Some(due_date))?;
             }
             
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Ajouter '{}'", title))?;
             
             println!(
                 "{} Tâche '{}' ajoutée avec l'ID {}",
@@ -82,6 +328,8 @@ fn run() -> Result<()> {
             priority,
             tags,
             overdue,
+            due_before,
+            due_after,
             limit,
         } => {
             let mut filter = TaskFilter::default();
@@ -99,11 +347,13 @@ fn run() -> Result<()> {
             }
             
             filter.overdue_only = overdue;
+            filter.due_before = due_before.as_deref().map(parse_date).transpose()?;
+            filter.due_after = due_after.as_deref().map(parse_date).transpose()?;
             
             let mut tasks = task_manager.filter_tasks(&filter);
             
             // Trier par date de création (plus récent en premier)
-            tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            tasks.sort_by_key(|t| std::cmp::Reverse(t.created_at));
             
             // Limiter le nombre de résultats
             if let Some(limit_count) = limit {
@@ -113,7 +363,7 @@ fn run() -> Result<()> {
             if tasks.is_empty() {
                 println!("{}", "Aucune tâche trouvée.".yellow());
             } else {
-                TaskDisplay::print_task_list(&tasks);
+                TaskDisplay::print_task_list(&tasks, &task_manager.get_blocked_ids());
             }
         }
         
@@ -122,8 +372,23 @@ fn run() -> Result<()> {
             let task_id = find_task_by_partial_id(&id, &task_ids)
                 .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
             
-            task_manager.complete_task(&task_id)?;
-            storage.save_tasks(task_manager.export_tasks())?;
+            let open_subtasks = task_manager.get_open_subtasks(&task_id);
+            if !open_subtasks.is_empty() {
+                TaskDisplay::warning(&format!(
+                    "{} sous-tâche(s) encore ouverte(s):",
+                    open_subtasks.len()
+                ));
+                for subtask in open_subtasks {
+                    println!(
+                        "    [{}] {}",
+                        subtask.id.to_string()[..8].to_uppercase().bright_blue(),
+                        subtask.title
+                    );
+                }
+            }
+
+            let next_id = task_manager.complete_task(&task_id)?;
+            task_manager.commit(storage.as_ref(), format!("Terminer '{}'", task_manager.get_task(&task_id).unwrap().title))?;
             
             let task = task_manager.get_task(&task_id).unwrap();
             println!(
@@ -131,15 +396,26 @@ fn run() -> Result<()> {
                 "✓".green().bold(),
                 task.title
             );
+            if let Some(next) = next_id.and_then(|id| task_manager.get_task(&id)) {
+                println!(
+                    "{} Prochaine occurrence {} pour le {}",
+                    "🔁".blue().bold(),
+                    next.id.to_string()[..8].to_uppercase().bright_blue(),
+                    next.due_date
+                        .map(|d| d.with_timezone(&UserTimeZone::current()).format("%d/%m/%Y").to_string())
+                        .unwrap_or_default()
+                );
+            }
         }
         
-        Commands::Start { id } => {
+        Commands::Start { id, force } => {
             let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
             let task_id = find_task_by_partial_id(&id, &task_ids)
                 .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
             
+            check_blocking(&task_manager, &task_id, force)?;
             task_manager.start_task(&task_id)?;
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Démarrer '{}'", task_manager.get_task(&task_id).unwrap().title))?;
             
             let task = task_manager.get_task(&task_id).unwrap();
             println!(
@@ -155,7 +431,7 @@ fn run() -> Result<()> {
                 .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
             
             task_manager.cancel_task(&task_id)?;
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Annuler '{}'", task_manager.get_task(&task_id).unwrap().title))?;
             
             let task = task_manager.get_task(&task_id).unwrap();
             println!(
@@ -165,6 +441,38 @@ fn run() -> Result<()> {
             );
         }
         
+        Commands::Reopen { id } => {
+            let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+            let task_id = find_task_by_partial_id(&id, &task_ids)
+                .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+            
+            task_manager.reopen_task(&task_id)?;
+            task_manager.commit(storage.as_ref(), format!("Rouvrir '{}'", task_manager.get_task(&task_id).unwrap().title))?;
+            
+            let task = task_manager.get_task(&task_id).unwrap();
+            println!(
+                "{} Tâche '{}' rouverte",
+                "↺".yellow().bold(),
+                task.title
+            );
+        }
+        
+        Commands::Reset { id } => {
+            let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+            let task_id = find_task_by_partial_id(&id, &task_ids)
+                .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+            
+            task_manager.reset_task(&task_id)?;
+            task_manager.commit(storage.as_ref(), format!("Remettre à faire '{}'", task_manager.get_task(&task_id).unwrap().title))?;
+            
+            let task = task_manager.get_task(&task_id).unwrap();
+            println!(
+                "{} Tâche '{}' remise à faire",
+                "↺".yellow().bold(),
+                task.title
+            );
+        }
+        
         Commands::Delete { id, force } => {
             let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
             let task_id = find_task_by_partial_id(&id, &task_ids)
@@ -187,8 +495,9 @@ fn run() -> Result<()> {
                 }
             }
             
+            backup_before(storage.as_ref(), &config)?;
             task_manager.delete_task(&task_id)?;
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Supprimer '{}'", task_title))?;
             
             println!(
                 "{} Tâche '{}' supprimée",
@@ -221,12 +530,11 @@ fn run() -> Result<()> {
             }
             
             if let Some(due_str) = due {
-                let due_date = parse_date(&due_str)
-                    .map_err(|_| anyhow!("Format de date invalide: {}", due_str))?;
+                let due_date = parse_date(&due_str)?;
                 task_manager.set_task_due_date(&task_id, Some(due_date))?;
             }
             
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Modifier '{}'", task_manager.get_task(&task_id).unwrap().title))?;
             
             let task = task_manager.get_task(&task_id).unwrap();
             println!(
@@ -263,7 +571,7 @@ fn run() -> Result<()> {
                     tasks.len(),
                     query.bright_blue()
                 );
-                TaskDisplay::print_task_list(&tasks);
+                TaskDisplay::print_task_list(&tasks, &task_manager.get_blocked_ids());
             }
         }
         
@@ -273,7 +581,186 @@ fn run() -> Result<()> {
                 .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
             
             let task = task_manager.get_task(&task_id).unwrap();
-            TaskDisplay::print_task_details(task);
+            let parent = task.parent_id.and_then(|p| task_manager.get_task(&p));
+            TaskDisplay::print_task_details(
+                task,
+                parent,
+                &task_manager.get_subtask_tree(&task_id),
+                task_manager.task_progress(&task_id),
+                &task_manager.get_dependencies(&task_id),
+            );
+        }
+        
+        Commands::Depend { action } => {
+            let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+            let (id, depends_on) = match &action {
+                DependCommands::Add { id, depends_on } | DependCommands::Remove { id, depends_on } => (id, depends_on),
+            };
+            let task_id = find_task_by_partial_id(id, &task_ids)
+                .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+            let depends_on_id = find_task_by_partial_id(depends_on, &task_ids)
+                .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", depends_on))?;
+            let title = task_manager.get_task(&task_id).unwrap().title.clone();
+            let other = task_manager.get_task(&depends_on_id).unwrap().title.clone();
+            
+            match action {
+                DependCommands::Add { .. } => {
+                    task_manager.add_dependency(&task_id, &depends_on_id)?;
+                    task_manager.commit(storage.as_ref(), format!("'{}' dépend de '{}'", title, other))?;
+                    
+                    println!(
+                        "{} '{}' dépend maintenant de '{}'",
+                        "🔗".green().bold(),
+                        title,
+                        other.bright_blue()
+                    );
+                }
+                
+                DependCommands::Remove { .. } => {
+                    task_manager.remove_dependency(&task_id, &depends_on_id)?;
+                    task_manager.commit(storage.as_ref(), format!("'{}' ne dépend plus de '{}'", title, other))?;
+                    
+                    println!(
+                        "{} '{}' ne dépend plus de '{}'",
+                        "✓".green().bold(),
+                        title,
+                        other.bright_blue()
+                    );
+                }
+            }
+        }
+        
+        Commands::Recur { action } => {
+            let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+            
+            match action {
+                RecurCommands::Set { id, rule } => {
+                    let task_id = find_task_by_partial_id(&id, &task_ids)
+                        .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+                    let recurrence: Recurrence = rule
+                        .parse()
+                        .map_err(|_| anyhow!("Récurrence invalide: {}", rule))?;
+                    let human = recurrence.to_human();
+                    
+                    task_manager.set_task_recurrence(&task_id, Some(recurrence))?;
+                    let title = task_manager.get_task(&task_id).unwrap().title.clone();
+                    task_manager.commit(storage.as_ref(), format!("Répéter '{}' ({})", title, human))?;
+                    
+                    println!(
+                        "{} '{}' se répète désormais: {}",
+                        "🔁".green().bold(),
+                        title,
+                        human.bright_blue()
+                    );
+                }
+                
+                RecurCommands::Stop { id } => {
+                    let task_id = find_task_by_partial_id(&id, &task_ids)
+                        .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+                    
+                    let count = task_manager.stop_recurrence(&task_id)?;
+                    let title = task_manager.get_task(&task_id).unwrap().title.clone();
+                    task_manager.commit(storage.as_ref(), format!("Arrêter la série '{}'", title))?;
+                    
+                    println!(
+                        "{} Série '{}' arrêtée ({} tâche(s) modifiée(s))",
+                        "⏹".green().bold(),
+                        title,
+                        count
+                    );
+                }
+            }
+        }
+        
+        Commands::Timer { action } => match action {
+            TimerCommands::Start { id, force } => {
+                let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+                let task_id = find_task_by_partial_id(&id, &task_ids)
+                    .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+                
+                if task_manager.get_task(&task_id).unwrap().status == TaskStatus::Todo {
+                    check_blocking(&task_manager, &task_id, force)?;
+                }
+                task_manager.start_timer(&task_id)?;
+                let title = task_manager.get_task(&task_id).unwrap().title.clone();
+                task_manager.commit(storage.as_ref(), format!("Démarrer le minuteur de '{}'", title))?;
+                
+                println!(
+                    "{} Minuteur démarré sur '{}'",
+                    "⏱".blue().bold(),
+                    title
+                );
+            }
+            
+            TimerCommands::Stop => {
+                let (task_id, duration) = task_manager.stop_timer()?;
+                let task = task_manager.get_task(&task_id).unwrap();
+                let title = task.title.clone();
+                let total = task.time_spent(chrono::Utc::now());
+                task_manager.commit(storage.as_ref(), format!("Arrêter le minuteur de '{}'", title))?;
+                
+                println!(
+                    "{} Minuteur arrêté sur '{}': {} (total {})",
+                    "⏹".green().bold(),
+                    title,
+                    format_time_spent(&duration).bright_blue(),
+                    format_time_spent(&total)
+                );
+            }
+            
+            TimerCommands::Status => match task_manager.get_active_timer() {
+                Some(task) => {
+                    let now = chrono::Utc::now();
+                    let session = task.active_session().unwrap();
+                    println!(
+                        "{} [{}] '{}' depuis {} ({}), total {}",
+                        "⏱".blue().bold(),
+                        task.id.to_string()[..8].to_uppercase().bright_blue(),
+                        task.title,
+                        session.started_at.with_timezone(&UserTimeZone::current()).format("%H:%M"),
+                        format_time_spent(&session.duration(now)).bright_blue(),
+                        format_time_spent(&task.time_spent(now))
+                    );
+                }
+                None => println!("{}", "Aucun minuteur en cours.".yellow()),
+            },
+        },
+        
+        Commands::Log { id, duration } => {
+            let task_ids: Vec<_> = task_manager.get_all_tasks().iter().map(|t| t.id).collect();
+            let task_id = find_task_by_partial_id(&id, &task_ids)
+                .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
+            let duration = parse_duration(&duration)?;
+            
+            task_manager.log_time(&task_id, duration)?;
+            let task = task_manager.get_task(&task_id).unwrap();
+            let title = task.title.clone();
+            let total = task.time_spent(chrono::Utc::now());
+            task_manager.commit(storage.as_ref(), format!("Enregistrer {} sur '{}'", format_time_spent(&duration), title))?;
+            
+            println!(
+                "{} Temps enregistré sur '{}': {} (total {})",
+                "✓".green().bold(),
+                title,
+                format_time_spent(&duration).bright_blue(),
+                format_time_spent(&total)
+            );
+        }
+        
+        Commands::Graph { output } => {
+            let tasks = task_manager.get_all_tasks();
+            
+            if let Some(file_path) = output {
+                let file = std::fs::File::create(&file_path)?;
+                formats::dot::write_graph(&tasks, file)?;
+                println!(
+                    "{} Graphe des dépendances exporté vers '{}'",
+                    "💾".green().bold(),
+                    file_path.bright_blue()
+                );
+            } else {
+                formats::dot::write_graph(&tasks, std::io::stdout().lock())?;
+            }
         }
         
         Commands::Tag { action } => {
@@ -284,7 +771,7 @@ fn run() -> Result<()> {
                         .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
                     
                     task_manager.add_tag_to_task(&task_id, tag.clone())?;
-                    storage.save_tasks(task_manager.export_tasks())?;
+                    task_manager.commit(storage.as_ref(), format!("Ajouter le tag '{}' à '{}'", tag, task_manager.get_task(&task_id).unwrap().title))?;
                     
                     println!(
                         "{} Tag '{}' ajouté à la tâche",
@@ -299,7 +786,7 @@ fn run() -> Result<()> {
                         .ok_or_else(|| anyhow!("Tâche avec l'ID '{}' non trouvée", id))?;
                     
                     task_manager.remove_tag_from_task(&task_id, &tag)?;
-                    storage.save_tasks(task_manager.export_tasks())?;
+                    task_manager.commit(storage.as_ref(), format!("Retirer le tag '{}' de '{}'", tag, task_manager.get_task(&task_id).unwrap().title))?;
                     
                     println!(
                         "{} Tag '{}' supprimé de la tâche",
@@ -327,75 +814,93 @@ fn run() -> Result<()> {
             }
         }
         
-        Commands::Export { output, format } => {
+        Commands::Export {
+            output,
+            format,
+            group_by,
+        } => {
             let tasks = task_manager.export_tasks();
+            let list: Vec<&Task> = tasks.values().collect();
             
-            match format {
+            write_export(output, |out| match format {
                 cli::ExportFormat::Json => {
-                    let json = serde_json::to_string_pretty(tasks)?;
-                    
-                    if let Some(file_path) = output {
-                        std::fs::write(&file_path, json)?;
-                        println!(
-                            "{} Tâches exportées vers '{}'",
-                            "💾".green().bold(),
-                            file_path.bright_blue()
-                        );
-                    } else {
-                        println!("{}", json);
-                    }
+                    writeln!(out, "{}", serde_json::to_string_pretty(tasks)?)?;
+                    Ok(())
                 }
-                
-                cli::ExportFormat::Csv => {
-                    // Implémentation CSV basique
-                    let mut csv_content = String::from("ID,Title,Description,Status,Priority,Tags,Created,Updated,Due,Completed\n");
-                    
-                    for task in tasks.values() {
-                        let tags_str = task.tags.join(";");
-                        let description = task.description.as_deref().unwrap_or("");
-                        let due_date = task.due_date.map(|d| d.to_rfc3339()).unwrap_or_default();
-                        let completed_at = task.completed_at.map(|d| d.to_rfc3339()).unwrap_or_default();
-                        
-                        csv_content.push_str(&format!(
-                            "{},{},{},{},{},{},{},{},{},{}\n",
-                            task.id,
-                            task.title,
-                            description,
-                            task.status,
-                            task.priority,
-                            tags_str,
-                            task.created_at.to_rfc3339(),
-                            task.updated_at.to_rfc3339(),
-                            due_date,
-                            completed_at
-                        ));
-                    }
-                    
-                    if let Some(file_path) = output {
-                        std::fs::write(&file_path, csv_content)?;
-                        println!(
-                            "{} Tâches exportées vers '{}'",
-                            "💾".green().bold(),
-                            file_path.bright_blue()
-                        );
-                    } else {
-                        println!("{}", csv_content);
-                    }
-                }
-            }
+                cli::ExportFormat::Csv => formats::csv::write_tasks(&list, out),
+                cli::ExportFormat::Taskwarrior => formats::taskwarrior::write_tasks(&list, out),
+                cli::ExportFormat::Ical => formats::ical::write_tasks(&list, out),
+                cli::ExportFormat::Markdown => formats::markdown::write_tasks(&list, group_by.into(), out),
+                cli::ExportFormat::Todotxt => formats::todotxt::write_tasks(&list, out),
+            })?;
         }
         
-        Commands::Import { file, format: _ } => {
+        Commands::Import {
+            file,
+            format,
+            mode,
+            on_conflict,
+            dry_run,
+        } => {
             let content = std::fs::read_to_string(&file)
                 .map_err(|_| anyhow!("Impossible de lire le fichier '{}'", file))?;
             
-            let imported_tasks: std::collections::HashMap<uuid::Uuid, task::Task> = 
-                serde_json::from_str(&content)
-                    .map_err(|_| anyhow!("Format JSON invalide dans le fichier '{}'", file))?;
+            let imported_tasks: HashMap<Uuid, Task> = match format {
+                cli::ExportFormat::Json => serde_json::from_str(&content)
+                    .map_err(|_| anyhow!("Format JSON invalide dans le fichier '{}'", file))?,
+                cli::ExportFormat::Csv => accept_parsed(
+                    formats::csv::read_tasks(content.as_bytes())
+                        .map_err(|e| anyhow!("CSV invalide dans le fichier '{}': {}", file, e))?,
+                    &file,
+                )?,
+                cli::ExportFormat::Taskwarrior => accept_parsed(
+                    formats::taskwarrior::read_tasks(&content)
+                        .map_err(|e| anyhow!("Fichier '{}': {}", file, e))?,
+                    &file,
+                )?,
+                cli::ExportFormat::Ical => accept_parsed(
+                    formats::ical::read_tasks(&content)
+                        .map_err(|e| anyhow!("iCalendar invalide dans le fichier '{}': {}", file, e))?,
+                    &file,
+                )?,
+                cli::ExportFormat::Markdown => {
+                    let mut tasks = accept_parsed(formats::markdown::read_tasks(&content), &file)?;
+                    formats::reconcile_plain_text(&mut tasks, task_manager.export_tasks(), true)?;
+                    tasks
+                }
+                cli::ExportFormat::Todotxt => {
+                    let mut tasks = accept_parsed(formats::todotxt::read_tasks(&content), &file)?;
+                    formats::reconcile_plain_text(&mut tasks, task_manager.export_tasks(), false)?;
+                    tasks
+                }
+            };
+            
+            let plan = plan_import(
+                task_manager.export_tasks(),
+                imported_tasks,
+                mode.into(),
+                |local, incoming| match on_conflict {
+                    CliConflict::Newest => newest_wins(local, incoming),
+                    CliConflict::Local => Ok(Resolution::KeepLocal),
+                    CliConflict::Incoming => Ok(Resolution::TakeIncoming),
+                    CliConflict::Ask => ask_resolution(local, incoming),
+                },
+            )?;
+            
+            TaskDisplay::print_import_plan(&plan);
+            
+            if dry_run {
+                println!("{}", "Simulation: aucune modification enregistrée.".yellow());
+                return Ok(());
+            }
+            if plan.is_empty() {
+                return Ok(());
+            }
             
-            let count = imported_tasks.len();
-            task_manager.load_tasks(imported_tasks);
-            storage.save_tasks(task_manager.export_tasks())?;
+            let count = plan.added.len() + plan.updated.len();
+            backup_before(storage.as_ref(), &config)?;
+            task_manager.apply_import(plan);
+            task_manager.commit(storage.as_ref(), format!("Importer {} tâche(s) depuis '{}'", count, file))?;
             
             println!(
                 "{} {} tâche(s) importée(s) depuis '{}'",
@@ -408,7 +913,7 @@ fn run() -> Result<()> {
         Commands::Clean { days, force } => {
             let cutoff_date = chrono::Utc::now() - chrono::Duration::days(days as i64);
             let completed_tasks: Vec<_> = task_manager
-                .get_tasks_by_status(Status::Completed)
+                .get_tasks_by_status(TaskStatus::Completed)
                 .into_iter()
                 .filter(|task| {
                     task.completed_at
@@ -445,6 +950,7 @@ fn run() -> Result<()> {
                 }
             }
             
+            backup_before(storage.as_ref(), &config)?;
             let task_ids_to_delete: Vec<_> = completed_tasks.iter().map(|task| task.id).collect();
             let mut deleted_count = 0;
             for task_id in task_ids_to_delete {
@@ -452,7 +958,7 @@ fn run() -> Result<()> {
                 deleted_count += 1;
             }
             
-            storage.save_tasks(task_manager.export_tasks())?;
+            task_manager.commit(storage.as_ref(), format!("Nettoyer {} tâche(s) terminée(s)", deleted_count))?;
             
             println!(
                 "{} {} tâche(s) supprimée(s)",
@@ -460,6 +966,187 @@ fn run() -> Result<()> {
                 deleted_count
             );
         }
+        
+        // Traitées avant l'ouverture du stockage
+        Commands::MigrateStorage { .. } | Commands::Db { .. } => unreachable!(),
+        
+        Commands::Undo { count } => {
+            for _ in 0..count {
+                match task_manager.undo(storage.as_ref())? {
+                    Some(operation) => println!(
+                        "{} Annulé: {}",
+                        "↶".yellow().bold(),
+                        operation.label
+                    ),
+                    None => {
+                        println!("{}", "Rien à annuler.".yellow());
+                        break;
+                    }
+                }
+            }
+        }
+        
+        Commands::Redo { count } => {
+            for _ in 0..count {
+                match task_manager.redo(storage.as_ref())? {
+                    Some(operation) => println!(
+                        "{} Rétabli: {}",
+                        "↷".green().bold(),
+                        operation.label
+                    ),
+                    None => {
+                        println!("{}", "Rien à rétablir.".yellow());
+                        break;
+                    }
+                }
+            }
+        }
+        
+        Commands::History { limit } => {
+            let journal = task_manager.journal();
+            let operations: Vec<_> = journal.history().take(limit).collect();
+            
+            if operations.is_empty() {
+                println!("{}", "Aucune opération dans l'historique.".yellow());
+            } else {
+                println!("{} Dernières opérations:", "📜".bright_blue().bold());
+                for (index, operation) in operations.iter().enumerate() {
+                    println!(
+                        "  {:>3}  {}  {} {}",
+                        index + 1,
+                        operation.recorded_at
+                            .with_timezone(&UserTimeZone::current())
+                            .format("%d/%m/%Y %H:%M")
+                            .to_string()
+                            .bright_black(),
+                        operation.label,
+                        format!("({} tâche(s))", operation.changes.len()).bright_black()
+                    );
+                }
+            }
+            
+            if journal.redo_count() > 0 {
+                println!(
+                    "  {} opération(s) annulée(s) peuvent être rétablies avec `rtf redo`.",
+                    journal.redo_count()
+                );
+            }
+        }
+        
+        Commands::Backup { action } => match action {
+            BackupCommands::Create => {
+                let backup = backup_before(storage.as_ref(), &config)?;
+                println!(
+                    "{} Sauvegarde {} créée ({})",
+                    "💾".green().bold(),
+                    backup.id.bright_blue(),
+                    backup.path.to_string_lossy()
+                );
+            }
+            
+            BackupCommands::List => {
+                let backups = storage.backup_dir().list()?;
+                if backups.is_empty() {
+                    println!("{} Aucune sauvegarde.", "💾".yellow());
+                    return Ok(());
+                }
+                
+                println!("{} {} sauvegarde(s):", "💾".bright_blue().bold(), backups.len());
+                for backup in backups {
+                    println!(
+                        "  {}  {}  {} octets",
+                        backup.id.bright_blue(),
+                        backup.created_at.with_timezone(&UserTimeZone::current()).format("%d/%m/%Y %H:%M:%S"),
+                        backup.size
+                    );
+                }
+            }
+            
+            BackupCommands::Restore { id, force } => {
+                let backup = storage.backup_dir().find(&id)?;
+                
+                if !force {
+                    print!(
+                        "Remplacer toutes les tâches par la sauvegarde {} ? (y/N): ",
+                        backup.id
+                    );
+                    use std::io::{self, Write};
+                    io::stdout().flush()?;
+                    
+                    let mut input = String::new();
+                    io::stdin().read_line(&mut input)?;
+                    
+                    if !input.trim().to_lowercase().starts_with('y') {
+                        println!("Restauration annulée.");
+                        return Ok(());
+                    }
+                }
+                
+                // L'état courant est sauvegardé avant d'être remplacé ; la
+                // rotation attend la fin pour ne pas supprimer la source
+                let current = storage.backup()?;
+                let count = storage.restore_backup(&backup)?;
+                storage.backup_dir().prune(&config.backup)?;
+                
+                println!(
+                    "{} {} tâche(s) restaurée(s) depuis la sauvegarde {}",
+                    "♻".green().bold(),
+                    count,
+                    backup.id.bright_blue()
+                );
+                println!(
+                    "  L'état précédent a été sauvegardé sous {}.",
+                    current.id.bright_blue()
+                );
+            }
+            
+            BackupCommands::Diff { id } => {
+                let backup = storage.backup_dir().find(&id)?;
+                let backup_tasks = storage.load_backup(&backup)?;
+                let current_tasks = storage.load_tasks()?;
+                let changes = diff_tasks(&backup_tasks, &current_tasks)?;
+                
+                if changes.is_empty() {
+                    println!(
+                        "{} Aucune différence avec la sauvegarde {}",
+                        "✓".green().bold(),
+                        backup.id.bright_blue()
+                    );
+                    return Ok(());
+                }
+                
+                println!(
+                    "{} {} différence(s) depuis la sauvegarde {}:",
+                    "🔍".bright_blue().bold(),
+                    changes.len(),
+                    backup.id.bright_blue()
+                );
+                for change in changes {
+                    let id_short = change.id.to_string()[..8].to_uppercase();
+                    match change.kind {
+                        ChangeKind::Added => println!(
+                            "  {} [{}] {}",
+                            "+".green().bold(),
+                            id_short,
+                            change.title
+                        ),
+                        ChangeKind::Removed => println!(
+                            "  {} [{}] {}",
+                            "-".red().bold(),
+                            id_short,
+                            change.title
+                        ),
+                        ChangeKind::Modified(fields) => println!(
+                            "  {} [{}] {} ({})",
+                            "~".yellow().bold(),
+                            id_short,
+                            change.title,
+                            fields.join(", ").bright_black()
+                        ),
+                    }
+                }
+            }
+        },
     }
     
     Ok(())
@@ -0,0 +1,193 @@
+//! Migrations versionnées de la base SQLite du CLI
+//!
+//! Les scripts de `migrations/` sont embarqués dans le binaire, comme le fait
+//! `sqlx::migrate!` pour la base PostgreSQL du serveur web, et appliqués dans
+//! l'ordre à l'ouverture de la base. La version atteinte est enregistrée
+//! dans la table `schema_version`.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+/// Une migration embarquée
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// Toutes les migrations connues, par ordre de version
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial",
+        sql: include_str!("../migrations/001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "task lifecycle",
+        sql: include_str!("../migrations/002_task_lifecycle.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "task extra attributes",
+        sql: include_str!("../migrations/003_task_extra.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "subtasks",
+        sql: include_str!("../migrations/004_task_parent.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "task dependencies",
+        sql: include_str!("../migrations/005_task_dependencies.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "recurring tasks",
+        sql: include_str!("../migrations/006_task_recurrence.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "time tracking",
+        sql: include_str!("../migrations/007_task_work_sessions.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "task projects",
+        sql: include_str!("../migrations/008_task_project_id.sql"),
+    },
+];
+
+fn ensure_version_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )
+        "#,
+        [],
+    )?;
+    Ok(())
+}
+
+/// Version actuelle du schéma (0 pour une base vierge ou antérieure au
+/// versionnage). Ne modifie pas la base, qui peut être ouverte en lecture seule.
+pub fn current_version(conn: &Connection) -> Result<i64> {
+    let versioned: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !versioned {
+        return Ok(0);
+    }
+    let version: Option<i64> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Migrations qui restent à appliquer
+pub fn pending_migrations(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if current > latest {
+        return Err(anyhow!(
+            "La base est en version {} mais ce binaire ne connaît que la version {}",
+            current,
+            latest
+        ));
+    }
+    Ok(MIGRATIONS.iter().filter(|m| m.version > current).collect())
+}
+
+/// Applique les migrations en attente, chacune dans sa propre transaction
+pub fn run_migrations(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    ensure_version_table(conn)?;
+    let pending = pending_migrations(conn)?;
+
+    for migration in &pending {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration.sql).map_err(|e| {
+            anyhow!(
+                "Échec de la migration {} ({}): {}",
+                migration.version,
+                migration.description,
+                e
+            )
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.description, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version);
+        }
+    }
+
+    #[test]
+    fn test_run_migrations_on_fresh_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(pending_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+
+        let applied = run_migrations(&conn).unwrap();
+        assert_eq!(applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+
+        // Une seconde exécution ne fait rien
+        assert!(run_migrations(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_unversioned_database() {
+        // Schéma créé par les versions antérieures au versionnage
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE tasks (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                tags TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                due_date TEXT,
+                completed_at TEXT
+            );
+            INSERT INTO tasks VALUES (
+                'a3c1f0d2-5b6e-4f7a-8b9c-0d1e2f3a4b5c', 'Old', NULL, 'In Progress', 'High', '[]',
+                '2024-01-15T10:00:00+00:00', '2024-01-15T10:00:00+00:00', NULL, NULL
+            );
+            "#,
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        let (status, created_by): (String, String) = conn
+            .query_row("SELECT status, created_by FROM tasks", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(status, "InProgress");
+        assert_eq!(created_by, "00000000-0000-0000-0000-000000000000");
+    }
+}
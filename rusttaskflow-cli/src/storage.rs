@@ -1,16 +1,79 @@
-use crate::task::Task;
+use crate::backup::{Backup, BackupDir};
+use crate::config::{Config, StorageType};
+use crate::migrations;
+use crate::task::{Priority, Task, TaskStatus};
 use anyhow::{anyhow, Result};
-use rusqlite::{params, Connection, Row};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OpenFlags, Row};
 use serde_json;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::fs;
+use std::hash::{Hash, Hasher};
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::Path;
 use uuid::Uuid;
 
 pub trait Storage {
     fn save_tasks(&self, tasks: &HashMap<Uuid, Task>) -> Result<()>;
     fn load_tasks(&self) -> Result<HashMap<Uuid, Task>>;
-    fn backup(&self) -> Result<()>;
+    /// Insère ou met à jour une seule tâche
+    fn upsert_task(&self, task: &Task) -> Result<()>;
+    /// Supprime une seule tâche (sans erreur si elle n'existe pas)
+    fn delete_task(&self, id: &Uuid) -> Result<()>;
+    /// Crée une sauvegarde horodatée de l'état courant
+    fn backup(&self) -> Result<Backup>;
+    /// Répertoire où sont rangées les sauvegardes de ce stockage
+    fn backup_dir(&self) -> BackupDir;
+    /// Lit les tâches contenues dans une sauvegarde
+    fn load_backup(&self, backup: &Backup) -> Result<HashMap<Uuid, Task>>;
+
+    /// Remplace toutes les tâches par le contenu d'une sauvegarde
+    fn restore_backup(&self, backup: &Backup) -> Result<usize> {
+        let tasks = self.load_backup(backup)?;
+        self.save_tasks(&tasks)?;
+        Ok(tasks.len())
+    }
+
+    /// Applique un lot de modifications. L'implémentation par défaut appelle
+    /// `upsert_task`/`delete_task` pour chaque tâche ; les backends la
+    /// redéfinissent pour écrire le lot d'un seul coup.
+    fn save_changes(&self, upserted: &[&Task], deleted: &[Uuid]) -> Result<()> {
+        for task in upserted {
+            self.upsert_task(task)?;
+        }
+        for id in deleted {
+            self.delete_task(id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Écrit un fichier de façon atomique : le contenu est écrit dans un fichier
+/// temporaire voisin, synchronisé sur disque puis renommé. Un crash laisse
+/// donc soit l'ancien fichier, soit le nouveau, jamais un fichier tronqué.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Chemin de fichier invalide: {}", path.display()))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    
+    fs::rename(&tmp_path, path)?;
+    
+    // Synchroniser le répertoire pour rendre le renommage durable
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+    
+    Ok(())
 }
 
 /// Stockage JSON
@@ -23,25 +86,12 @@ impl JsonStorage {
         Self { file_path }
     }
 
-    pub fn default() -> Result<Self> {
-        let data_dir = dirs::data_dir()
-            .ok_or_else(|| anyhow!("Impossible de trouver le répertoire de données"))?;
-        let app_dir = data_dir.join("rusttaskflow");
-        
-        if !app_dir.exists() {
-            fs::create_dir_all(&app_dir)?;
-        }
-        
-        let file_path = app_dir.join("tasks.json").to_string_lossy().to_string();
-        Ok(Self::new(file_path))
-    }
 }
 
 impl Storage for JsonStorage {
     fn save_tasks(&self, tasks: &HashMap<Uuid, Task>) -> Result<()> {
         let json = serde_json::to_string_pretty(tasks)?;
-        fs::write(&self.file_path, json)?;
-        Ok(())
+        write_atomic(Path::new(&self.file_path), json.as_bytes())
     }
 
     fn load_tasks(&self) -> Result<HashMap<Uuid, Task>> {
@@ -58,161 +108,125 @@ impl Storage for JsonStorage {
         Ok(tasks)
     }
 
-    fn backup(&self) -> Result<()> {
-        let backup_path = format!("{}.backup", self.file_path);
-        if Path::new(&self.file_path).exists() {
-            fs::copy(&self.file_path, backup_path)?;
+    fn upsert_task(&self, task: &Task) -> Result<()> {
+        self.save_changes(&[task], &[])
+    }
+
+    fn delete_task(&self, id: &Uuid) -> Result<()> {
+        self.save_changes(&[], &[*id])
+    }
+
+    fn save_changes(&self, upserted: &[&Task], deleted: &[Uuid]) -> Result<()> {
+        // Le fichier JSON est réécrit en entier : on n'y touche qu'une fois par lot
+        let mut tasks = self.load_tasks()?;
+        for task in upserted {
+            tasks.insert(task.id, (*task).clone());
         }
-        Ok(())
+        for id in deleted {
+            tasks.remove(id);
+        }
+        self.save_tasks(&tasks)
+    }
+
+    fn backup(&self) -> Result<Backup> {
+        self.backup_dir().create(|path| {
+            if Path::new(&self.file_path).exists() {
+                fs::copy(&self.file_path, path)?;
+            } else {
+                write_atomic(path, b"{}")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn backup_dir(&self) -> BackupDir {
+        BackupDir::for_file(Path::new(&self.file_path))
+    }
+
+    fn load_backup(&self, backup: &Backup) -> Result<HashMap<Uuid, Task>> {
+        JsonStorage::new(backup.path.to_string_lossy().to_string()).load_tasks()
     }
 }
 
 /// Stockage SQLite
 pub struct SqliteStorage {
     db_path: String,
+    conn: Connection,
 }
 
 impl SqliteStorage {
     pub fn new(db_path: String) -> Result<Self> {
-        let storage = Self { db_path };
+        let conn = Connection::open(&db_path)?;
+        let storage = Self { db_path, conn };
         storage.init_database()?;
         Ok(storage)
     }
 
-    pub fn default() -> Result<Self> {
-        let data_dir = dirs::data_dir()
-            .ok_or_else(|| anyhow!("Impossible de trouver le répertoire de données"))?;
-        let app_dir = data_dir.join("rusttaskflow");
-        
-        if !app_dir.exists() {
-            fs::create_dir_all(&app_dir)?;
-        }
-        
-        let db_path = app_dir.join("tasks.db").to_string_lossy().to_string();
-        Self::new(db_path)
+    /// Ouvre une sauvegarde sans jamais l'écrire : le fichier est lu en
+    /// lecture seule et copié en mémoire, et seule la copie est migrée
+    pub fn open_backup(path: &Path) -> Result<Self> {
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut conn = Connection::open_in_memory()?;
+        rusqlite::backup::Backup::new(&source, &mut conn)?
+            .run_to_completion(100, std::time::Duration::ZERO, None)?;
+
+        let storage = Self {
+            db_path: path.to_string_lossy().to_string(),
+            conn,
+        };
+        storage.init_database()?;
+        Ok(storage)
     }
 
     fn init_database(&self) -> Result<()> {
-        let conn = Connection::open(&self.db_path)?;
-        
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT NOT NULL,
-                priority TEXT NOT NULL,
-                tags TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                due_date TEXT,
-                completed_at TEXT
-            )
-            "#,
-            [],
-        )?;
-        
+        migrations::run_migrations(&self.conn)?;
         Ok(())
     }
 
+    fn conversion_error<E>(idx: usize, e: E) -> rusqlite::Error
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    }
+
+    fn parse_uuid(idx: usize, value: &str) -> Result<Uuid, rusqlite::Error> {
+        Uuid::parse_str(value).map_err(|e| Self::conversion_error(idx, e))
+    }
+
+    fn parse_date(idx: usize, value: &str) -> Result<DateTime<Utc>, rusqlite::Error> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|d| d.with_timezone(&Utc))
+            .map_err(|e| Self::conversion_error(idx, e))
+    }
+
+    fn parse_optional_date(idx: usize, value: Option<String>) -> Result<Option<DateTime<Utc>>, rusqlite::Error> {
+        value.map(|v| Self::parse_date(idx, &v)).transpose()
+    }
+
     fn task_from_row(row: &Row) -> Result<(Uuid, Task), rusqlite::Error> {
-        let id_str: String = row.get(0)?;
-        let id = Uuid::parse_str(&id_str).map_err(|e| {
-            rusqlite::Error::FromSqlConversionFailure(
-                0,
-                rusqlite::types::Type::Text,
-                Box::new(e),
-            )
-        })?;
-        
+        let id = Self::parse_uuid(0, &row.get::<_, String>(0)?)?;
         let title: String = row.get(1)?;
         let description: Option<String> = row.get(2)?;
         let status_str: String = row.get(3)?;
         let priority_str: String = row.get(4)?;
         let tags_str: String = row.get(5)?;
-        let created_at_str: String = row.get(6)?;
-        let updated_at_str: String = row.get(7)?;
-        let due_date_str: Option<String> = row.get(8)?;
-        let completed_at_str: Option<String> = row.get(9)?;
-        
-        // Parse status
-        let status = match status_str.as_str() {
-            "Todo" => crate::task::Status::Todo,
-            "InProgress" => crate::task::Status::InProgress,
-            "Completed" => crate::task::Status::Completed,
-            "Cancelled" => crate::task::Status::Cancelled,
-            _ => crate::task::Status::Todo,
-        };
         
-        // Parse priority
-        let priority = match priority_str.as_str() {
-            "Low" => crate::task::Priority::Low,
-            "Medium" => crate::task::Priority::Medium,
-            "High" => crate::task::Priority::High,
-            "Critical" => crate::task::Priority::Critical,
-            _ => crate::task::Priority::Medium,
-        };
+        // Parse status et priorité (identifiants stables, cf. `as_str`)
+        let status: TaskStatus = status_str.parse().map_err(|e| Self::conversion_error(3, e))?;
+        let priority: Priority = priority_str.parse().map_err(|e| Self::conversion_error(4, e))?;
         
         // Parse tags
         let tags: Vec<String> = if tags_str.is_empty() {
             Vec::new()
         } else {
-            serde_json::from_str(&tags_str).unwrap_or_default()
+            serde_json::from_str(&tags_str).map_err(|e| Self::conversion_error(5, e))?
         };
         
-        // Parse dates
-        let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
-            .map_err(|e| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    6,
-                    rusqlite::types::Type::Text,
-                    Box::new(e),
-                )
-            })?
-            .with_timezone(&chrono::Utc);
-        
-        let updated_at = chrono::DateTime::parse_from_rfc3339(&updated_at_str)
-            .map_err(|e| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    7,
-                    rusqlite::types::Type::Text,
-                    Box::new(e),
-                )
-            })?
-            .with_timezone(&chrono::Utc);
-        
-        let due_date = if let Some(due_str) = due_date_str {
-            Some(
-                chrono::DateTime::parse_from_rfc3339(&due_str)
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            8,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?
-                    .with_timezone(&chrono::Utc),
-            )
-        } else {
-            None
-        };
-        
-        let completed_at = if let Some(completed_str) = completed_at_str {
-            Some(
-                chrono::DateTime::parse_from_rfc3339(&completed_str)
-                    .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            9,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?
-                    .with_timezone(&chrono::Utc),
-            )
-        } else {
-            None
-        };
+        let assigned_to = row
+            .get::<_, Option<String>>(13)?
+            .map(|s| Self::parse_uuid(13, &s))
+            .transpose()?;
         
         let task = Task {
             id,
@@ -221,57 +235,127 @@ impl SqliteStorage {
             status,
             priority,
             tags,
-            created_at,
-            updated_at,
-            due_date,
-            completed_at,
+            created_at: Self::parse_date(6, &row.get::<_, String>(6)?)?,
+            updated_at: Self::parse_date(7, &row.get::<_, String>(7)?)?,
+            started_at: Self::parse_optional_date(10, row.get(10)?)?,
+            completed_at: Self::parse_optional_date(9, row.get(9)?)?,
+            cancelled_at: Self::parse_optional_date(11, row.get(11)?)?,
+            reopened_at: Self::parse_optional_date(12, row.get(12)?)?,
+            due_date: Self::parse_optional_date(8, row.get(8)?)?,
+            assigned_to,
+            created_by: Self::parse_uuid(14, &row.get::<_, String>(14)?)?,
+            parent_id: row
+                .get::<_, Option<String>>(16)?
+                .map(|s| Self::parse_uuid(16, &s))
+                .transpose()?,
+            depends_on: serde_json::from_str(&row.get::<_, String>(17)?)
+                .map_err(|e| Self::conversion_error(17, e))?,
+            recurrence: row
+                .get::<_, Option<String>>(18)?
+                .map(|s| s.parse().map_err(|e| Self::conversion_error(18, e)))
+                .transpose()?,
+            series_id: row
+                .get::<_, Option<String>>(19)?
+                .map(|s| Self::parse_uuid(19, &s))
+                .transpose()?,
+            project_id: row
+                .get::<_, Option<String>>(21)?
+                .map(|s| Self::parse_uuid(21, &s))
+                .transpose()?,
+            work_sessions: serde_json::from_str(&row.get::<_, String>(20)?)
+                .map_err(|e| Self::conversion_error(20, e))?,
+            extra: serde_json::from_str(&row.get::<_, String>(15)?)
+                .map_err(|e| Self::conversion_error(15, e))?,
         };
         
         Ok((id, task))
     }
+
+    fn upsert_with(conn: &Connection, task: &Task) -> Result<()> {
+        let tags_json = serde_json::to_string(&task.tags)?;
+        let extra_json = serde_json::to_string(&task.extra)?;
+        let depends_on_json = serde_json::to_string(&task.depends_on)?;
+        let work_sessions_json = serde_json::to_string(&task.work_sessions)?;
+        
+        conn.execute(
+            r#"
+            INSERT INTO tasks (
+                id, title, description, status, priority, tags,
+                created_at, updated_at, due_date, completed_at,
+                started_at, cancelled_at, reopened_at, assigned_to, created_by, extra, parent_id,
+                depends_on, recurrence, series_id, work_sessions, project_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)
+            ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                description = excluded.description,
+                status = excluded.status,
+                priority = excluded.priority,
+                tags = excluded.tags,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                due_date = excluded.due_date,
+                completed_at = excluded.completed_at,
+                started_at = excluded.started_at,
+                cancelled_at = excluded.cancelled_at,
+                reopened_at = excluded.reopened_at,
+                assigned_to = excluded.assigned_to,
+                created_by = excluded.created_by,
+                extra = excluded.extra,
+                parent_id = excluded.parent_id,
+                depends_on = excluded.depends_on,
+                recurrence = excluded.recurrence,
+                series_id = excluded.series_id,
+                work_sessions = excluded.work_sessions,
+                project_id = excluded.project_id
+            "#,
+            params![
+                task.id.to_string(),
+                task.title,
+                task.description,
+                task.status.as_str(),
+                task.priority.as_str(),
+                tags_json,
+                task.created_at.to_rfc3339(),
+                task.updated_at.to_rfc3339(),
+                task.due_date.map(|d| d.to_rfc3339()),
+                task.completed_at.map(|d| d.to_rfc3339()),
+                task.started_at.map(|d| d.to_rfc3339()),
+                task.cancelled_at.map(|d| d.to_rfc3339()),
+                task.reopened_at.map(|d| d.to_rfc3339()),
+                task.assigned_to.map(|id| id.to_string()),
+                task.created_by.to_string(),
+                extra_json,
+                task.parent_id.map(|id| id.to_string()),
+                depends_on_json,
+                task.recurrence.as_ref().map(|r| r.to_string()),
+                task.series_id.map(|id| id.to_string()),
+                work_sessions_json,
+                task.project_id.map(|id| id.to_string()),
+            ],
+        )?;
+        
+        Ok(())
+    }
 }
 
 impl Storage for SqliteStorage {
     fn save_tasks(&self, tasks: &HashMap<Uuid, Task>) -> Result<()> {
-        let conn = Connection::open(&self.db_path)?;
-        
-        // Vider la table
-        conn.execute("DELETE FROM tasks", [])?;
+        let tx = self.conn.unchecked_transaction()?;
         
-        // Insérer toutes les tâches
+        // Remplacer le contenu de la table dans une seule transaction
+        tx.execute("DELETE FROM tasks", [])?;
         for task in tasks.values() {
-            let tags_json = serde_json::to_string(&task.tags)?;
-            
-            conn.execute(
-                r#"
-                INSERT INTO tasks (
-                    id, title, description, status, priority, tags,
-                    created_at, updated_at, due_date, completed_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-                "#,
-                params![
-                    task.id.to_string(),
-                    task.title,
-                    task.description,
-                    task.status.to_string(),
-                    task.priority.to_string(),
-                    tags_json,
-                    task.created_at.to_rfc3339(),
-                    task.updated_at.to_rfc3339(),
-                    task.due_date.map(|d| d.to_rfc3339()),
-                    task.completed_at.map(|d| d.to_rfc3339()),
-                ],
-            )?;
+            Self::upsert_with(&tx, task)?;
         }
         
+        tx.commit()?;
         Ok(())
     }
 
     fn load_tasks(&self) -> Result<HashMap<Uuid, Task>> {
-        let conn = Connection::open(&self.db_path)?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT id, title, description, status, priority, tags, created_at, updated_at, due_date, completed_at FROM tasks"
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, description, status, priority, tags, created_at, updated_at, due_date, completed_at, \
+             started_at, cancelled_at, reopened_at, assigned_to, created_by, extra, parent_id, depends_on, recurrence, series_id, work_sessions, project_id FROM tasks"
         )?;
         
         let task_iter = stmt.query_map([], Self::task_from_row)?;
@@ -285,28 +369,106 @@ impl Storage for SqliteStorage {
         Ok(tasks)
     }
 
-    fn backup(&self) -> Result<()> {
-        let backup_path = format!("{}.backup", self.db_path);
-        if Path::new(&self.db_path).exists() {
-            fs::copy(&self.db_path, backup_path)?;
+    fn upsert_task(&self, task: &Task) -> Result<()> {
+        Self::upsert_with(&self.conn, task)
+    }
+
+    fn delete_task(&self, id: &Uuid) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM tasks WHERE id = ?1", params![id.to_string()])?;
+        Ok(())
+    }
+
+    fn save_changes(&self, upserted: &[&Task], deleted: &[Uuid]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        
+        for task in upserted {
+            Self::upsert_with(&tx, task)?;
         }
+        for id in deleted {
+            tx.execute("DELETE FROM tasks WHERE id = ?1", params![id.to_string()])?;
+        }
+        
+        tx.commit()?;
         Ok(())
     }
+
+    fn backup(&self) -> Result<Backup> {
+        // VACUUM INTO produit une copie cohérente même si la base est ouverte
+        self.backup_dir().create(|path| {
+            self.conn
+                .execute("VACUUM INTO ?1", params![path.to_string_lossy().to_string()])?;
+            Ok(())
+        })
+    }
+
+    fn backup_dir(&self) -> BackupDir {
+        BackupDir::for_file(Path::new(&self.db_path))
+    }
+
+    fn load_backup(&self, backup: &Backup) -> Result<HashMap<Uuid, Task>> {
+        // Une sauvegarde ancienne est mise au schéma courant, en mémoire
+        SqliteStorage::open_backup(&backup.path)?.load_tasks()
+    }
 }
 
-/// Factory pour créer le bon type de stockage
-pub fn create_storage(storage_type: &str) -> Result<Box<dyn Storage>> {
-    match storage_type.to_lowercase().as_str() {
-        "json" => Ok(Box::new(JsonStorage::default()?)),
-        "sqlite" => Ok(Box::new(SqliteStorage::default()?)),
-        _ => Err(anyhow!("Type de stockage non supporté: {}", storage_type)),
+/// Empreinte d'une tâche, calculée sur sa forme sérialisée complète
+pub fn task_checksum(task: &Task) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(task)?.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Copie toutes les tâches d'un stockage vers un autre, puis relit la
+/// destination pour vérifier le nombre de tâches et l'empreinte de chacune.
+/// Le stockage source n'est pas modifié.
+pub fn transfer_tasks(source: &dyn Storage, destination: &dyn Storage) -> Result<usize> {
+    let tasks = source.load_tasks()?;
+    destination.save_tasks(&tasks)?;
+    
+    let copied = destination.load_tasks()?;
+    if copied.len() != tasks.len() {
+        return Err(anyhow!(
+            "Vérification échouée: {} tâche(s) lue(s) après copie au lieu de {}",
+            copied.len(),
+            tasks.len()
+        ));
+    }
+    
+    for (id, task) in &tasks {
+        let copy = copied
+            .get(id)
+            .ok_or_else(|| anyhow!("Vérification échouée: tâche {} absente après copie", id))?;
+        if task_checksum(copy)? != task_checksum(task)? {
+            return Err(anyhow!(
+                "Vérification échouée: tâche {} ('{}') modifiée par la copie",
+                id,
+                task.title
+            ));
+        }
+    }
+    
+    Ok(tasks.len())
+}
+
+/// Factory pour créer le stockage choisi par la configuration
+pub fn create_storage(config: &Config) -> Result<Box<dyn Storage>> {
+    match config.storage {
+        StorageType::Json => {
+            let path = config.json_path()?;
+            Ok(Box::new(JsonStorage::new(path.to_string_lossy().to_string())))
+        }
+        StorageType::Sqlite => {
+            let path = config.sqlite_path()?;
+            Ok(Box::new(SqliteStorage::new(path.to_string_lossy().to_string())?))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::Task;
+    use crate::task::new_task;
     use tempfile::tempdir;
 
     #[test]
@@ -316,7 +478,7 @@ mod tests {
         let storage = JsonStorage::new(file_path.to_string_lossy().to_string());
         
         let mut tasks = HashMap::new();
-        let task = Task::new("Test task".to_string());
+        let task = new_task("Test task".to_string());
         let task_id = task.id;
         tasks.insert(task_id, task);
         
@@ -329,6 +491,27 @@ mod tests {
         assert!(loaded_tasks.contains_key(&task_id));
     }
 
+    #[test]
+    fn test_json_save_is_atomic() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("tasks.json");
+        let storage = JsonStorage::new(file_path.to_string_lossy().to_string());
+        
+        let mut tasks = HashMap::new();
+        let task = new_task("Test task".to_string());
+        tasks.insert(task.id, task);
+        storage.save_tasks(&tasks).unwrap();
+        storage.save_tasks(&tasks).unwrap();
+        
+        // Aucun fichier temporaire ne doit rester après l'écriture
+        let entries: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("tasks.json")]);
+        assert_eq!(storage.load_tasks().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_sqlite_storage() {
         let temp_dir = tempdir().unwrap();
@@ -336,7 +519,7 @@ mod tests {
         let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
         
         let mut tasks = HashMap::new();
-        let task = Task::new("Test task".to_string());
+        let task = new_task("Test task".to_string());
         let task_id = task.id;
         tasks.insert(task_id, task);
         
@@ -348,4 +531,236 @@ mod tests {
         assert_eq!(loaded_tasks.len(), 1);
         assert!(loaded_tasks.contains_key(&task_id));
     }
+
+    #[test]
+    fn test_sqlite_upsert_and_delete() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+        
+        let mut task = new_task("Test task".to_string());
+        let other = new_task("Other task".to_string());
+        storage.save_changes(&[&task, &other], &[]).unwrap();
+        
+        task.set_title("Renamed task".to_string());
+        storage.upsert_task(&task).unwrap();
+        storage.delete_task(&other.id).unwrap();
+        
+        let loaded_tasks = storage.load_tasks().unwrap();
+        assert_eq!(loaded_tasks.len(), 1);
+        assert_eq!(loaded_tasks[&task.id].title, "Renamed task");
+    }
+
+    #[test]
+    fn test_sqlite_round_trips_lifecycle_fields() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+        
+        let mut task = new_task("Test task".to_string());
+        task.start().unwrap();
+        task.log_time(chrono::Duration::minutes(45), task.created_at).unwrap();
+        task.start_timer(task.created_at).unwrap();
+        task.cancel().unwrap();
+        task.assigned_to = Some(Uuid::new_v4());
+        task.extra.insert("project".to_string(), serde_json::json!("Q1"));
+        task.parent_id = Some(Uuid::new_v4());
+        task.depends_on = vec![Uuid::new_v4(), Uuid::new_v4()];
+        task.recurrence = Some("every 2 weeks on mon,thu".parse().unwrap());
+        task.series_id = Some(Uuid::new_v4());
+        task.project_id = Some(Uuid::new_v4());
+        storage.upsert_task(&task).unwrap();
+        
+        let loaded = &storage.load_tasks().unwrap()[&task.id];
+        assert_eq!(loaded.status, TaskStatus::Cancelled);
+        assert_eq!(loaded.started_at, task.started_at);
+        assert_eq!(loaded.cancelled_at, task.cancelled_at);
+        assert_eq!(loaded.assigned_to, task.assigned_to);
+        assert_eq!(loaded.created_by, task.created_by);
+        assert_eq!(loaded.extra, task.extra);
+        assert_eq!(loaded.parent_id, task.parent_id);
+        assert_eq!(loaded.depends_on, task.depends_on);
+        assert_eq!(loaded.recurrence, task.recurrence);
+        assert_eq!(loaded.series_id, task.series_id);
+        assert_eq!(loaded.project_id, task.project_id);
+        assert_eq!(loaded.work_sessions.len(), 2);
+        assert_eq!(loaded.work_sessions, task.work_sessions);
+    }
+
+    #[test]
+    fn test_sqlite_rejects_unknown_values() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+        storage.upsert_task(&new_task("Test task".to_string())).unwrap();
+        
+        // Une valeur illisible est une erreur, jamais une valeur par défaut
+        for (column, value) in [("status", "Paused"), ("priority", "Urgent"), ("tags", "work")] {
+            let saved: String = storage
+                .conn
+                .query_row(&format!("SELECT {} FROM tasks", column), [], |row| row.get(0))
+                .unwrap();
+            storage
+                .conn
+                .execute(&format!("UPDATE tasks SET {} = ?1", column), params![value])
+                .unwrap();
+            assert!(storage.load_tasks().is_err(), "{}", column);
+            storage
+                .conn
+                .execute(&format!("UPDATE tasks SET {} = ?1", column), params![saved])
+                .unwrap();
+        }
+        assert_eq!(storage.load_tasks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json_save_changes() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("test_tasks.json");
+        let storage = JsonStorage::new(file_path.to_string_lossy().to_string());
+        
+        let task = new_task("Test task".to_string());
+        let other = new_task("Other task".to_string());
+        storage.save_changes(&[&task, &other], &[]).unwrap();
+        storage.save_changes(&[], &[other.id]).unwrap();
+        
+        let loaded_tasks = storage.load_tasks().unwrap();
+        assert_eq!(loaded_tasks.len(), 1);
+        assert!(loaded_tasks.contains_key(&task.id));
+    }
+
+    #[test]
+    fn test_transfer_tasks_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let json = JsonStorage::new(temp_dir.path().join("tasks.json").to_string_lossy().to_string());
+        let sqlite = SqliteStorage::new(temp_dir.path().join("tasks.db").to_string_lossy().to_string()).unwrap();
+        let json_back = JsonStorage::new(temp_dir.path().join("back.json").to_string_lossy().to_string());
+        
+        let mut tasks = HashMap::new();
+        let mut in_progress = new_task("Virgule, \"guillemets\"\net saut de ligne".to_string());
+        in_progress.set_description(Some("Description".to_string()));
+        in_progress.set_priority(Priority::Critical);
+        in_progress.add_tag("work".to_string());
+        in_progress.add_tag("urgent".to_string());
+        in_progress.set_due_date(Some(Utc::now()));
+        in_progress.start().unwrap();
+        let mut completed = new_task("Completed".to_string());
+        completed.complete().unwrap();
+        let mut reopened = new_task("Reopened".to_string());
+        reopened.cancel().unwrap();
+        reopened.reopen().unwrap();
+        reopened.assigned_to = Some(Uuid::new_v4());
+        reopened.project_id = Some(Uuid::new_v4());
+        for task in [in_progress, completed, reopened, new_task("Todo".to_string())] {
+            tasks.insert(task.id, task);
+        }
+        json.save_tasks(&tasks).unwrap();
+        
+        assert_eq!(transfer_tasks(&json, &sqlite).unwrap(), 4);
+        assert_eq!(transfer_tasks(&sqlite, &json_back).unwrap(), 4);
+        
+        let round_tripped = json_back.load_tasks().unwrap();
+        for (id, task) in &tasks {
+            assert_eq!(task_checksum(&round_tripped[id]).unwrap(), task_checksum(task).unwrap());
+        }
+        // La source reste intacte
+        assert_eq!(json.load_tasks().unwrap().len(), 4);
+    }
+
+    fn check_backup_and_restore(storage: &dyn Storage) {
+        let first = new_task("First".to_string());
+        let mut tasks = HashMap::new();
+        tasks.insert(first.id, first.clone());
+        storage.save_tasks(&tasks).unwrap();
+        
+        let backup = storage.backup().unwrap();
+        assert!(backup.path.exists());
+        assert_eq!(storage.backup_dir().list().unwrap().len(), 1);
+        
+        storage.delete_task(&first.id).unwrap();
+        storage.upsert_task(&new_task("Second".to_string())).unwrap();
+        
+        let saved = storage.load_backup(&backup).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert!(saved.contains_key(&first.id));
+        
+        assert_eq!(storage.restore_backup(&backup).unwrap(), 1);
+        let restored = storage.load_tasks().unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            task_checksum(&restored[&first.id]).unwrap(),
+            task_checksum(&first).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_backup_and_restore() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("tasks.json");
+        check_backup_and_restore(&JsonStorage::new(file_path.to_string_lossy().to_string()));
+    }
+
+    #[test]
+    fn test_sqlite_backup_and_restore() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("tasks.db");
+        check_backup_and_restore(&SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_sqlite_old_backup_is_left_untouched() {
+        let temp_dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(temp_dir.path().join("tasks.db").to_string_lossy().to_string()).unwrap();
+
+        // Sauvegarde faite avant le versionnage du schéma
+        let path = temp_dir.path().join("old-backup.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                r#"
+                CREATE TABLE tasks (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    status TEXT NOT NULL,
+                    priority TEXT NOT NULL,
+                    tags TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    due_date TEXT,
+                    completed_at TEXT
+                );
+                INSERT INTO tasks VALUES (
+                    'a3c1f0d2-5b6e-4f7a-8b9c-0d1e2f3a4b5c', 'Old', NULL, 'Todo', 'High', '[]',
+                    '2024-01-15T10:00:00+00:00', '2024-01-15T10:00:00+00:00', NULL, NULL
+                );
+                "#,
+            )
+            .unwrap();
+        let before = fs::read(&path).unwrap();
+        let backup = Backup {
+            id: "old".to_string(),
+            path: path.clone(),
+            created_at: Utc::now(),
+            size: before.len() as u64,
+        };
+
+        let tasks = storage.load_backup(&backup).unwrap();
+        assert_eq!(tasks.values().next().unwrap().title, "Old");
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_create_storage_uses_data_dir() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config {
+            storage: StorageType::Sqlite,
+            data_dir: Some(temp_dir.path().join("project")),
+            ..Config::default()
+        };
+        
+        let storage = create_storage(&config).unwrap();
+        storage.upsert_task(&new_task("Test task".to_string())).unwrap();
+        assert!(temp_dir.path().join("project").join("tasks.db").exists());
+    }
 }
\ No newline at end of file
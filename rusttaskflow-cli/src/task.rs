@@ -1,183 +1,19 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::fmt;
-use uuid::Uuid;
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum Priority {
-    Low,
-    Medium,
-    High,
-    Critical,
-}
-
-impl fmt::Display for Priority {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Priority::Low => write!(f, "Low"),
-            Priority::Medium => write!(f, "Medium"),
-            Priority::High => write!(f, "High"),
-            Priority::Critical => write!(f, "Critical"),
-        }
-    }
-}
-
-impl Default for Priority {
-    fn default() -> Self {
-        Priority::Medium
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum Status {
-    Todo,
-    InProgress,
-    Completed,
-    Cancelled,
-}
-
-impl fmt::Display for Status {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Status::Todo => write!(f, "Todo"),
-            Status::InProgress => write!(f, "In Progress"),
-            Status::Completed => write!(f, "Completed"),
-            Status::Cancelled => write!(f, "Cancelled"),
-        }
-    }
-}
-
-impl Default for Status {
-    fn default() -> Self {
-        Status::Todo
-    }
-}
+//! Modèle de tâche du CLI
+//!
+//! Le CLI partage le modèle de `rusttaskflow-core` avec le serveur web afin
+//! qu'une tâche ait le même sens localement et côté serveur.
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Task {
-    pub id: Uuid,
-    pub title: String,
-    pub description: Option<String>,
-    pub status: Status,
-    pub priority: Priority,
-    pub tags: Vec<String>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-    pub due_date: Option<DateTime<Utc>>,
-    pub completed_at: Option<DateTime<Utc>>,
-}
-
-impl Task {
-    /// Crée une nouvelle tâche
-    pub fn new(title: String) -> Self {
-        let now = Utc::now();
-        Self {
-            id: Uuid::new_v4(),
-            title,
-            description: None,
-            status: Status::default(),
-            priority: Priority::default(),
-            tags: Vec::new(),
-            created_at: now,
-            updated_at: now,
-            due_date: None,
-            completed_at: None,
-        }
-    }
-
-    /// Marque la tâche comme terminée
-    pub fn complete(&mut self) {
-        self.status = Status::Completed;
-        self.completed_at = Some(Utc::now());
-        self.updated_at = Utc::now();
-    }
-
-    /// Marque la tâche comme en cours
-    pub fn start(&mut self) {
-        self.status = Status::InProgress;
-        self.updated_at = Utc::now();
-    }
-
-    /// Annule la tâche
-    pub fn cancel(&mut self) {
-        self.status = Status::Cancelled;
-        self.updated_at = Utc::now();
-    }
-
-    /// Met à jour le titre
-    pub fn set_title(&mut self, title: String) {
-        self.title = title;
-        self.updated_at = Utc::now();
-    }
-
-    /// Met à jour la description
-    pub fn set_description(&mut self, description: Option<String>) {
-        self.description = description;
-        self.updated_at = Utc::now();
-    }
-
-    /// Met à jour la priorité
-    pub fn set_priority(&mut self, priority: Priority) {
-        self.priority = priority;
-        self.updated_at = Utc::now();
-    }
-
-    /// Ajoute un tag
-    pub fn add_tag(&mut self, tag: String) {
-        if !self.tags.contains(&tag) {
-            self.tags.push(tag);
-            self.updated_at = Utc::now();
-        }
-    }
-
-    /// Supprime un tag
-    pub fn remove_tag(&mut self, tag: &str) {
-        self.tags.retain(|t| t != tag);
-        self.updated_at = Utc::now();
-    }
-
-    /// Met à jour la date d'échéance
-    pub fn set_due_date(&mut self, due_date: Option<DateTime<Utc>>) {
-        self.due_date = due_date;
-        self.updated_at = Utc::now();
-    }
-
-    /// Vérifie si la tâche est en retard
-    pub fn is_overdue(&self) -> bool {
-        if let Some(due_date) = self.due_date {
-            return due_date < Utc::now() && self.status != Status::Completed;
-        }
-        false
-    }
+use uuid::Uuid;
 
-    /// Vérifie si la tâche correspond aux tags donnés
-    pub fn has_tags(&self, tags: &[String]) -> bool {
-        tags.iter().all(|tag| self.tags.contains(tag))
-    }
+pub use rusttaskflow_core::models::{Priority, Task, TaskStatus, WorkSession};
+pub use rusttaskflow_core::recurrence::Recurrence;
 
-    /// Vérifie si la tâche correspond au filtre de texte
-    pub fn matches_text(&self, text: &str) -> bool {
-        let text_lower = text.to_lowercase();
-        self.title.to_lowercase().contains(&text_lower)
-            || self
-                .description
-                .as_ref()
-                .map_or(false, |desc| desc.to_lowercase().contains(&text_lower))
-            || self.tags.iter().any(|tag| tag.to_lowercase().contains(&text_lower))
-    }
-}
+/// Créateur des tâches locales : le CLI n'a pas de notion d'utilisateur
+pub const LOCAL_USER: Uuid = Uuid::nil();
 
-impl fmt::Display for Task {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "[{}] {} - {} ({})",
-            self.id.to_string()[..8].to_uppercase(),
-            self.title,
-            self.status,
-            self.priority
-        )
-    }
+/// Crée une nouvelle tâche locale avec les valeurs par défaut du CLI
+pub fn new_task(title: String) -> Task {
+    Task::new(title, None, Priority::default(), Vec::new(), LOCAL_USER)
 }
 
 #[cfg(test)]
@@ -186,28 +22,29 @@ mod tests {
 
     #[test]
     fn test_new_task() {
-        let task = Task::new("Test task".to_string());
+        let task = new_task("Test task".to_string());
         assert_eq!(task.title, "Test task");
-        assert_eq!(task.status, Status::Todo);
+        assert_eq!(task.status, TaskStatus::Todo);
         assert_eq!(task.priority, Priority::Medium);
+        assert_eq!(task.created_by, LOCAL_USER);
         assert!(task.tags.is_empty());
     }
 
     #[test]
     fn test_complete_task() {
-        let mut task = Task::new("Test task".to_string());
-        task.complete();
-        assert_eq!(task.status, Status::Completed);
+        let mut task = new_task("Test task".to_string());
+        task.complete().unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
         assert!(task.completed_at.is_some());
     }
 
     #[test]
     fn test_add_remove_tags() {
-        let mut task = Task::new("Test task".to_string());
+        let mut task = new_task("Test task".to_string());
         task.add_tag("work".to_string());
         task.add_tag("urgent".to_string());
         assert_eq!(task.tags.len(), 2);
-        
+
         task.remove_tag("work");
         assert_eq!(task.tags.len(), 1);
         assert_eq!(task.tags[0], "urgent");
@@ -215,13 +52,42 @@ mod tests {
 
     #[test]
     fn test_text_matching() {
-        let mut task = Task::new("Important work task".to_string());
+        let mut task = new_task("Important work task".to_string());
         task.set_description(Some("This is a critical task".to_string()));
         task.add_tag("urgent".to_string());
-        
+
         assert!(task.matches_text("work"));
         assert!(task.matches_text("critical"));
         assert!(task.matches_text("urgent"));
         assert!(!task.matches_text("personal"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_legacy_json_task() {
+        // Format écrit par les versions précédentes du CLI
+        let json = r#"{
+            "id": "6f1c1f0e-8a4b-4a53-9a0e-2f7d2b9c6e11",
+            "title": "Ancienne tâche",
+            "description": null,
+            "status": "InProgress",
+            "priority": "High",
+            "tags": ["work"],
+            "created_at": "2024-01-15T10:00:00Z",
+            "updated_at": "2024-01-16T10:00:00Z",
+            "due_date": "2024-02-01T23:59:59Z",
+            "completed_at": null
+        }"#;
+
+        let task: Task = serde_json::from_str(json).unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(task.created_by, LOCAL_USER);
+        assert!(task.started_at.is_none());
+        assert!(task.assigned_to.is_none());
+
+        let reloaded: Task = serde_json::from_str(&serde_json::to_string(&task).unwrap()).unwrap();
+        assert_eq!(reloaded.id, task.id);
+        assert_eq!(reloaded.due_date, task.due_date);
+        assert_eq!(reloaded.updated_at, task.updated_at);
+    }
+}
@@ -1,28 +1,24 @@
-use crate::task::{Priority, Status, Task};
+use crate::import::ImportPlan;
+use crate::journal::{Journal, Operation, TaskChange};
+use crate::storage::{task_checksum, Storage};
+use crate::task::{new_task, Priority, Recurrence, Task, TaskStatus};
+use crate::timezone::UserTimeZone;
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TaskFilter {
-    pub status: Option<Status>,
+    pub status: Option<TaskStatus>,
     pub priority: Option<Priority>,
     pub tags: Vec<String>,
     pub text: Option<String>,
     pub overdue_only: bool,
-}
-
-impl Default for TaskFilter {
-    fn default() -> Self {
-        Self {
-            status: None,
-            priority: None,
-            tags: Vec::new(),
-            text: None,
-            overdue_only: false,
-        }
-    }
+    /// Échéance au plus tard à cette date (incluse)
+    pub due_before: Option<DateTime<Utc>>,
+    /// Échéance strictement après cette date
+    pub due_after: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,11 +29,57 @@ pub struct TaskStats {
     pub completed: usize,
     pub cancelled: usize,
     pub overdue: usize,
+    /// Tâches ouvertes à rendre aujourd'hui, dans le fuseau de l'utilisateur
+    pub due_today: usize,
+    /// Tâches ouvertes à rendre cette semaine (du lundi au dimanche)
+    pub due_this_week: usize,
+    /// Tâches rattachées à une tâche parente
+    pub subtasks: usize,
+    /// Avancement global entre 0 et 1, calculé depuis les sous-tâches
+    /// (voir `TaskManager::task_progress`)
+    pub progress: f64,
+    /// Temps passé sur l'ensemble des tâches, minuteur en cours compris
+    pub time_spent: Duration,
+    /// Temps passé par tag
+    pub time_by_tag: BTreeMap<String, TimeSpent>,
+    /// Temps passé par priorité, de la plus haute à la plus basse
+    pub time_by_priority: Vec<(Priority, TimeSpent)>,
+}
+
+/// Temps passé sur un groupe de tâches (un tag, une priorité)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeSpent {
+    pub total: Duration,
+    /// Tâches du groupe sur lesquelles du temps a été enregistré
+    pub tasks: usize,
+}
+
+impl TimeSpent {
+    fn add(&mut self, duration: Duration) {
+        self.total += duration;
+        self.tasks += 1;
+    }
+
+    /// Temps moyen par tâche du groupe
+    pub fn average(&self) -> Duration {
+        match i32::try_from(self.tasks) {
+            Ok(tasks) if tasks > 0 => self.total / tasks,
+            _ => Duration::zero(),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct TaskManager {
     tasks: HashMap<Uuid, Task>,
+    /// Tâches créées ou modifiées depuis le dernier enregistrement
+    dirty: HashSet<Uuid>,
+    /// Tâches supprimées depuis le dernier enregistrement
+    deleted: HashSet<Uuid>,
+    /// État avant modification des tâches touchées depuis le dernier enregistrement
+    originals: HashMap<Uuid, Option<Task>>,
+    /// Historique des opérations pour undo/redo
+    journal: Journal,
 }
 
 impl TaskManager {
@@ -45,120 +87,470 @@ impl TaskManager {
     pub fn new() -> Self {
         Self {
             tasks: HashMap::new(),
+            dirty: HashSet::new(),
+            deleted: HashSet::new(),
+            originals: HashMap::new(),
+            journal: Journal::default(),
         }
     }
 
+    /// Utilise `journal` pour enregistrer les opérations
+    pub fn set_journal(&mut self, journal: Journal) {
+        self.journal = journal;
+    }
+
+    /// Journal des opérations
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
     /// Ajoute une nouvelle tâche
     pub fn add_task(&mut self, title: String) -> Uuid {
-        let task = Task::new(title);
+        let task = new_task(title);
         let id = task.id;
+        self.remember(id);
         self.tasks.insert(id, task);
+        self.mark_dirty(id);
         id
     }
 
+    /// Récupère une tâche mutable et la marque comme modifiée
+    fn task_mut(&mut self, id: &Uuid) -> Result<&mut Task> {
+        self.remember(*id);
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        self.dirty.insert(*id);
+        self.deleted.remove(id);
+        Ok(task)
+    }
+
+    /// Conserve l'état d'une tâche avant sa première modification
+    fn remember(&mut self, id: Uuid) {
+        if !self.originals.contains_key(&id) {
+            self.originals.insert(id, self.tasks.get(&id).cloned());
+        }
+    }
+
+    fn mark_dirty(&mut self, id: Uuid) {
+        self.deleted.remove(&id);
+        self.dirty.insert(id);
+    }
+
+    fn mark_deleted(&mut self, id: Uuid) {
+        self.dirty.remove(&id);
+        self.deleted.insert(id);
+    }
+
     /// Récupère une tâche par son ID
     pub fn get_task(&self, id: &Uuid) -> Option<&Task> {
         self.tasks.get(id)
     }
 
-    /// Récupère une tâche mutable par son ID
+    /// Récupère une tâche mutable par son ID (elle sera réenregistrée)
     pub fn get_task_mut(&mut self, id: &Uuid) -> Option<&mut Task> {
-        self.tasks.get_mut(id)
+        self.task_mut(id).ok()
     }
 
-    /// Supprime une tâche
+    /// Supprime une tâche ; ses sous-tâches remontent d'un niveau et les
+    /// tâches qui en dépendaient n'en dépendent plus
     pub fn delete_task(&mut self, id: &Uuid) -> Result<Task> {
-        self.tasks
+        if self.tasks.contains_key(id) {
+            self.remember(*id);
+        }
+        let task = self
+            .tasks
             .remove(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))
+            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        self.mark_deleted(*id);
+
+        let children: Vec<Uuid> = self.get_subtasks(id).iter().map(|t| t.id).collect();
+        for child_id in children {
+            let child = self.task_mut(&child_id)?;
+            child.parent_id = task.parent_id;
+            child.updated_at = Utc::now();
+        }
+        let dependents: Vec<Uuid> = self.get_dependents(id).iter().map(|t| t.id).collect();
+        for dependent_id in dependents {
+            self.remove_dependency(&dependent_id, id)?;
+        }
+        Ok(task)
+    }
+
+    /// Rattache une tâche à une tâche parente, ou la détache avec `None`
+    pub fn set_task_parent(&mut self, id: &Uuid, parent_id: Option<Uuid>) -> Result<()> {
+        if let Some(parent_id) = parent_id {
+            if !self.tasks.contains_key(&parent_id) {
+                return Err(anyhow!("Tâche parente avec l'ID {} non trouvée", parent_id));
+            }
+            if parent_id == *id || self.get_ancestors(&parent_id).contains(id) {
+                return Err(anyhow!(
+                    "Une tâche ne peut pas devenir sa propre sous-tâche ni celle de l'une de ses sous-tâches"
+                ));
+            }
+        }
+
+        let task = self.task_mut(id)?;
+        task.parent_id = parent_id;
+        task.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Ancêtres d'une tâche, du parent direct à la racine
+    pub fn get_ancestors(&self, id: &Uuid) -> Vec<Uuid> {
+        let mut ancestors = Vec::new();
+        let mut current = self.tasks.get(id).and_then(|t| t.parent_id);
+        // Des données importées peuvent contenir un cycle : on s'arrête au premier retour
+        while let Some(parent_id) = current.filter(|p| p != id && !ancestors.contains(p)) {
+            ancestors.push(parent_id);
+            current = self.tasks.get(&parent_id).and_then(|t| t.parent_id);
+        }
+        ancestors
+    }
+
+    /// Sous-tâches directes d'une tâche, par date de création
+    pub fn get_subtasks(&self, id: &Uuid) -> Vec<&Task> {
+        let mut subtasks: Vec<&Task> = self
+            .tasks
+            .values()
+            .filter(|t| t.parent_id == Some(*id) && t.id != *id)
+            .collect();
+        subtasks.sort_by_key(|t| t.created_at);
+        subtasks
+    }
+
+    /// Sous-tâches de tous niveaux avec leur profondeur (1 pour les sous-tâches
+    /// directes), dans l'ordre d'affichage de l'arbre
+    pub fn get_subtask_tree(&self, id: &Uuid) -> Vec<(usize, &Task)> {
+        fn visit<'a>(
+            manager: &'a TaskManager,
+            id: &Uuid,
+            depth: usize,
+            visited: &mut HashSet<Uuid>,
+            tree: &mut Vec<(usize, &'a Task)>,
+        ) {
+            for child in manager.get_subtasks(id) {
+                if visited.insert(child.id) {
+                    tree.push((depth, child));
+                    visit(manager, &child.id, depth + 1, visited, tree);
+                }
+            }
+        }
+
+        let mut tree = Vec::new();
+        visit(self, id, 1, &mut HashSet::from([*id]), &mut tree);
+        tree
+    }
+
+    /// Sous-tâches de tous niveaux qui ne sont ni terminées ni annulées
+    pub fn get_open_subtasks(&self, id: &Uuid) -> Vec<&Task> {
+        self.get_subtask_tree(id)
+            .into_iter()
+            .map(|(_, task)| task)
+            .filter(|t| matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress))
+            .collect()
+    }
+
+    /// Avancement d'une tâche entre 0 et 1 : 1 si elle est terminée, sinon la
+    /// moyenne de l'avancement de ses sous-tâches non annulées (0 sans sous-tâche)
+    pub fn task_progress(&self, id: &Uuid) -> f64 {
+        self.tasks
+            .get(id)
+            .map_or(0.0, |task| self.progress_of(task, &mut HashSet::new()))
+    }
+
+    fn progress_of(&self, task: &Task, visited: &mut HashSet<Uuid>) -> f64 {
+        if task.status == TaskStatus::Completed {
+            return 1.0;
+        }
+        if !visited.insert(task.id) {
+            return 0.0;
+        }
+
+        let children: Vec<f64> = self
+            .get_subtasks(&task.id)
+            .into_iter()
+            .filter(|c| c.status != TaskStatus::Cancelled)
+            .map(|c| self.progress_of(c, visited))
+            .collect();
+        if children.is_empty() {
+            0.0
+        } else {
+            children.iter().sum::<f64>() / children.len() as f64
+        }
+    }
+
+    /// Ajoute une dépendance : `id` ne peut être démarrée qu'une fois
+    /// `depends_on` terminée. Refusée si elle créerait un cycle.
+    pub fn add_dependency(&mut self, id: &Uuid, depends_on: &Uuid) -> Result<()> {
+        if !self.tasks.contains_key(depends_on) {
+            return Err(anyhow!("Tâche avec l'ID {} non trouvée", depends_on));
+        }
+        if id == depends_on {
+            return Err(anyhow!("Une tâche ne peut pas dépendre d'elle-même"));
+        }
+        // Le cycle passe par le chemin existant de `depends_on` vers `id`
+        if let Some(path) = self.dependency_path(depends_on, id) {
+            let cycle: Vec<String> = std::iter::once(id)
+                .chain(path.iter())
+                .map(|t| t.to_string()[..8].to_uppercase())
+                .collect();
+            return Err(anyhow!(
+                "Dépendance refusée, elle créerait un cycle: {}",
+                cycle.join(" → ")
+            ));
+        }
+
+        let task = self.task_mut(id)?;
+        if task.depends_on.contains(depends_on) {
+            return Err(anyhow!("Cette dépendance existe déjà"));
+        }
+        task.depends_on.push(*depends_on);
+        task.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Retire une dépendance
+    pub fn remove_dependency(&mut self, id: &Uuid, depends_on: &Uuid) -> Result<()> {
+        let task = self.task_mut(id)?;
+        let count = task.depends_on.len();
+        task.depends_on.retain(|d| d != depends_on);
+        if task.depends_on.len() == count {
+            return Err(anyhow!("La tâche ne dépend pas de {}", depends_on));
+        }
+        task.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Chemin de dépendances de `from` jusqu'à `to` (les deux inclus), s'il existe
+    pub fn dependency_path(&self, from: &Uuid, to: &Uuid) -> Option<Vec<Uuid>> {
+        let mut previous: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut queue = std::collections::VecDeque::from([*from]);
+        let mut visited = HashSet::from([*from]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == *to {
+                let mut path = vec![current];
+                while let Some(p) = previous.get(path.last().unwrap()) {
+                    path.push(*p);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let Some(task) = self.tasks.get(&current) else {
+                continue;
+            };
+            for next in &task.depends_on {
+                if visited.insert(*next) {
+                    previous.insert(*next, current);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Tâches dont une tâche dépend directement
+    pub fn get_dependencies(&self, id: &Uuid) -> Vec<&Task> {
+        self.tasks
+            .get(id)
+            .map(|task| task.depends_on.iter().filter_map(|d| self.tasks.get(d)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Tâches qui dépendent directement d'une tâche
+    pub fn get_dependents(&self, id: &Uuid) -> Vec<&Task> {
+        let mut dependents: Vec<&Task> = self
+            .tasks
+            .values()
+            .filter(|t| t.depends_on.contains(id))
+            .collect();
+        dependents.sort_by_key(|t| t.created_at);
+        dependents
+    }
+
+    /// Dépendances pas encore terminées, ni annulées, qui bloquent une tâche
+    pub fn get_blocking_tasks(&self, id: &Uuid) -> Vec<&Task> {
+        self.get_dependencies(id)
+            .into_iter()
+            .filter(|t| matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress))
+            .collect()
+    }
+
+    /// Tâches encore ouvertes bloquées par au moins une dépendance
+    pub fn get_blocked_ids(&self) -> HashSet<Uuid> {
+        self.tasks
+            .values()
+            .filter(|t| matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress))
+            .filter(|t| !self.get_blocking_tasks(&t.id).is_empty())
+            .map(|t| t.id)
+            .collect()
     }
 
     /// Met à jour le titre d'une tâche
     pub fn update_task_title(&mut self, id: &Uuid, title: String) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.set_title(title);
         Ok(())
     }
 
     /// Met à jour la description d'une tâche
     pub fn update_task_description(&mut self, id: &Uuid, description: Option<String>) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.set_description(description);
         Ok(())
     }
 
     /// Met à jour la priorité d'une tâche
     pub fn update_task_priority(&mut self, id: &Uuid, priority: Priority) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.set_priority(priority);
         Ok(())
     }
 
-    /// Marque une tâche comme terminée
-    pub fn complete_task(&mut self, id: &Uuid) -> Result<()> {
+    /// Marque une tâche comme terminée ; pour une tâche récurrente, crée
+    /// l'occurrence suivante de la série et renvoie son ID
+    pub fn complete_task(&mut self, id: &Uuid) -> Result<Option<Uuid>> {
+        let task = self.task_mut(id)?;
+        task.complete()?;
+
+        // Une tâche rouverte puis terminée à nouveau a déjà son occurrence suivante
+        let series_id = task.series_id.unwrap_or(task.id);
+        let next_exists = self.tasks.values().any(|t| {
+            t.id != *id
+                && t.series_id == Some(series_id)
+                && matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress)
+        });
+        if next_exists {
+            return Ok(None);
+        }
+
+        let Some(next) = self.task_mut(id)?.next_occurrence(Utc::now(), &UserTimeZone::current()) else {
+            return Ok(None);
+        };
+        let next_id = next.id;
+        self.remember(next_id);
+        self.tasks.insert(next_id, next);
+        self.mark_dirty(next_id);
+        Ok(Some(next_id))
+    }
+
+    /// Définit ou retire la récurrence d'une tâche
+    pub fn set_task_recurrence(&mut self, id: &Uuid, recurrence: Option<Recurrence>) -> Result<()> {
+        let task = self.task_mut(id)?;
+        task.recurrence = recurrence;
+        task.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Arrête la série d'une tâche récurrente : aucune de ses occurrences
+    /// n'en créera plus de nouvelle. Renvoie le nombre de tâches modifiées.
+    pub fn stop_recurrence(&mut self, id: &Uuid) -> Result<usize> {
         let task = self
             .tasks
-            .get_mut(id)
+            .get(id)
             .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
-        task.complete();
+        if task.recurrence.is_none() {
+            return Err(anyhow!("La tâche '{}' n'est pas récurrente", task.title));
+        }
+
+        let series_id = task.series_id.unwrap_or(task.id);
+        let series: Vec<Uuid> = self
+            .tasks
+            .values()
+            .filter(|t| t.id == series_id || t.id == *id || t.series_id == Some(series_id))
+            .filter(|t| t.recurrence.is_some())
+            .map(|t| t.id)
+            .collect();
+        for task_id in &series {
+            self.set_task_recurrence(task_id, None)?;
+        }
+        Ok(series.len())
+    }
+
+    /// Tâche dont le minuteur tourne ; il n'y en a qu'une à la fois
+    pub fn get_active_timer(&self) -> Option<&Task> {
+        self.tasks.values().find(|t| t.active_session().is_some())
+    }
+
+    /// Démarre le minuteur d'une tâche, qui passe en cours si elle était à faire
+    pub fn start_timer(&mut self, id: &Uuid) -> Result<()> {
+        if let Some(active) = self.get_active_timer() {
+            return Err(anyhow!(
+                "Un minuteur tourne déjà sur '{}', arrêtez-le d'abord avec `rtf timer stop`",
+                active.title
+            ));
+        }
+        self.task_mut(id)?.start_timer(Utc::now())?;
+        Ok(())
+    }
+
+    /// Arrête le minuteur en cours ; renvoie la tâche et la durée de la session
+    pub fn stop_timer(&mut self) -> Result<(Uuid, Duration)> {
+        let id = self
+            .get_active_timer()
+            .map(|t| t.id)
+            .ok_or_else(|| anyhow!("Aucun minuteur en cours"))?;
+        let duration = self
+            .task_mut(&id)?
+            .stop_timer(Utc::now())
+            .ok_or_else(|| anyhow!("Aucun minuteur en cours"))?;
+        Ok((id, duration))
+    }
+
+    /// Enregistre du temps passé sur une tâche, dans une session qui se
+    /// termine maintenant
+    pub fn log_time(&mut self, id: &Uuid, duration: Duration) -> Result<()> {
+        self.task_mut(id)?.log_time(duration, Utc::now())?;
         Ok(())
     }
 
     /// Marque une tâche comme en cours
     pub fn start_task(&mut self, id: &Uuid) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
-        task.start();
+        let task = self.task_mut(id)?;
+        task.start()?;
         Ok(())
     }
 
     /// Annule une tâche
     pub fn cancel_task(&mut self, id: &Uuid) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
-        task.cancel();
+        let task = self.task_mut(id)?;
+        task.cancel()?;
+        Ok(())
+    }
+
+    /// Rouvre une tâche terminée ou annulée
+    pub fn reopen_task(&mut self, id: &Uuid) -> Result<()> {
+        let task = self.task_mut(id)?;
+        task.reopen()?;
+        Ok(())
+    }
+
+    /// Remet une tâche en cours à l'état « À faire »
+    pub fn reset_task(&mut self, id: &Uuid) -> Result<()> {
+        let task = self.task_mut(id)?;
+        task.reset()?;
         Ok(())
     }
 
     /// Ajoute un tag à une tâche
     pub fn add_tag_to_task(&mut self, id: &Uuid, tag: String) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.add_tag(tag);
         Ok(())
     }
 
     /// Supprime un tag d'une tâche
     pub fn remove_tag_from_task(&mut self, id: &Uuid, tag: &str) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.remove_tag(tag);
         Ok(())
     }
 
     /// Met à jour la date d'échéance d'une tâche
     pub fn set_task_due_date(&mut self, id: &Uuid, due_date: Option<DateTime<Utc>>) -> Result<()> {
-        let task = self
-            .tasks
-            .get_mut(id)
-            .ok_or_else(|| anyhow!("Tâche avec l'ID {} non trouvée", id))?;
+        let task = self.task_mut(id)?;
         task.set_due_date(due_date);
         Ok(())
     }
@@ -204,6 +596,18 @@ impl TaskManager {
                     return false;
                 }
 
+                // Filtre par échéance : les tâches sans échéance sont exclues
+                if filter.due_before.is_some() || filter.due_after.is_some() {
+                    let Some(due_date) = task.due_date else {
+                        return false;
+                    };
+                    if filter.due_before.is_some_and(|before| due_date > before)
+                        || filter.due_after.is_some_and(|after| due_date <= after)
+                    {
+                        return false;
+                    }
+                }
+
                 true
             })
             .collect()
@@ -228,7 +632,7 @@ impl TaskManager {
     }
 
     /// Récupère les tâches par statut
-    pub fn get_tasks_by_status(&self, status: Status) -> Vec<&Task> {
+    pub fn get_tasks_by_status(&self, status: TaskStatus) -> Vec<&Task> {
         let filter = TaskFilter {
             status: Some(status),
             ..Default::default()
@@ -263,21 +667,70 @@ impl TaskManager {
             completed: 0,
             cancelled: 0,
             overdue: 0,
+            due_today: 0,
+            due_this_week: 0,
+            subtasks: 0,
+            progress: 0.0,
+            time_spent: Duration::zero(),
+            time_by_tag: BTreeMap::new(),
+            time_by_priority: Vec::new(),
         };
+        let timezone = UserTimeZone::current();
+        let now = Utc::now();
+        let mut time_by_priority: HashMap<&str, TimeSpent> = HashMap::new();
+        // Les tâches de premier niveau portent l'avancement de leurs sous-tâches
+        let mut roots = Vec::new();
 
         for task in self.tasks.values() {
             match task.status {
-                Status::Todo => stats.todo += 1,
-                Status::InProgress => stats.in_progress += 1,
-                Status::Completed => stats.completed += 1,
-                Status::Cancelled => stats.cancelled += 1,
+                TaskStatus::Todo => stats.todo += 1,
+                TaskStatus::InProgress => stats.in_progress += 1,
+                TaskStatus::Completed => stats.completed += 1,
+                TaskStatus::Cancelled => stats.cancelled += 1,
             }
 
             if task.is_overdue() {
                 stats.overdue += 1;
             }
+
+            if let Some(due_date) = task.due_date.filter(|_| matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress)) {
+                if timezone.is_today(&due_date) {
+                    stats.due_today += 1;
+                }
+                if timezone.is_this_week(&due_date) {
+                    stats.due_this_week += 1;
+                }
+            }
+
+            let time_spent = task.time_spent(now);
+            if time_spent > Duration::zero() {
+                stats.time_spent += time_spent;
+                for tag in &task.tags {
+                    stats.time_by_tag.entry(tag.clone()).or_default().add(time_spent);
+                }
+                time_by_priority
+                    .entry(task.priority.as_str())
+                    .or_default()
+                    .add(time_spent);
+            }
+
+            match task.parent_id.filter(|p| self.tasks.contains_key(p)) {
+                Some(_) => stats.subtasks += 1,
+                None if task.status != TaskStatus::Cancelled => roots.push(task.id),
+                None => {}
+            }
+        }
+
+        if !roots.is_empty() {
+            let total: f64 = roots.iter().map(|id| self.task_progress(id)).sum();
+            stats.progress = total / roots.len() as f64;
         }
 
+        stats.time_by_priority = [Priority::Critical, Priority::High, Priority::Medium, Priority::Low]
+            .into_iter()
+            .filter_map(|p| time_by_priority.remove(p.as_str()).map(|time| (p, time)))
+            .collect();
+
         stats
     }
 
@@ -301,12 +754,147 @@ impl TaskManager {
 
     /// Vide toutes les tâches
     pub fn clear_all_tasks(&mut self) {
+        let ids: Vec<Uuid> = self.tasks.keys().copied().collect();
+        for id in ids {
+            self.remember(id);
+            self.mark_deleted(id);
+        }
         self.tasks.clear();
     }
 
-    /// Charge les tâches depuis un HashMap
+    /// Charge les tâches depuis un HashMap (état déjà enregistré)
     pub fn load_tasks(&mut self, tasks: HashMap<Uuid, Task>) {
         self.tasks = tasks;
+        self.dirty.clear();
+        self.deleted.clear();
+        self.originals.clear();
+    }
+
+    /// Applique un plan d'import préparé par `import::plan_import`
+    pub fn apply_import(&mut self, plan: ImportPlan) {
+        for id in plan.removed {
+            if self.tasks.contains_key(&id) {
+                self.remember(id);
+                self.tasks.remove(&id);
+                self.mark_deleted(id);
+            }
+        }
+        for task in plan.added.into_iter().chain(plan.updated) {
+            let id = task.id;
+            self.remember(id);
+            self.tasks.insert(id, task);
+            self.mark_dirty(id);
+        }
+    }
+
+    /// Indique s'il reste des modifications non enregistrées
+    pub fn has_changes(&self) -> bool {
+        !self.dirty.is_empty() || !self.deleted.is_empty()
+    }
+
+    /// Enregistre uniquement les tâches modifiées ou supprimées
+    pub fn save_changes(&mut self, storage: &dyn Storage) -> Result<()> {
+        if !self.has_changes() {
+            return Ok(());
+        }
+
+        let upserted: Vec<&Task> = self
+            .dirty
+            .iter()
+            .filter_map(|id| self.tasks.get(id))
+            .collect();
+        let deleted: Vec<Uuid> = self.deleted.iter().copied().collect();
+        storage.save_changes(&upserted, &deleted)?;
+
+        self.dirty.clear();
+        self.deleted.clear();
+        self.originals.clear();
+        Ok(())
+    }
+
+    /// Opération décrivant les modifications non enregistrées (sans les
+    /// tâches revenues à leur état d'origine)
+    fn pending_operation(&self, label: String) -> Result<Option<Operation>> {
+        let mut changes = Vec::new();
+        for (id, before) in &self.originals {
+            let after = self.tasks.get(id);
+            let unchanged = match (before, after) {
+                (Some(before), Some(after)) => task_checksum(before)? == task_checksum(after)?,
+                (None, None) => true,
+                _ => false,
+            };
+            if !unchanged {
+                changes.push(TaskChange {
+                    id: *id,
+                    before: before.clone(),
+                    after: after.cloned(),
+                });
+            }
+        }
+
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Operation {
+            label,
+            recorded_at: Utc::now(),
+            changes,
+        }))
+    }
+
+    /// Enregistre les modifications et les inscrit au journal sous `label`
+    pub fn commit(&mut self, storage: &dyn Storage, label: impl Into<String>) -> Result<()> {
+        let operation = self.pending_operation(label.into())?;
+        self.save_changes(storage)?;
+
+        if let Some(operation) = operation {
+            self.journal.record(operation);
+            self.journal.save()?;
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, operation: &Operation) -> Result<()> {
+        operation.check_applicable(|id| self.tasks.get(id))?;
+        for change in &operation.changes {
+            match &change.after {
+                Some(task) => {
+                    self.tasks.insert(change.id, task.clone());
+                    self.mark_dirty(change.id);
+                }
+                None => {
+                    self.tasks.remove(&change.id);
+                    self.mark_deleted(change.id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Annule la dernière opération du journal
+    pub fn undo(&mut self, storage: &dyn Storage) -> Result<Option<Operation>> {
+        let Some(operation) = self.journal.last_done().cloned() else {
+            return Ok(None);
+        };
+
+        self.apply(&operation.inverse())?;
+        self.save_changes(storage)?;
+        self.journal.mark_undone();
+        self.journal.save()?;
+        Ok(Some(operation))
+    }
+
+    /// Rétablit la dernière opération annulée
+    pub fn redo(&mut self, storage: &dyn Storage) -> Result<Option<Operation>> {
+        let Some(operation) = self.journal.last_undone().cloned() else {
+            return Ok(None);
+        };
+
+        self.apply(&operation)?;
+        self.save_changes(storage)?;
+        self.journal.mark_redone();
+        self.journal.save()?;
+        Ok(Some(operation))
     }
 
     /// Exporte toutes les tâches
@@ -324,6 +912,8 @@ impl Default for TaskManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::SqliteStorage;
+    use rusttaskflow_core::TaskFlowError;
 
     #[test]
     fn test_add_and_get_task() {
@@ -332,7 +922,7 @@ mod tests {
         
         let task = manager.get_task(&id).unwrap();
         assert_eq!(task.title, "Test task");
-        assert_eq!(task.status, Status::Todo);
+        assert_eq!(task.status, TaskStatus::Todo);
     }
 
     #[test]
@@ -342,26 +932,180 @@ mod tests {
         
         manager.complete_task(&id).unwrap();
         let task = manager.get_task(&id).unwrap();
-        assert_eq!(task.status, Status::Completed);
+        assert_eq!(task.status, TaskStatus::Completed);
     }
 
     #[test]
-    fn test_filter_tasks() {
+    fn test_invalid_transitions_are_rejected() {
+        let mut manager = TaskManager::new();
+        let id = manager.add_task("Test task".to_string());
+
+        manager.cancel_task(&id).unwrap();
+        let err = manager.complete_task(&id).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TaskFlowError>(),
+            Some(TaskFlowError::InvalidStatusTransition { .. })
+        ));
+        assert!(manager.start_task(&id).is_err());
+        assert!(manager.reset_task(&id).is_err());
+        assert_eq!(manager.get_task(&id).unwrap().status, TaskStatus::Cancelled);
+
+        let id = manager.add_task("Done task".to_string());
+        manager.complete_task(&id).unwrap();
+        assert!(manager.start_task(&id).is_err());
+        assert!(manager.cancel_task(&id).is_err());
+    }
+
+    #[test]
+    fn test_reopen_and_reset() {
+        let mut manager = TaskManager::new();
+        let id = manager.add_task("Test task".to_string());
+
+        assert!(manager.reopen_task(&id).is_err());
+
+        manager.start_task(&id).unwrap();
+        assert!(manager.get_task(&id).unwrap().started_at.is_some());
+        manager.reset_task(&id).unwrap();
+        let task = manager.get_task(&id).unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        assert!(task.started_at.is_none());
+        assert!(task.reopened_at.is_some());
+
+        manager.cancel_task(&id).unwrap();
+        assert!(manager.get_task(&id).unwrap().cancelled_at.is_some());
+        manager.reopen_task(&id).unwrap();
+        let task = manager.get_task(&id).unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        assert!(task.cancelled_at.is_none());
+        assert!(task.completed_at.is_none());
+    }
+
+    #[test]
+    fn test_save_changes_only_writes_dirty_tasks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
         let mut manager = TaskManager::new();
         let id1 = manager.add_task("Task 1".to_string());
         let id2 = manager.add_task("Task 2".to_string());
+        assert!(manager.has_changes());
+        manager.save_changes(&storage).unwrap();
+        assert!(!manager.has_changes());
+
+        // Modification faite directement dans la base : une tâche non modifiée
+        // côté gestionnaire ne doit pas être réécrite
+        let mut external = storage.load_tasks().unwrap()[&id2].clone();
+        external.set_title("Changed elsewhere".to_string());
+        storage.upsert_task(&external).unwrap();
+
+        manager.update_task_title(&id1, "Task 1 bis".to_string()).unwrap();
+        manager.save_changes(&storage).unwrap();
+
+        let loaded = storage.load_tasks().unwrap();
+        assert_eq!(loaded[&id1].title, "Task 1 bis");
+        assert_eq!(loaded[&id2].title, "Changed elsewhere");
+
+        manager.delete_task(&id1).unwrap();
+        manager.save_changes(&storage).unwrap();
+        assert!(!storage.load_tasks().unwrap().contains_key(&id1));
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+        let mut manager = TaskManager::new();
+        let id = manager.add_task("Task".to_string());
+        manager.commit(&storage, "add").unwrap();
+        manager.update_task_title(&id, "Renamed".to_string()).unwrap();
+        manager.commit(&storage, "edit").unwrap();
+        manager.delete_task(&id).unwrap();
+        manager.commit(&storage, "delete").unwrap();
+        // Une commande sans effet n'est pas journalisée
+        manager.commit(&storage, "noop").unwrap();
+        assert_eq!(manager.journal().history().count(), 3);
+
+        assert_eq!(manager.undo(&storage).unwrap().unwrap().label, "delete");
+        assert_eq!(storage.load_tasks().unwrap()[&id].title, "Renamed");
+        assert_eq!(manager.undo(&storage).unwrap().unwrap().label, "edit");
+        assert_eq!(storage.load_tasks().unwrap()[&id].title, "Task");
+
+        assert_eq!(manager.redo(&storage).unwrap().unwrap().label, "edit");
+        assert_eq!(manager.get_task(&id).unwrap().title, "Renamed");
+        assert_eq!(manager.journal().redo_count(), 1);
+
+        // Une nouvelle opération efface ce qui pouvait être rétabli
+        manager.complete_task(&id).unwrap();
+        manager.commit(&storage, "complete").unwrap();
+        assert!(manager.redo(&storage).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_refuses_outside_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("tasks.db");
+        let storage = SqliteStorage::new(db_path.to_string_lossy().to_string()).unwrap();
+
+        let mut manager = TaskManager::new();
+        let id = manager.add_task("Task".to_string());
+        manager.commit(&storage, "add").unwrap();
+
+        // Modification faite sans passer par le journal (restauration, autre outil…)
+        let mut tasks = storage.load_tasks().unwrap();
+        tasks.get_mut(&id).unwrap().set_title("Changed".to_string());
+        manager.load_tasks(tasks);
+
+        assert!(manager.undo(&storage).is_err());
+        assert_eq!(manager.journal().history().count(), 1);
+    }
+
+    #[test]
+    fn test_filter_tasks() {
+        let mut manager = TaskManager::new();
+        let id1 = manager.add_task("Task 1".to_string());
+        manager.add_task("Task 2".to_string());
         
         manager.complete_task(&id1).unwrap();
         
-        let completed_tasks = manager.get_tasks_by_status(Status::Completed);
+        let completed_tasks = manager.get_tasks_by_status(TaskStatus::Completed);
         assert_eq!(completed_tasks.len(), 1);
         assert_eq!(completed_tasks[0].title, "Task 1");
         
-        let todo_tasks = manager.get_tasks_by_status(Status::Todo);
+        let todo_tasks = manager.get_tasks_by_status(TaskStatus::Todo);
         assert_eq!(todo_tasks.len(), 1);
         assert_eq!(todo_tasks[0].title, "Task 2");
     }
 
+    #[test]
+    fn test_filter_tasks_by_due_date() {
+        let mut manager = TaskManager::new();
+        let soon = manager.add_task("Soon".to_string());
+        let later = manager.add_task("Later".to_string());
+        manager.add_task("No due date".to_string());
+        let now = Utc::now();
+        manager.set_task_due_date(&soon, Some(now + chrono::Duration::days(1))).unwrap();
+        manager.set_task_due_date(&later, Some(now + chrono::Duration::days(10))).unwrap();
+
+        let filter = TaskFilter {
+            due_before: Some(now + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        let tasks = manager.filter_tasks(&filter);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, soon);
+
+        let filter = TaskFilter {
+            due_after: Some(now + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        let tasks = manager.filter_tasks(&filter);
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, later);
+    }
+
     #[test]
     fn test_search_tasks() {
         let mut manager = TaskManager::new();
@@ -378,7 +1122,7 @@ mod tests {
         let mut manager = TaskManager::new();
         let id1 = manager.add_task("Task 1".to_string());
         let id2 = manager.add_task("Task 2".to_string());
-        let id3 = manager.add_task("Task 3".to_string());
+        manager.add_task("Task 3".to_string());
         
         manager.complete_task(&id1).unwrap();
         manager.start_task(&id2).unwrap();
@@ -389,4 +1133,146 @@ mod tests {
         assert_eq!(stats.in_progress, 1);
         assert_eq!(stats.todo, 1);
     }
+
+    #[test]
+    fn test_dependencies() {
+        let mut manager = TaskManager::new();
+        let a = manager.add_task("A".to_string());
+        let b = manager.add_task("B".to_string());
+        let c = manager.add_task("C".to_string());
+
+        // C dépend de B qui dépend de A
+        manager.add_dependency(&b, &a).unwrap();
+        manager.add_dependency(&c, &b).unwrap();
+        assert!(manager.add_dependency(&c, &b).is_err());
+        assert!(manager.add_dependency(&a, &a).is_err());
+        let cycle = manager.add_dependency(&a, &c).unwrap_err().to_string();
+        assert!(cycle.contains("cycle"), "{}", cycle);
+        assert_eq!(manager.dependency_path(&c, &a), Some(vec![c, b, a]));
+
+        assert_eq!(manager.get_blocked_ids(), HashSet::from([b, c]));
+        manager.complete_task(&a).unwrap();
+        assert_eq!(manager.get_blocked_ids(), HashSet::from([c]));
+        assert_eq!(manager.get_blocking_tasks(&c)[0].id, b);
+
+        // Supprimer B libère C
+        manager.delete_task(&b).unwrap();
+        assert!(manager.get_task(&c).unwrap().depends_on.is_empty());
+        assert!(manager.get_blocked_ids().is_empty());
+    }
+
+    #[test]
+    fn test_recurring_task() {
+        use chrono::{Duration, TimeZone};
+
+        let mut manager = TaskManager::new();
+        let id = manager.add_task("Sortir les poubelles".to_string());
+        let monday = Utc.with_ymd_and_hms(2024, 1, 1, 20, 0, 0).unwrap();
+        manager.set_task_due_date(&id, Some(monday)).unwrap();
+        let recurrence: Recurrence = "every 2 weeks on mon,thu".parse().unwrap();
+        assert_eq!(recurrence.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH");
+        assert_eq!(recurrence.to_human(), "every 2 weeks on mon,thu");
+        manager.set_task_recurrence(&id, Some(recurrence.clone())).unwrap();
+
+        // La série suit le rythme, en sautant les semaines impaires
+        assert_eq!(recurrence.next_after(monday, &Utc), Some(monday + Duration::days(3)));
+        assert_eq!(recurrence.next_after(monday + Duration::days(3), &Utc), Some(monday + Duration::days(14)));
+
+        // Les occurrences passées sont sautées
+        let next_id = manager.complete_task(&id).unwrap().unwrap();
+        let next = manager.get_task(&next_id).unwrap();
+        assert_eq!(next.series_id, Some(id));
+        assert_eq!(next.recurrence, Some(recurrence));
+        assert!(next.due_date.unwrap() > Utc::now());
+        assert_eq!(manager.get_task(&id).unwrap().series_id, Some(id));
+
+        // Terminer à nouveau une occurrence rouverte ne duplique pas la suivante
+        manager.reopen_task(&id).unwrap();
+        assert_eq!(manager.complete_task(&id).unwrap(), None);
+
+        assert_eq!(manager.stop_recurrence(&next_id).unwrap(), 2);
+        assert_eq!(manager.complete_task(&next_id).unwrap(), None);
+        assert!(manager.stop_recurrence(&next_id).is_err());
+
+        assert!("every fortnight".parse::<Recurrence>().is_err());
+        assert!("monthly on mon".parse::<Recurrence>().is_err());
+        assert_eq!("FREQ=MONTHLY".parse::<Recurrence>().unwrap().to_human(), "monthly");
+    }
+
+    #[test]
+    fn test_time_tracking() {
+        let mut manager = TaskManager::new();
+        let write = manager.add_task("Rédiger".to_string());
+        let review = manager.add_task("Relire".to_string());
+        manager.add_tag_to_task(&write, "doc".to_string()).unwrap();
+        manager.add_tag_to_task(&review, "doc".to_string()).unwrap();
+        manager.update_task_priority(&review, Priority::High).unwrap();
+
+        // Un seul minuteur à la fois, et démarrer le minuteur démarre la tâche
+        manager.start_timer(&write).unwrap();
+        assert_eq!(manager.get_task(&write).unwrap().status, TaskStatus::InProgress);
+        assert_eq!(manager.get_active_timer().unwrap().id, write);
+        assert!(manager.start_timer(&review).is_err());
+        assert!(manager.start_timer(&write).is_err());
+        let (stopped, _) = manager.stop_timer().unwrap();
+        assert_eq!(stopped, write);
+        assert!(manager.stop_timer().is_err());
+
+        manager.log_time(&write, Duration::minutes(30)).unwrap();
+        manager.log_time(&review, Duration::minutes(90)).unwrap();
+        assert!(manager.log_time(&review, Duration::zero()).is_err());
+        assert!(manager.log_time(&review, Duration::try_minutes(100_000_000_000_000).unwrap()).is_err());
+
+        // Terminer la tâche arrête son minuteur
+        manager.start_timer(&review).unwrap();
+        manager.complete_task(&review).unwrap();
+        assert!(manager.get_active_timer().is_none());
+        assert!(manager.start_timer(&review).is_err());
+
+        let stats = manager.get_stats();
+        assert!(stats.time_spent >= Duration::minutes(120));
+        let doc = &stats.time_by_tag["doc"];
+        assert_eq!(doc.tasks, 2);
+        assert!(doc.average() >= Duration::minutes(60));
+        let priorities: Vec<Priority> = stats.time_by_priority.iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(priorities, vec![Priority::High, Priority::Medium]);
+        assert_eq!(stats.time_by_priority[1].1.tasks, 1);
+    }
+
+    #[test]
+    fn test_subtasks() {
+        let mut manager = TaskManager::new();
+        let parent = manager.add_task("Parent".to_string());
+        let child1 = manager.add_task("Child 1".to_string());
+        let child2 = manager.add_task("Child 2".to_string());
+        let grandchild = manager.add_task("Grandchild".to_string());
+        manager.set_task_parent(&child1, Some(parent)).unwrap();
+        manager.set_task_parent(&child2, Some(parent)).unwrap();
+        manager.set_task_parent(&grandchild, Some(child2)).unwrap();
+
+        // Pas de cycle
+        assert!(manager.set_task_parent(&parent, Some(grandchild)).is_err());
+        assert!(manager.set_task_parent(&parent, Some(parent)).is_err());
+
+        let tree: Vec<(usize, Uuid)> = manager
+            .get_subtask_tree(&parent)
+            .into_iter()
+            .map(|(depth, t)| (depth, t.id))
+            .collect();
+        assert_eq!(tree, vec![(1, child1), (1, child2), (2, grandchild)]);
+        assert_eq!(manager.get_ancestors(&grandchild), vec![child2, parent]);
+
+        // child1 terminée, child2 à 0 tant que grandchild est ouverte
+        manager.complete_task(&child1).unwrap();
+        assert_eq!(manager.task_progress(&parent), 0.5);
+        assert_eq!(manager.get_open_subtasks(&parent).len(), 2);
+        manager.complete_task(&grandchild).unwrap();
+        assert_eq!(manager.task_progress(&parent), 1.0);
+        assert_eq!(manager.get_stats().subtasks, 3);
+        assert_eq!(manager.get_stats().progress, 1.0);
+
+        // Supprimer un parent fait remonter ses sous-tâches
+        manager.delete_task(&child2).unwrap();
+        assert_eq!(manager.get_task(&grandchild).unwrap().parent_id, Some(parent));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+//! Fuseau horaire de l'utilisateur
+//!
+//! Les dates sont stockées en UTC ; le fuseau configuré (`timezone` dans
+//! `config.toml`, `RTF_TIMEZONE` ou `--timezone`) sert à interpréter les
+//! dates saisies, à les afficher et à calculer « aujourd'hui » et « cette
+//! semaine ». Par défaut, c'est le fuseau du système.
+//!
+//! `UserTimeZone` implémente `chrono::TimeZone` : `DateTime<UserTimeZone>`
+//! s'utilise comme `DateTime<Local>`.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Fuseau choisi par `set_current`, lu par `UserTimeZone::current`
+static CURRENT: OnceLock<UserTimeZone> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserTimeZone {
+    /// Fuseau du système (variable `TZ` ou configuration de la machine)
+    #[default]
+    System,
+    /// Fuseau de la base IANA, par exemple `Europe/Paris`
+    Named(chrono_tz::Tz),
+}
+
+/// Décalage d'une date dans un `UserTimeZone`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserOffset {
+    zone: UserTimeZone,
+    fixed: FixedOffset,
+}
+
+impl Offset for UserOffset {
+    fn fix(&self) -> FixedOffset {
+        self.fixed
+    }
+}
+
+impl fmt::Display for UserOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fixed.fmt(f)
+    }
+}
+
+impl UserTimeZone {
+    /// Fuseau configuré pour la commande en cours, sinon celui du système
+    pub fn current() -> Self {
+        CURRENT.get().copied().unwrap_or_default()
+    }
+
+    /// Fixe le fuseau de la commande en cours ; seul le premier appel compte
+    pub fn set_current(zone: Self) {
+        let _ = CURRENT.set(zone);
+    }
+
+    /// Date et heure courantes dans ce fuseau
+    pub fn now(&self) -> DateTime<Self> {
+        Utc::now().with_timezone(self)
+    }
+
+    /// Jour courant dans ce fuseau
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// Indique si `date` tombe aujourd'hui dans ce fuseau
+    pub fn is_today(&self, date: &DateTime<Utc>) -> bool {
+        date.with_timezone(self).date_naive() == self.today()
+    }
+
+    /// Indique si `date` tombe dans la semaine en cours (du lundi au dimanche)
+    pub fn is_this_week(&self, date: &DateTime<Utc>) -> bool {
+        let today = self.today();
+        let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+        let day = date.with_timezone(self).date_naive();
+        day >= monday && day < monday + Days::new(7)
+    }
+
+    fn offset(&self, fixed: FixedOffset) -> UserOffset {
+        UserOffset { zone: *self, fixed }
+    }
+}
+
+impl TimeZone for UserTimeZone {
+    type Offset = UserOffset;
+
+    fn from_offset(offset: &UserOffset) -> Self {
+        offset.zone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<UserOffset> {
+        match self {
+            UserTimeZone::System => Local.offset_from_local_date(local).map(|o| self.offset(o)),
+            UserTimeZone::Named(tz) => tz.offset_from_local_date(local).map(|o| self.offset(o.fix())),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<UserOffset> {
+        match self {
+            UserTimeZone::System => Local.offset_from_local_datetime(local).map(|o| self.offset(o)),
+            UserTimeZone::Named(tz) => tz.offset_from_local_datetime(local).map(|o| self.offset(o.fix())),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> UserOffset {
+        match self {
+            UserTimeZone::System => self.offset(Local.offset_from_utc_date(utc)),
+            UserTimeZone::Named(tz) => self.offset(tz.offset_from_utc_date(utc).fix()),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> UserOffset {
+        match self {
+            UserTimeZone::System => self.offset(Local.offset_from_utc_datetime(utc)),
+            UserTimeZone::Named(tz) => self.offset(tz.offset_from_utc_datetime(utc).fix()),
+        }
+    }
+}
+
+/// `system` ou le nom IANA du fuseau
+impl fmt::Display for UserTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserTimeZone::System => f.write_str("system"),
+            UserTimeZone::Named(tz) => f.write_str(tz.name()),
+        }
+    }
+}
+
+impl FromStr for UserTimeZone {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("system") || value.eq_ignore_ascii_case("local") {
+            return Ok(UserTimeZone::System);
+        }
+        value
+            .parse::<chrono_tz::Tz>()
+            .map(UserTimeZone::Named)
+            .map_err(|_| anyhow!("Fuseau horaire inconnu: {} (exemples: system, UTC, Europe/Paris)", value))
+    }
+}
+
+impl Serialize for UserTimeZone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UserTimeZone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!("system".parse::<UserTimeZone>().unwrap(), UserTimeZone::System);
+        assert_eq!(
+            "Europe/Paris".parse::<UserTimeZone>().unwrap(),
+            UserTimeZone::Named(chrono_tz::Europe::Paris)
+        );
+        assert_eq!(UserTimeZone::Named(chrono_tz::Europe::Paris).to_string(), "Europe/Paris");
+        assert!("Mars/Olympus".parse::<UserTimeZone>().is_err());
+    }
+
+    #[test]
+    fn test_named_timezone_conversions() {
+        let paris: UserTimeZone = "Europe/Paris".parse().unwrap();
+        let summer: DateTime<Utc> = "2024-05-01T07:00:00Z".parse().unwrap();
+        let winter: DateTime<Utc> = "2024-01-15T07:00:00Z".parse().unwrap();
+        assert_eq!(summer.with_timezone(&paris).format("%H:%M %z").to_string(), "09:00 +0200");
+        assert_eq!(winter.with_timezone(&paris).format("%H:%M %z").to_string(), "08:00 +0100");
+
+        let local = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let date = paris.from_local_datetime(&local).unwrap();
+        assert_eq!(date.with_timezone(&Utc), summer);
+        assert_eq!(date.timezone(), paris);
+    }
+}
@@ -1,36 +1,51 @@
-use crate::task::{Priority, Status, Task};
-use crate::task_manager::TaskStats;
-use chrono::{DateTime, Local, Utc};
+use crate::import::{ImportPlan, Resolution};
+use crate::task::{Priority, TaskStatus, Task};
+use crate::task_manager::{TaskStats, TimeSpent};
+use crate::timezone::UserTimeZone;
+use chrono::{DateTime, Utc};
 use colored::*;
+use std::collections::HashSet;
+use uuid::Uuid;
 
 
 pub struct TaskDisplay;
 
 impl TaskDisplay {
-    /// Affiche une liste de tâches dans un format tabulaire
-    pub fn print_task_list(tasks: &[&Task]) {
+    /// Affiche une liste de tâches dans un format tabulaire, les sous-tâches
+    /// en retrait sous leur tâche parente ; les tâches de `blocked` sont
+    /// marquées comme bloquées par une dépendance
+    pub fn print_task_list(tasks: &[&Task], blocked: &HashSet<Uuid>) {
         if tasks.is_empty() {
             return;
         }
 
         println!();
         println!(
-            "{:<10} {:<30} {:<12} {:<10} {:<15} {:<10}",
+            "{:<10} {:<30} {:<12} {:<10} {:<15} {:<10} {}",
             "ID".bold().underline(),
             "TITRE".bold().underline(),
             "STATUT".bold().underline(),
             "PRIORITÉ".bold().underline(),
             "TAGS".bold().underline(),
-            "ÉCHÉANCE".bold().underline()
+            "ÉCHÉANCE".bold().underline(),
+            "RÉPÉTITION".bold().underline()
         );
-        println!("{}", "─".repeat(90).bright_black());
+        println!("{}", "─".repeat(105).bright_black());
 
-        for task in tasks {
+        for (depth, task) in tree_order(tasks) {
             let id_short = task.id.to_string()[..8].to_uppercase();
-            let title = if task.title.len() > 28 {
-                format!("{}...", &task.title[..25])
+            let indent = if depth > 0 {
+                format!("{}└ ", "  ".repeat(depth - 1))
             } else {
-                task.title.clone()
+                String::new()
+            };
+            let lock = if blocked.contains(&task.id) { "⛔ " } else { "" };
+            let timer = if task.active_session().is_some() { "⏱ " } else { "" };
+            let title = format!("{}{}{}{}", indent, lock, timer, task.title);
+            let title = if title.chars().count() > 28 {
+                format!("{}...", title.chars().take(25).collect::<String>())
+            } else {
+                title
             };
 
             let status_colored = Self::colorize_status(&task.status);
@@ -49,10 +64,10 @@ impl TaskDisplay {
             };
 
             let due_str = if let Some(due_date) = task.due_date {
-                let local_due = due_date.with_timezone(&Local);
+                let local_due = due_date.with_timezone(&UserTimeZone::current());
                 let now = Utc::now();
                 
-                if due_date < now && task.status != Status::Completed {
+                if due_date < now && task.status != TaskStatus::Completed {
                     format!(
                         "{} {}",
                         "⚠".red(),
@@ -65,14 +80,20 @@ impl TaskDisplay {
                 "-".bright_black().to_string()
             };
 
+            let repeat_str = match &task.recurrence {
+                Some(recurrence) => recurrence.to_human().bright_magenta().to_string(),
+                None => "-".bright_black().to_string(),
+            };
+
             println!(
-                "{:<10} {:<30} {:<20} {:<18} {:<25} {:<10}",
+                "{:<10} {:<30} {:<20} {:<18} {:<25} {:<10} {}",
                 id_short.bright_blue(),
                 title,
                 status_colored,
                 priority_colored,
                 tags_str,
-                due_str
+                due_str,
+                repeat_str
             );
         }
         
@@ -82,10 +103,25 @@ impl TaskDisplay {
             "📋".bright_blue(),
             tasks.len().to_string().bright_white().bold()
         );
+        let blocked_count = tasks.iter().filter(|t| blocked.contains(&t.id)).count();
+        if blocked_count > 0 {
+            println!(
+                "{} {} bloquée(s) par une dépendance",
+                "⛔".red(),
+                blocked_count.to_string().bright_white().bold()
+            );
+        }
     }
 
-    /// Affiche les détails complets d'une tâche
-    pub fn print_task_details(task: &Task) {
+    /// Affiche les détails complets d'une tâche, avec sa tâche parente,
+    /// l'arbre de ses sous-tâches (profondeur, tâche) et ses dépendances
+    pub fn print_task_details(
+        task: &Task,
+        parent: Option<&Task>,
+        subtasks: &[(usize, &Task)],
+        progress: f64,
+        dependencies: &[&Task],
+    ) {
         println!();
         println!("{}", "═".repeat(60).bright_blue());
         println!(
@@ -124,6 +160,30 @@ impl TaskDisplay {
             Self::colorize_priority(&task.priority)
         );
 
+        if let Some(recurrence) = &task.recurrence {
+            println!(
+                "{:<15} {}",
+                "Répétition:".bold(),
+                recurrence.to_human().bright_magenta()
+            );
+        }
+        if let Some(series_id) = task.series_id.filter(|s| *s != task.id) {
+            println!(
+                "{:<15} {}",
+                "Série:".bold(),
+                format!("[{}]", series_id.to_string()[..8].to_uppercase()).bright_black()
+            );
+        }
+
+        if let Some(parent) = parent {
+            println!(
+                "{:<15} {} {}",
+                "Parente:".bold(),
+                format!("[{}]", parent.id.to_string()[..8].to_uppercase()).bright_blue(),
+                parent.title
+            );
+        }
+
         // Tags
         if !task.tags.is_empty() {
             println!(
@@ -143,7 +203,7 @@ impl TaskDisplay {
             "{:<15} {}",
             "Créée le:".bold(),
             task.created_at
-                .with_timezone(&Local)
+                .with_timezone(&UserTimeZone::current())
                 .format("%d/%m/%Y à %H:%M")
                 .to_string()
                 .bright_black()
@@ -152,16 +212,16 @@ impl TaskDisplay {
             "{:<15} {}",
             "Modifiée le:".bold(),
             task.updated_at
-                .with_timezone(&Local)
+                .with_timezone(&UserTimeZone::current())
                 .format("%d/%m/%Y à %H:%M")
                 .to_string()
                 .bright_black()
         );
 
         if let Some(due_date) = task.due_date {
-            let local_due = due_date.with_timezone(&Local);
+            let local_due = due_date.with_timezone(&UserTimeZone::current());
             let now = Utc::now();
-            let due_str = if due_date < now && task.status != Status::Completed {
+            let due_str = if due_date < now && task.status != TaskStatus::Completed {
                 format!(
                     "{} {}",
                     "⚠ EN RETARD:".red().bold(),
@@ -170,11 +230,30 @@ impl TaskDisplay {
             } else {
                 local_due.format("%d/%m/%Y à %H:%M").to_string().normal().to_string()
             };
+            let relative = match task.status {
+                TaskStatus::Todo | TaskStatus::InProgress => {
+                    format!(" ({})", relative_time(&due_date)).bright_black().to_string()
+                }
+                _ => String::new(),
+            };
             
             println!(
-                "{:<15} {}",
+                "{:<15} {}{}",
                 "Échéance:".bold(),
-                due_str
+                due_str,
+                relative
+            );
+        }
+
+        if let Some(started_at) = task.started_at {
+            println!(
+                "{:<15} {}",
+                "Démarrée le:".bold(),
+                started_at
+                    .with_timezone(&UserTimeZone::current())
+                    .format("%d/%m/%Y à %H:%M")
+                    .to_string()
+                    .bright_blue()
             );
         }
 
@@ -183,13 +262,95 @@ impl TaskDisplay {
                 "{:<15} {}",
                 "Terminée le:".bold(),
                 completed_at
-                    .with_timezone(&Local)
+                    .with_timezone(&UserTimeZone::current())
                     .format("%d/%m/%Y à %H:%M")
                     .to_string()
                     .green()
             );
         }
 
+        if let Some(cancelled_at) = task.cancelled_at {
+            println!(
+                "{:<15} {}",
+                "Annulée le:".bold(),
+                cancelled_at
+                    .with_timezone(&UserTimeZone::current())
+                    .format("%d/%m/%Y à %H:%M")
+                    .to_string()
+                    .red()
+            );
+        }
+
+        if let Some(reopened_at) = task.reopened_at {
+            println!(
+                "{:<15} {}",
+                "Rouverte le:".bold(),
+                reopened_at
+                    .with_timezone(&UserTimeZone::current())
+                    .format("%d/%m/%Y à %H:%M")
+                    .to_string()
+                    .yellow()
+            );
+        }
+
+        if !task.work_sessions.is_empty() {
+            let now = Utc::now();
+            let mut time_str = format!(
+                "{} ({} session(s))",
+                format_time_spent(&task.time_spent(now)).bright_blue(),
+                task.work_sessions.len()
+            );
+            if let Some(session) = task.active_session() {
+                time_str.push_str(&format!(
+                    ", {} depuis {}",
+                    "⏱ minuteur en cours".blue(),
+                    session.started_at.with_timezone(&UserTimeZone::current()).format("%H:%M")
+                ));
+            }
+            println!("{:<15} {}", "Temps passé:".bold(), time_str);
+        }
+
+        if !dependencies.is_empty() {
+            println!();
+            println!("{}", "Dépend de:".bold().underline());
+            for dependency in dependencies {
+                println!(
+                    "  {} {} {}",
+                    format!("[{}]", dependency.id.to_string()[..8].to_uppercase()).bright_blue(),
+                    dependency.title,
+                    Self::colorize_status(&dependency.status)
+                );
+            }
+        }
+
+        if !subtasks.is_empty() {
+            let done = subtasks
+                .iter()
+                .filter(|(_, t)| t.status == TaskStatus::Completed)
+                .count();
+            println!();
+            println!(
+                "{} {}",
+                "Sous-tâches:".bold().underline(),
+                format!(
+                    "{}/{} terminée(s), {:.0}%",
+                    done,
+                    subtasks.len(),
+                    progress * 100.0
+                )
+                .bright_black()
+            );
+            for (depth, subtask) in subtasks {
+                println!(
+                    "  {}└ {} {} {}",
+                    "  ".repeat(depth - 1),
+                    format!("[{}]", subtask.id.to_string()[..8].to_uppercase()).bright_blue(),
+                    subtask.title,
+                    Self::colorize_status(&subtask.status)
+                );
+            }
+        }
+
         println!();
         println!("{}", "═".repeat(60).bright_blue());
     }
@@ -212,45 +373,40 @@ impl TaskDisplay {
             "Total des tâches:".bold(),
             stats.total.to_string().bright_white().bold()
         );
+        if stats.subtasks > 0 {
+            println!(
+                "{:<20} {}",
+                "Dont sous-tâches:".bold(),
+                stats.subtasks.to_string().bright_white()
+            );
+        }
         println!();
 
         // Répartition par statut
         println!("{}", "Répartition par statut:".bold().underline());
         println!(
-            "  {:<15} {}",
+            "  {:<15} {} ({}%)",
             "À faire:".normal(),
-            format!(
-                "{} ({}%)",
-                stats.todo.to_string().bright_yellow(),
-                Self::percentage(stats.todo, stats.total).bright_black()
-            )
+            stats.todo.to_string().bright_yellow(),
+            Self::percentage(stats.todo, stats.total).bright_black()
         );
         println!(
-            "  {:<15} {}",
+            "  {:<15} {} ({}%)",
             "En cours:".normal(),
-            format!(
-                "{} ({}%)",
-                stats.in_progress.to_string().bright_blue(),
-                Self::percentage(stats.in_progress, stats.total).bright_black()
-            )
+            stats.in_progress.to_string().bright_blue(),
+            Self::percentage(stats.in_progress, stats.total).bright_black()
         );
         println!(
-            "  {:<15} {}",
+            "  {:<15} {} ({}%)",
             "Terminées:".normal(),
-            format!(
-                "{} ({}%)",
-                stats.completed.to_string().bright_green(),
-                Self::percentage(stats.completed, stats.total).bright_black()
-            )
+            stats.completed.to_string().bright_green(),
+            Self::percentage(stats.completed, stats.total).bright_black()
         );
         println!(
-            "  {:<15} {}",
+            "  {:<15} {} ({}%)",
             "Annulées:".normal(),
-            format!(
-                "{} ({}%)",
-                stats.cancelled.to_string().bright_red(),
-                Self::percentage(stats.cancelled, stats.total).bright_black()
-            )
+            stats.cancelled.to_string().bright_red(),
+            Self::percentage(stats.cancelled, stats.total).bright_black()
         );
         println!();
 
@@ -267,11 +423,35 @@ impl TaskDisplay {
                 "✓".green().bold()
             );
         }
+        if stats.due_this_week > 0 {
+            println!(
+                "{} {} à rendre aujourd'hui, {} cette semaine",
+                "📅".bright_blue().bold(),
+                stats.due_today.to_string().bright_yellow(),
+                stats.due_this_week.to_string().bright_yellow()
+            );
+        }
 
-        // Barre de progression
+        // Temps passé, par priorité et par tag
+        if stats.time_spent > chrono::Duration::zero() {
+            println!();
+            println!(
+                "{} {}",
+                "Temps passé:".bold().underline(),
+                format_time_spent(&stats.time_spent).bright_white().bold()
+            );
+            for (priority, time) in &stats.time_by_priority {
+                Self::print_time_spent(&Self::colorize_priority(priority).to_string(), time);
+            }
+            for (tag, time) in &stats.time_by_tag {
+                Self::print_time_spent(&format!("#{}", tag).bright_blue().to_string(), time);
+            }
+        }
+
+        // Barre de progression, sous-tâches comprises
         if stats.total > 0 {
             println!();
-            let progress = (stats.completed as f64 / stats.total as f64 * 100.0) as usize;
+            let progress = (stats.progress * 100.0) as usize;
             let bar_length = 30;
             let filled = (progress * bar_length / 100).min(bar_length);
             let empty = bar_length - filled;
@@ -290,12 +470,12 @@ impl TaskDisplay {
     }
 
     /// Colorie le statut selon sa valeur
-    fn colorize_status(status: &Status) -> ColoredString {
+    fn colorize_status(status: &TaskStatus) -> ColoredString {
         match status {
-            Status::Todo => "📝 À faire".bright_yellow(),
-            Status::InProgress => "⚡ En cours".bright_blue(),
-            Status::Completed => "✅ Terminé".bright_green(),
-            Status::Cancelled => "❌ Annulé".bright_red(),
+            TaskStatus::Todo => "📝 À faire".bright_yellow(),
+            TaskStatus::InProgress => "⚡ En cours".bright_blue(),
+            TaskStatus::Completed => "✅ Terminé".bright_green(),
+            TaskStatus::Cancelled => "❌ Annulé".bright_red(),
         }
     }
 
@@ -309,6 +489,21 @@ impl TaskDisplay {
         }
     }
 
+    /// Ligne du temps passé sur un groupe de tâches, dans les statistiques
+    fn print_time_spent(label: &str, time: &TimeSpent) {
+        println!(
+            "  {:<15} {} {}",
+            label,
+            format_time_spent(&time.total),
+            format!(
+                "(moyenne {} sur {} tâche(s))",
+                format_time_spent(&time.average()),
+                time.tasks
+            )
+            .bright_black()
+        );
+    }
+
     /// Calcule le pourcentage
     fn percentage(value: usize, total: usize) -> String {
         if total == 0 {
@@ -318,6 +513,38 @@ impl TaskDisplay {
         }
     }
 
+    /// Affiche le résumé d'un import
+    pub fn print_import_plan(plan: &ImportPlan) {
+        println!("{}", "📥 Résumé de l'import".bold());
+        println!("  {} {} ajoutée(s)", "+".green().bold(), plan.added.len());
+        println!("  {} {} mise(s) à jour", "~".yellow().bold(), plan.updated.len());
+        if !plan.removed.is_empty() {
+            println!("  {} {} supprimée(s)", "-".red().bold(), plan.removed.len());
+        }
+        if plan.unchanged > 0 {
+            println!("  {} {} identique(s)", "=".bright_black(), plan.unchanged);
+        }
+        if plan.skipped > 0 {
+            println!("  {} {} ignorée(s) (déjà présentes)", "·".bright_black(), plan.skipped);
+        }
+
+        if !plan.conflicts.is_empty() {
+            println!("  {} {} conflit(s):", "⚠".yellow().bold(), plan.conflicts.len());
+            for conflict in &plan.conflicts {
+                let kept = match conflict.resolution {
+                    Resolution::KeepLocal => "version locale conservée",
+                    Resolution::TakeIncoming => "version importée retenue",
+                };
+                println!(
+                    "      [{}] {} ({})",
+                    conflict.id.to_string()[..8].to_uppercase(),
+                    conflict.title,
+                    kept.bright_black()
+                );
+            }
+        }
+    }
+
     /// Affiche un message de succès
     pub fn success(message: &str) {
         println!("{} {}", "✓".green().bold(), message);
@@ -339,6 +566,41 @@ impl TaskDisplay {
     }
 }
 
+/// Ordonne les tâches en arbre : chaque sous-tâche suit sa tâche parente,
+/// avec sa profondeur. Une tâche dont la parente n'est pas dans la liste est
+/// affichée comme une racine.
+fn tree_order<'a>(tasks: &[&'a Task]) -> Vec<(usize, &'a Task)> {
+    fn visit<'a>(
+        tasks: &[&'a Task],
+        task: &'a Task,
+        depth: usize,
+        visited: &mut HashSet<Uuid>,
+        ordered: &mut Vec<(usize, &'a Task)>,
+    ) {
+        if !visited.insert(task.id) {
+            return;
+        }
+        ordered.push((depth, task));
+        for child in tasks.iter().filter(|t| t.parent_id == Some(task.id)) {
+            visit(tasks, child, depth + 1, visited, ordered);
+        }
+    }
+
+    let listed: HashSet<Uuid> = tasks.iter().map(|t| t.id).collect();
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        if !task.parent_id.is_some_and(|p| listed.contains(&p)) {
+            visit(tasks, task, 0, &mut visited, &mut ordered);
+        }
+    }
+    // Les tâches restantes forment un cycle (données importées)
+    for task in tasks {
+        visit(tasks, task, 0, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
 /// Trait pour formater les durées de manière lisible
 pub trait DurationFormat {
     fn human_duration(&self) -> String;
@@ -360,9 +622,31 @@ impl DurationFormat for chrono::Duration {
     }
 }
 
-/// Utilitaires pour l'affichage des dates relatives
+/// Temps passé sur une tâche : `45m`, `2h05`
+pub fn format_time_spent(duration: &chrono::Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+/// Utilitaires pour l'affichage des dates relatives : « demain à 09:00 » ou
+/// « hier à 18:30 » pour les jours voisins dans le fuseau de l'utilisateur,
+/// sinon une durée (« dans 3h », « il y a 2j »)
 pub fn relative_time(datetime: &DateTime<Utc>) -> String {
-    let now = Utc::now();
+    relative_time_from(datetime, &UserTimeZone::current().now())
+}
+
+fn relative_time_from(datetime: &DateTime<Utc>, now: &DateTime<UserTimeZone>) -> String {
+    let local = datetime.with_timezone(&now.timezone());
+    match (local.date_naive() - now.date_naive()).num_days() {
+        1 => return format!("demain à {}", local.format("%H:%M")),
+        -1 => return format!("hier à {}", local.format("%H:%M")),
+        _ => {}
+    }
+
     let duration = now.signed_duration_since(*datetime);
     
     if duration.num_seconds() < 0 {
@@ -377,15 +661,27 @@ pub fn relative_time(datetime: &DateTime<Utc>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::Task;
 
     #[test]
     fn test_colorize_status() {
-        let status = Status::Completed;
+        let status = TaskStatus::Completed;
         let colored = TaskDisplay::colorize_status(&status);
         assert!(colored.to_string().contains("Terminé"));
     }
 
+    #[test]
+    fn test_relative_time_uses_timezone() {
+        let tokyo: UserTimeZone = "Asia/Tokyo".parse().unwrap();
+        let utc: UserTimeZone = "UTC".parse().unwrap();
+        let now: DateTime<Utc> = "2024-05-15T14:00:00Z".parse().unwrap();
+        let due: DateTime<Utc> = "2024-05-15T16:00:00Z".parse().unwrap();
+
+        assert_eq!(relative_time_from(&due, &now.with_timezone(&tokyo)), "demain à 01:00");
+        assert_eq!(relative_time_from(&due, &now.with_timezone(&utc)), "dans 2h");
+        assert_eq!(relative_time_from(&now, &due.with_timezone(&tokyo)), "hier à 23:00");
+        assert_eq!(relative_time_from(&(now - chrono::Duration::days(3)), &now.with_timezone(&utc)), "il y a 3j");
+    }
+
     #[test]
     fn test_percentage() {
         assert_eq!(TaskDisplay::percentage(25, 100), "25.0");
@@ -393,6 +689,21 @@ mod tests {
         assert_eq!(TaskDisplay::percentage(10, 0), "0");
     }
 
+    #[test]
+    fn test_tree_order() {
+        let parent = crate::task::new_task("Parent".to_string());
+        let mut child = crate::task::new_task("Child".to_string());
+        child.parent_id = Some(parent.id);
+        let mut orphan = crate::task::new_task("Orphan".to_string());
+        orphan.parent_id = Some(Uuid::new_v4());
+
+        let ordered: Vec<(usize, &str)> = tree_order(&[&child, &orphan, &parent])
+            .into_iter()
+            .map(|(depth, t)| (depth, t.title.as_str()))
+            .collect();
+        assert_eq!(ordered, vec![(0, "Orphan"), (0, "Parent"), (1, "Child")]);
+    }
+
     #[test]
     fn test_duration_format() {
         let duration = chrono::Duration::seconds(3661); // 1h 1m 1s
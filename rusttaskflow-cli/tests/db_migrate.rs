@@ -0,0 +1,94 @@
+//! `rtf db migrate` lancé sur une vraie base, à travers le binaire
+
+use rusqlite::{params, Connection};
+use rusttaskflow_cli::migrations::{self, MIGRATIONS};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::tempdir;
+
+fn tables(conn: &Connection) -> Vec<String> {
+    let mut statement = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+        .unwrap();
+    statement
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn rtf(data_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rtf"))
+        .args(["--storage", "sqlite", "--data-dir"])
+        .arg(data_dir)
+        .args(args)
+        .env("RTF_CONFIG", data_dir.join("config.toml"))
+        .output()
+        .unwrap()
+}
+
+/// Base arrêtée à la migration 3, comme l'aurait laissée une ancienne version
+fn old_schema_database(path: &Path) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, description TEXT NOT NULL, applied_at TEXT NOT NULL)",
+    )
+    .unwrap();
+    for migration in &MIGRATIONS[..3] {
+        conn.execute_batch(migration.sql).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, '')",
+            params![migration.version, migration.description],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_dry_run_leaves_an_old_schema_untouched() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("tasks.db");
+    old_schema_database(&db_path);
+
+    let output = rtf(dir.path(), &["db", "migrate", "--dry-run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{} migration(s) à appliquer", MIGRATIONS.len() - 3)), "{}", stdout);
+
+    let conn = Connection::open(&db_path).unwrap();
+    assert_eq!(migrations::current_version(&conn).unwrap(), 3);
+
+    let output = rtf(dir.path(), &["db", "migrate"]);
+    assert!(output.status.success());
+    assert_eq!(migrations::current_version(&conn).unwrap(), MIGRATIONS.last().unwrap().version);
+}
+
+#[test]
+fn test_dry_run_creates_no_database() {
+    let dir = tempdir().unwrap();
+
+    let output = rtf(dir.path(), &["db", "migrate", "--dry-run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{} migration(s) à appliquer", MIGRATIONS.len())), "{}", stdout);
+    assert!(!dir.path().join("tasks.db").exists());
+}
+
+#[test]
+fn test_dry_run_leaves_an_unversioned_database_untouched() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("tasks.db");
+    // Schéma des versions antérieures au versionnage
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE tasks (id TEXT PRIMARY KEY, title TEXT NOT NULL, description TEXT, status TEXT NOT NULL, \
+         priority TEXT NOT NULL, tags TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL, \
+         due_date TEXT, completed_at TEXT)",
+    )
+    .unwrap();
+
+    let output = rtf(dir.path(), &["db", "migrate", "--dry-run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(tables(&conn), ["tasks"]);
+    assert_eq!(migrations::current_version(&conn).unwrap(), 0);
+}
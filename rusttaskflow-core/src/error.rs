@@ -7,10 +7,14 @@ pub enum TaskFlowError {
     
     #[error("User not found: {id}")]
     UserNotFound { id: String },
+
+    #[error("Project not found: {id}")]
+    ProjectNotFound { id: String },
     
     #[error("Invalid task status transition from {from} to {to}")]
     InvalidStatusTransition { from: String, to: String },
     
+    #[cfg(feature = "database")]
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     
@@ -0,0 +1,213 @@
+//! Export iCalendar (RFC 5545) des tâches sous forme de VTODO
+//!
+//! Partagé par l'export du CLI et par le flux `.ics` du serveur web.
+
+use crate::models::{Priority, Task, TaskStatus};
+use chrono::{DateTime, Utc};
+
+/// Format des dates UTC de RFC 5545 (`20240115T100000Z`)
+pub const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Longueur maximale d'une ligne, en octets, avant repli
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Échappe une valeur de type TEXT (RFC 5545 §3.3.11)
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Ajoute une ligne de contenu terminée par CRLF, repliée à 75 octets
+/// sans couper de caractère UTF-8 (RFC 5545 §3.1)
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // L'espace de continuation compte dans la ligne suivante
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// Valeur STATUS d'un VTODO
+pub fn status_value(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "NEEDS-ACTION",
+        TaskStatus::InProgress => "IN-PROCESS",
+        TaskStatus::Completed => "COMPLETED",
+        TaskStatus::Cancelled => "CANCELLED",
+    }
+}
+
+/// Valeur PRIORITY d'un VTODO (1 = la plus haute, 9 = la plus basse)
+pub fn priority_value(priority: &Priority) -> u8 {
+    match priority {
+        Priority::Critical => 1,
+        Priority::High => 3,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn push_vtodo(out: &mut String, task: &Task) {
+    push_line(out, "BEGIN:VTODO");
+    push_line(out, &format!("UID:{}", task.id));
+    // Sans METHOD, DTSTAMP est la date de dernière révision de l'objet
+    push_line(out, &format!("DTSTAMP:{}", format_date(task.updated_at)));
+    push_line(out, &format!("CREATED:{}", format_date(task.created_at)));
+    push_line(out, &format!("LAST-MODIFIED:{}", format_date(task.updated_at)));
+    push_line(out, &format!("SUMMARY:{}", escape_text(&task.title)));
+    if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+        push_line(out, &format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(due) = task.due_date {
+        push_line(out, &format!("DUE:{}", format_date(due)));
+    }
+    push_line(out, &format!("STATUS:{}", status_value(&task.status)));
+    push_line(out, &format!("PRIORITY:{}", priority_value(&task.priority)));
+    if !task.tags.is_empty() {
+        let categories: Vec<String> = task.tags.iter().map(|t| escape_text(t)).collect();
+        push_line(out, &format!("CATEGORIES:{}", categories.join(",")));
+    }
+    if let Some(completed_at) = task.completed_at {
+        push_line(out, &format!("COMPLETED:{}", format_date(completed_at)));
+    }
+    if let Some(parent_id) = task.parent_id {
+        push_line(out, &format!("RELATED-TO;RELTYPE=PARENT:{}", parent_id));
+    }
+    // RELTYPE=DEPENDS-ON est défini par RFC 9253
+    for depends_on in &task.depends_on {
+        push_line(out, &format!("RELATED-TO;RELTYPE=DEPENDS-ON:{}", depends_on));
+    }
+    push_line(out, "END:VTODO");
+}
+
+/// Construit un VCALENDAR contenant un VTODO par tâche
+pub fn write_calendar(tasks: &[&Task], name: &str) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//RustTaskFlow//RustTaskFlow//FR");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|t| t.created_at);
+    for task in tasks {
+        push_vtodo(&mut out, task);
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        assert_eq!(escape_text("ligne 1\r\nligne 2\n"), "ligne 1\\nligne 2\\n");
+        assert_eq!(escape_text("déjà vu"), "déjà vu");
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let mut out = String::new();
+        push_line(&mut out, &"a".repeat(200));
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", "a".repeat(200)));
+    }
+
+    #[test]
+    fn test_folding_keeps_utf8_characters_whole() {
+        // 74 octets puis « é » sur deux octets : le repli passe avant lui
+        let line = format!("{}é{}", "a".repeat(74), "b".repeat(10));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines[0], "a".repeat(74));
+        assert_eq!(lines[1], format!(" é{}", "b".repeat(10)));
+        assert!(lines.iter().all(|l| l.len() <= MAX_LINE_OCTETS));
+
+        // Uniquement des caractères sur trois octets
+        let mut out = String::new();
+        push_line(&mut out, &"€".repeat(60));
+        for line in out.trim_end_matches("\r\n").split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", "€".repeat(60)));
+    }
+
+    #[test]
+    fn test_vtodo_lines() {
+        let mut task = Task::new(
+            "Réunion, budget".to_string(),
+            Some("Préparer; relire".to_string()),
+            Priority::High,
+            vec!["travail".to_string()],
+            Uuid::new_v4(),
+        );
+        task.due_date = Some(Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap());
+        task.start().unwrap();
+
+        let calendar = write_calendar(&[&task], "Mes tâches");
+        let lines: Vec<&str> = calendar.split("\r\n").collect();
+        for expected in [
+            "BEGIN:VCALENDAR",
+            "X-WR-CALNAME:Mes tâches",
+            "BEGIN:VTODO",
+            "SUMMARY:Réunion\\, budget",
+            "DESCRIPTION:Préparer\\; relire",
+            "DUE:20240115T100000Z",
+            "STATUS:IN-PROCESS",
+            "PRIORITY:3",
+            "CATEGORIES:travail",
+            "END:VTODO",
+            "END:VCALENDAR",
+        ] {
+            assert!(lines.contains(&expected), "{} absent de\n{}", expected, calendar);
+        }
+        assert!(!calendar.contains("COMPLETED:"));
+
+        task.complete().unwrap();
+        let calendar = write_calendar(&[&task], "Mes tâches");
+        assert!(calendar.contains("\r\nSTATUS:COMPLETED\r\n"));
+        assert!(calendar.contains("\r\nCOMPLETED:"));
+    }
+
+    #[test]
+    fn test_status_and_priority_values() {
+        assert_eq!(status_value(&TaskStatus::Todo), "NEEDS-ACTION");
+        assert_eq!(status_value(&TaskStatus::Cancelled), "CANCELLED");
+        assert_eq!(priority_value(&Priority::Critical), 1);
+        assert_eq!(priority_value(&Priority::Low), 9);
+    }
+}
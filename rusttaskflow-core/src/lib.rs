@@ -1,5 +1,8 @@
 pub mod models;
 pub mod error;
+pub mod ical;
+pub mod recurrence;
 
 pub use models::*;
-pub use error::*;
\ No newline at end of file
+pub use error::*;
+pub use recurrence::{Frequency, Recurrence};
\ No newline at end of file
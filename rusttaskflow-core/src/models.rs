@@ -1,9 +1,13 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use crate::recurrence::Recurrence;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "database", derive(sqlx::Type), sqlx(type_name = "task_status"))]
 pub enum TaskStatus {
+    #[default]
     Todo,
     InProgress,
     Completed,
@@ -21,9 +25,39 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl TaskStatus {
+    /// Identifiant stable (indépendant de la langue d'affichage) utilisé pour le stockage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "Todo",
+            TaskStatus::InProgress => "InProgress",
+            TaskStatus::Completed => "Completed",
+            TaskStatus::Cancelled => "Cancelled",
+        }
+    }
+}
+
+impl std::str::FromStr for TaskStatus {
+    type Err = crate::TaskFlowError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s {
+            "Todo" => Ok(TaskStatus::Todo),
+            "InProgress" => Ok(TaskStatus::InProgress),
+            "Completed" => Ok(TaskStatus::Completed),
+            "Cancelled" => Ok(TaskStatus::Cancelled),
+            _ => Err(crate::TaskFlowError::Validation {
+                message: format!("Unknown task status: {}", s),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "database", derive(sqlx::Type), sqlx(type_name = "task_priority"))]
 pub enum Priority {
     Low,
+    #[default]
     Medium,
     High,
     Critical,
@@ -40,7 +74,36 @@ impl std::fmt::Display for Priority {
     }
 }
 
+impl Priority {
+    /// Identifiant stable (indépendant de la langue d'affichage) utilisé pour le stockage
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "Low",
+            Priority::Medium => "Medium",
+            Priority::High => "High",
+            Priority::Critical => "Critical",
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = crate::TaskFlowError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s {
+            "Low" => Ok(Priority::Low),
+            "Medium" => Ok(Priority::Medium),
+            "High" => Ok(Priority::High),
+            "Critical" => Ok(Priority::Critical),
+            _ => Err(crate::TaskFlowError::Validation {
+                message: format!("Unknown priority: {}", s),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct Task {
     pub id: Uuid,
     pub title: String,
@@ -50,11 +113,53 @@ pub struct Task {
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // Les champs absents des anciens fichiers `tasks.json` du CLI prennent
+    // leur valeur par défaut, ce qui permet de les relire sans conversion.
+    #[serde(default)]
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub cancelled_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reopened_at: Option<DateTime<Utc>>, // Last return to Todo (reopen or reset)
     pub due_date: Option<DateTime<Utc>>,
+    #[serde(default)]
     pub assigned_to: Option<Uuid>, // User ID for collaboration
-    pub created_by: Uuid, // User ID who created the task
+    #[serde(default)]
+    pub created_by: Uuid, // User ID who created the task (nil for local CLI tasks)
+    #[serde(default)]
+    pub parent_id: Option<Uuid>, // Parent task when this one is a subtask
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>, // Tasks that must be completed before this one
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>, // Schedule of a recurring task
+    #[serde(default)]
+    pub series_id: Option<Uuid>, // First occurrence of the series this task belongs to
+    #[serde(default)]
+    pub project_id: Option<Uuid>, // Shared project; None for a personal task
+    #[serde(default)]
+    #[cfg_attr(feature = "database", sqlx(skip))]
+    pub work_sessions: Vec<WorkSession>, // Time tracked on the task, the last session may still be running
+    /// Attributs importés d'autres outils sans équivalent dans ce modèle
+    /// (annotations, UDA Taskwarrior…), conservés pour les réexporter
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[cfg_attr(feature = "database", sqlx(skip))]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// Période de travail sur une tâche ; `ended_at` est absent tant que le
+/// minuteur tourne
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkSession {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl WorkSession {
+    /// Durée de la session, arrêtée à `now` si elle est en cours
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.ended_at.unwrap_or(now) - self.started_at).max(Duration::zero())
+    }
 }
 
 impl Task {
@@ -77,9 +182,18 @@ impl Task {
             updated_at: now,
             started_at: None,
             completed_at: None,
+            cancelled_at: None,
+            reopened_at: None,
             due_date: None,
             assigned_to: None,
             created_by,
+            parent_id: None,
+            depends_on: Vec::new(),
+            recurrence: None,
+            series_id: None,
+            project_id: None,
+            work_sessions: Vec::new(),
+            extra: BTreeMap::new(),
         }
     }
 
@@ -101,6 +215,7 @@ impl Task {
     pub fn complete(&mut self) -> crate::Result<()> {
         match self.status {
             TaskStatus::InProgress | TaskStatus::Todo => {
+                self.stop_timer(Utc::now());
                 self.status = TaskStatus::Completed;
                 self.completed_at = Some(Utc::now());
                 self.updated_at = Utc::now();
@@ -116,7 +231,9 @@ impl Task {
     pub fn cancel(&mut self) -> crate::Result<()> {
         match self.status {
             TaskStatus::Todo | TaskStatus::InProgress => {
+                self.stop_timer(Utc::now());
                 self.status = TaskStatus::Cancelled;
+                self.cancelled_at = Some(Utc::now());
                 self.updated_at = Utc::now();
                 Ok(())
             }
@@ -126,9 +243,204 @@ impl Task {
             }),
         }
     }
+
+    /// Rouvre une tâche terminée ou annulée
+    pub fn reopen(&mut self) -> crate::Result<()> {
+        match self.status {
+            TaskStatus::Completed | TaskStatus::Cancelled => {
+                self.status = TaskStatus::Todo;
+                self.completed_at = None;
+                self.cancelled_at = None;
+                self.reopened_at = Some(Utc::now());
+                self.updated_at = Utc::now();
+                Ok(())
+            }
+            _ => Err(crate::TaskFlowError::InvalidStatusTransition {
+                from: self.status.to_string(),
+                to: TaskStatus::Todo.to_string(),
+            }),
+        }
+    }
+
+    /// Remet une tâche en cours à l'état « À faire »
+    pub fn reset(&mut self) -> crate::Result<()> {
+        match self.status {
+            TaskStatus::InProgress => {
+                self.stop_timer(Utc::now());
+                self.status = TaskStatus::Todo;
+                self.started_at = None;
+                self.reopened_at = Some(Utc::now());
+                self.updated_at = Utc::now();
+                Ok(())
+            }
+            _ => Err(crate::TaskFlowError::InvalidStatusTransition {
+                from: self.status.to_string(),
+                to: TaskStatus::Todo.to_string(),
+            }),
+        }
+    }
+
+    /// Prochaine occurrence d'une tâche récurrente qui vient d'être terminée.
+    /// Son échéance suit la récurrence à partir de l'échéance actuelle (ou de
+    /// la date de fin) en sautant les occurrences déjà passées. La tâche
+    /// terminée est rattachée à la série, dont elle devient la première
+    /// occurrence si elle n'en avait pas. Les dates se comptent dans `tz`.
+    /// `None` si la tâche n'est pas une tâche récurrente terminée, ou si la
+    /// série dépasse les dates représentables.
+    pub fn next_occurrence<Tz: TimeZone>(&mut self, now: DateTime<Utc>, tz: &Tz) -> Option<Task> {
+        let mut recurrence = self.recurrence.clone()?;
+        if self.status != TaskStatus::Completed {
+            return None;
+        }
+        let series_id = *self.series_id.get_or_insert(self.id);
+
+        // Une série mensuelle ou annuelle garde le jour de sa première échéance
+        let start = self.due_date.or(self.completed_at).unwrap_or(now);
+        recurrence.anchor(start, tz);
+
+        let mut due = recurrence.next_after(start, tz)?;
+        while due <= now {
+            due = recurrence.next_after(due, tz)?;
+        }
+
+        let mut next = Task::new(
+            self.title.clone(),
+            self.description.clone(),
+            self.priority.clone(),
+            self.tags.clone(),
+            self.created_by,
+        );
+        next.due_date = Some(due);
+        next.assigned_to = self.assigned_to;
+        next.parent_id = self.parent_id;
+        next.recurrence = Some(recurrence);
+        next.series_id = Some(series_id);
+        next.project_id = self.project_id;
+        Some(next)
+    }
+
+    /// Session de travail en cours, s'il y en a une
+    pub fn active_session(&self) -> Option<&WorkSession> {
+        self.work_sessions.last().filter(|s| s.ended_at.is_none())
+    }
+
+    /// Démarre le minuteur ; une tâche à faire passe en cours
+    pub fn start_timer(&mut self, now: DateTime<Utc>) -> crate::Result<()> {
+        if self.active_session().is_some() {
+            return Err(crate::TaskFlowError::Validation {
+                message: "A timer is already running on this task".to_string(),
+            });
+        }
+        match self.status {
+            TaskStatus::Completed | TaskStatus::Cancelled => {
+                return Err(crate::TaskFlowError::Validation {
+                    message: format!("Cannot track time on a task that is {}", self.status),
+                })
+            }
+            TaskStatus::Todo => self.start()?,
+            TaskStatus::InProgress => {}
+        }
+        self.work_sessions.push(WorkSession {
+            started_at: now,
+            ended_at: None,
+        });
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Arrête le minuteur et renvoie la durée de la session, s'il tournait
+    pub fn stop_timer(&mut self, now: DateTime<Utc>) -> Option<Duration> {
+        let session = self.work_sessions.last_mut().filter(|s| s.ended_at.is_none())?;
+        session.ended_at = Some(now.max(session.started_at));
+        let duration = session.duration(now);
+        self.updated_at = now;
+        Some(duration)
+    }
+
+    /// Enregistre une session déjà terminée, qui s'achève à `ended_at`
+    pub fn log_time(&mut self, duration: Duration, ended_at: DateTime<Utc>) -> crate::Result<()> {
+        if duration <= Duration::zero() {
+            return Err(crate::TaskFlowError::Validation {
+                message: "Logged time must be positive".to_string(),
+            });
+        }
+        let started_at = ended_at.checked_sub_signed(duration).ok_or_else(|| crate::TaskFlowError::Validation {
+            message: "Logged time is too long".to_string(),
+        })?;
+        let session = WorkSession {
+            started_at,
+            ended_at: Some(ended_at),
+        };
+        // La session en cours reste la dernière
+        let position = self.work_sessions.len() - usize::from(self.active_session().is_some());
+        self.work_sessions.insert(position, session);
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Temps passé sur la tâche, session en cours comprise
+    pub fn time_spent(&self, now: DateTime<Utc>) -> Duration {
+        self.work_sessions
+            .iter()
+            .fold(Duration::zero(), |total, session| total + session.duration(now))
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+        self.updated_at = Utc::now();
+    }
+
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+        self.updated_at = Utc::now();
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.updated_at = Utc::now();
+    }
+
+    pub fn add_tag(&mut self, tag: String) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+            self.updated_at = Utc::now();
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t != tag);
+        self.updated_at = Utc::now();
+    }
+
+    pub fn set_due_date(&mut self, due_date: Option<DateTime<Utc>>) {
+        self.due_date = due_date;
+        self.updated_at = Utc::now();
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        if let Some(due_date) = self.due_date {
+            return due_date < Utc::now() && self.status != TaskStatus::Completed;
+        }
+        false
+    }
+
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    pub fn matches_text(&self, text: &str) -> bool {
+        let text_lower = text.to_lowercase();
+        self.title.to_lowercase().contains(&text_lower)
+            || self
+                .description
+                .as_ref()
+                .is_some_and(|desc| desc.to_lowercase().contains(&text_lower))
+            || self.tags.iter().any(|tag| tag.to_lowercase().contains(&text_lower))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct User {
     pub id: Uuid,
     pub username: String,
@@ -162,6 +474,16 @@ pub struct CreateTaskRequest {
     pub tags: Vec<String>,
     pub due_date: Option<DateTime<Utc>>,
     pub assigned_to: Option<Uuid>,
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// RRULE or readable form, e.g. "every 2 weeks on mon,thu"
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// Defaults to the parent task's project
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -174,7 +496,7 @@ pub struct UpdateTaskRequest {
     pub assigned_to: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TaskFilter {
     pub status: Option<TaskStatus>,
     pub priority: Option<Priority>,
@@ -183,6 +505,92 @@ pub struct TaskFilter {
     pub tags: Option<Vec<String>>,
     pub due_before: Option<DateTime<Utc>>,
     pub due_after: Option<DateTime<Utc>>,
+    pub project_id: Option<Uuid>,
+}
+
+/// Rôle d'un membre dans un projet, du plus au moins privilégié
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::Type), sqlx(type_name = "project_role"))]
+pub enum ProjectRole {
+    /// Gère le projet et ses membres
+    Owner,
+    /// Crée et modifie les tâches du projet
+    Editor,
+    /// Consulte les tâches du projet
+    Viewer,
+}
+
+impl ProjectRole {
+    fn rank(&self) -> u8 {
+        match self {
+            ProjectRole::Viewer => 0,
+            ProjectRole::Editor => 1,
+            ProjectRole::Owner => 2,
+        }
+    }
+
+    /// Indique si ce rôle accorde au moins les droits de `required`
+    pub fn includes(&self, required: ProjectRole) -> bool {
+        self.rank() >= required.rank()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct Project {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Project {
+    pub fn new(name: String, description: Option<String>, created_by: Uuid) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            description,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct ProjectMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: ProjectRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateProjectRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateProjectRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddProjectMemberRequest {
+    pub user_id: Uuid,
+    pub role: ProjectRole,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateProjectMemberRequest {
+    pub role: ProjectRole,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,627 @@
+//! Récurrence des tâches, sous-ensemble de la RRULE de RFC 5545
+//!
+//! Une récurrence est stockée et sérialisée sous forme de RRULE
+//! (`FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`). La saisie accepte aussi une forme
+//! lisible : `daily`, `weekly`, `monthly`, `yearly`, `every 3 days`,
+//! `every 2 weeks on mon,thu`, `monthly on day 31`, `yearly on feb 29`.
+//!
+//! Les jours, jours de la semaine et mois se comptent dans le fuseau passé à
+//! `next_after` (celui de l'utilisateur pour le CLI, UTC pour le serveur
+//! web) : une échéance à 23h59 heure locale reste à 23h59, y compris après un
+//! changement d'heure.
+//!
+//! Une série mensuelle ou annuelle garde le jour du mois de sa première
+//! échéance (`BYMONTHDAY`, et `BYMONTH` pour une série annuelle) : un
+//! 31 janvier donne le 29 février puis le 31 mars. Contrairement à RFC 5545,
+//! qui saute les mois trop courts, le jour est ramené au dernier du mois.
+
+use chrono::{DateTime, Datelike, Days, Duration, Month, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::TaskFlowError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn rrule(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+
+    /// Nom de l'unité dans la forme lisible (`every 2 weeks`)
+    fn unit(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Nombre de périodes entre deux occurrences (de 1 à `MAX_INTERVAL`)
+    pub interval: u32,
+    /// Jours de la semaine (`BYDAY`), seulement pour une fréquence hebdomadaire
+    pub weekdays: Vec<Weekday>,
+    /// Jour du mois des occurrences (`BYMONTHDAY`), pour une fréquence
+    /// mensuelle ou annuelle ; par défaut, celui de l'occurrence précédente
+    pub month_day: Option<u32>,
+    /// Mois des occurrences (`BYMONTH`), pour une fréquence annuelle avec `month_day`
+    pub month: Option<u32>,
+}
+
+/// Intervalle maximal accepté à la saisie
+pub const MAX_INTERVAL: u32 = 1000;
+
+const RRULE_DAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "MO"),
+    (Weekday::Tue, "TU"),
+    (Weekday::Wed, "WE"),
+    (Weekday::Thu, "TH"),
+    (Weekday::Fri, "FR"),
+    (Weekday::Sat, "SA"),
+    (Weekday::Sun, "SU"),
+];
+
+fn invalid(value: &str) -> TaskFlowError {
+    TaskFlowError::Validation {
+        message: format!(
+            "Invalid recurrence '{}' (expected daily, weekly, monthly, yearly or e.g. 'every 2 weeks on mon,thu')",
+            value
+        ),
+    }
+}
+
+fn parse_interval(value: &str) -> Option<u32> {
+    value.trim().parse().ok().filter(|n| (1..=MAX_INTERVAL).contains(n))
+}
+
+/// Date UTC d'une heure locale de `tz` : une heure sautée par le passage à
+/// l'heure d'été est décalée d'une heure, une heure répétée prend la première
+fn to_utc<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&local.checked_add_signed(Duration::hours(1))?).earliest())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Date `months` mois après le mois de `from`, au jour `day` ramené au
+/// dernier jour des mois plus courts, à la même heure
+fn in_month(from: NaiveDateTime, months: u32, day: u32) -> Option<NaiveDateTime> {
+    let first = from.date().with_day(1)?.checked_add_months(Months::new(months))?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some(first.with_day(day.min(last.day()))?.and_time(from.time()))
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_day: None,
+            month: None,
+        }
+    }
+
+    /// Fixe le jour (et pour une série annuelle, le mois) des occurrences
+    /// d'après la première échéance dans `tz`, s'ils ne le sont pas déjà
+    pub fn anchor<Tz: TimeZone>(&mut self, first: DateTime<Utc>, tz: &Tz) {
+        let first = first.with_timezone(tz).naive_local();
+        match self.frequency {
+            Frequency::Monthly => {
+                self.month_day.get_or_insert(first.day());
+            }
+            Frequency::Yearly if self.month_day.is_none() => {
+                self.month_day = Some(first.day());
+                self.month = Some(first.month());
+            }
+            _ => {}
+        }
+    }
+
+    /// Première occurrence strictement après `after`, calculée sur l'heure
+    /// locale de `tz` ; `None` au-delà des dates représentables
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Utc>, tz: &Tz) -> Option<DateTime<Utc>> {
+        let next = self.next_naive(after.with_timezone(tz).naive_local())?;
+        to_utc(tz, next)
+    }
+
+    fn next_naive(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let interval = self.interval.max(1);
+        match self.frequency {
+            Frequency::Daily => after.checked_add_days(Days::new(interval.into())),
+            Frequency::Weekly if self.weekdays.is_empty() => {
+                after.checked_add_days(Days::new(7 * u64::from(interval)))
+            }
+            Frequency::Weekly => {
+                // Les semaines comptent à partir de celle de `after`, qui est
+                // elle-même une occurrence de la série
+                let week_start = |date: NaiveDateTime| {
+                    date.date() - Days::new(date.weekday().num_days_from_monday().into())
+                };
+                let anchor = week_start(after);
+                // Un jour de la liste tombe dans les interval + 1 semaines
+                (1..=7 * u64::from(interval) + 7)
+                    .map_while(|days| after.checked_add_days(Days::new(days)))
+                    .find(|date| {
+                        let weeks = (week_start(*date) - anchor).num_weeks();
+                        weeks % i64::from(interval) == 0 && self.weekdays.contains(&date.weekday())
+                    })
+            }
+            Frequency::Monthly | Frequency::Yearly => {
+                let step = match self.frequency {
+                    Frequency::Monthly => interval,
+                    _ => interval.checked_mul(12)?,
+                };
+                // Premier mois candidat : celui de `after`, ou le mois de la série
+                let first = match self.month {
+                    Some(month) => (month + 12 - after.month()) % 12,
+                    None => 0,
+                };
+                let day = self.month_day.unwrap_or(after.day());
+                let candidate = in_month(after, first, day)?;
+                if candidate > after {
+                    Some(candidate)
+                } else {
+                    in_month(after, first.checked_add(step)?, day)
+                }
+            }
+        }
+    }
+
+    /// Forme lisible, acceptée en retour par `parse`
+    pub fn to_human(&self) -> String {
+        let mut text = match (self.interval, self.frequency) {
+            (1, Frequency::Daily) => "daily".to_string(),
+            (1, Frequency::Weekly) => "weekly".to_string(),
+            (1, Frequency::Monthly) => "monthly".to_string(),
+            (1, Frequency::Yearly) => "yearly".to_string(),
+            (n, frequency) => format!("every {} {}s", n, frequency.unit()),
+        };
+        if !self.weekdays.is_empty() {
+            let days: Vec<String> = self
+                .weekdays
+                .iter()
+                .map(|d| d.to_string().to_lowercase())
+                .collect();
+            text.push_str(&format!(" on {}", days.join(",")));
+        }
+        match (self.month, self.month_day) {
+            (Some(month), Some(day)) => {
+                let name = Month::try_from(month as u8).map_or("", |m| m.name());
+                text.push_str(&format!(" on {} {}", name[..3].to_lowercase(), day));
+            }
+            (None, Some(day)) => text.push_str(&format!(" on day {}", day)),
+            _ => {}
+        }
+        text
+    }
+
+    fn parse_rrule(value: &str) -> Option<Self> {
+        let mut recurrence: Option<Self> = None;
+        let mut interval = 1;
+        let mut weekdays = Vec::new();
+        let mut month_day = None;
+        let mut month = None;
+
+        for part in value.trim().trim_start_matches("RRULE:").split(';') {
+            let (key, val) = part.split_once('=')?;
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    let frequency = match val.trim().to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    };
+                    recurrence = Some(Self::new(frequency));
+                }
+                "INTERVAL" => interval = parse_interval(val)?,
+                "BYDAY" => {
+                    for day in val.split(',') {
+                        let day = day.trim().to_uppercase();
+                        let (weekday, _) = RRULE_DAYS.iter().find(|(_, code)| *code == day)?;
+                        weekdays.push(*weekday);
+                    }
+                }
+                "BYMONTHDAY" => month_day = Some(val.trim().parse().ok()?),
+                "BYMONTH" => month = Some(val.trim().parse().ok()?),
+                // Les autres parties (WKST, UNTIL, COUNT…) ne sont pas gérées
+                _ => return None,
+            }
+        }
+
+        let mut recurrence = recurrence?;
+        recurrence.interval = interval;
+        if !weekdays.is_empty() {
+            if recurrence.frequency != Frequency::Weekly {
+                return None;
+            }
+            recurrence.weekdays = weekdays;
+        }
+        recurrence.set_month_day(month, month_day)?;
+        Some(recurrence)
+    }
+
+    /// Jour (et mois) des occurrences : un seul jour pour une série
+    /// mensuelle, un jour et un mois existants pour une série annuelle
+    fn set_month_day(&mut self, month: Option<u32>, day: Option<u32>) -> Option<()> {
+        let valid = match (self.frequency, month, day) {
+            (_, None, None) => true,
+            (Frequency::Monthly, None, Some(day)) => (1..=31).contains(&day),
+            // 2000 est bissextile : le 29 février est accepté
+            (Frequency::Yearly, Some(month), Some(day)) => NaiveDate::from_ymd_opt(2000, month, day).is_some(),
+            _ => false,
+        };
+        if !valid {
+            return None;
+        }
+        self.month = month;
+        self.month_day = day;
+        Some(())
+    }
+
+    fn parse_human(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        let (schedule, days) = match value.split_once(" on ") {
+            Some((schedule, days)) => (schedule.trim(), Some(days)),
+            None => (value.as_str(), None),
+        };
+
+        let mut recurrence = match schedule {
+            "daily" => Self::new(Frequency::Daily),
+            "weekly" => Self::new(Frequency::Weekly),
+            "monthly" => Self::new(Frequency::Monthly),
+            "yearly" => Self::new(Frequency::Yearly),
+            _ => {
+                let words: Vec<&str> = schedule.split_whitespace().collect();
+                let (interval, unit) = match words.as_slice() {
+                    ["every", unit] => (1, *unit),
+                    ["every", n, unit] => (parse_interval(n)?, *unit),
+                    _ => return None,
+                };
+                let frequency = match unit.trim_end_matches('s') {
+                    "day" => Frequency::Daily,
+                    "week" => Frequency::Weekly,
+                    "month" => Frequency::Monthly,
+                    "year" => Frequency::Yearly,
+                    _ => return None,
+                };
+                Self {
+                    interval,
+                    ..Self::new(frequency)
+                }
+            }
+        };
+
+        match (recurrence.frequency, days) {
+            (_, None) => {}
+            (Frequency::Daily, Some(_)) => return None,
+            (Frequency::Weekly, Some(days)) => {
+                for day in days.split(',') {
+                    let weekday: Weekday = day.trim().parse().ok()?;
+                    if !recurrence.weekdays.contains(&weekday) {
+                        recurrence.weekdays.push(weekday);
+                    }
+                }
+                recurrence.weekdays.sort_by_key(|d| d.num_days_from_monday());
+            }
+            // `on day 31`
+            (Frequency::Monthly, Some(day)) => {
+                let day = day.trim().strip_prefix("day ")?.trim().parse().ok()?;
+                recurrence.set_month_day(None, Some(day))?;
+            }
+            // `on feb 29`
+            (Frequency::Yearly, Some(date)) => {
+                let (month, day) = date.trim().split_once(' ')?;
+                let month = month.parse::<Month>().ok()?.number_from_month();
+                recurrence.set_month_day(Some(month), Some(day.trim().parse().ok()?))?;
+            }
+        }
+        Some(recurrence)
+    }
+}
+
+/// RRULE (`FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`), sans le préfixe `RRULE:`
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.rrule())?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(month) = self.month {
+            write!(f, ";BYMONTH={}", month)?;
+        }
+        if let Some(day) = self.month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+        if !self.weekdays.is_empty() {
+            let days: Vec<&str> = self
+                .weekdays
+                .iter()
+                .filter_map(|d| RRULE_DAYS.iter().find(|(w, _)| w == d).map(|(_, code)| *code))
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Accepte une RRULE ou la forme lisible
+impl FromStr for Recurrence {
+    type Err = TaskFlowError;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        let parsed = if value.contains('=') {
+            Self::parse_rrule(value)
+        } else {
+            Self::parse_human(value)
+        };
+        parsed.ok_or_else(|| invalid(value))
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Priority, Task};
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let cases = [
+            ("daily", "FREQ=DAILY"),
+            ("weekly", "FREQ=WEEKLY"),
+            ("monthly", "FREQ=MONTHLY"),
+            ("yearly", "FREQ=YEARLY"),
+            ("every 3 days", "FREQ=DAILY;INTERVAL=3"),
+            ("every 2 weeks on mon,thu", "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH"),
+            ("monthly on day 31", "FREQ=MONTHLY;BYMONTHDAY=31"),
+            ("every 6 months on day 15", "FREQ=MONTHLY;INTERVAL=6;BYMONTHDAY=15"),
+            ("yearly on feb 29", "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29"),
+        ];
+        for (human, rrule) in cases {
+            let recurrence: Recurrence = human.parse().unwrap();
+            assert_eq!(recurrence.to_string(), rrule);
+            assert_eq!(recurrence.to_human(), human);
+            assert_eq!(rrule.parse::<Recurrence>().unwrap(), recurrence);
+        }
+
+        // Variantes de saisie
+        assert_eq!("Every 1 Week".parse::<Recurrence>().unwrap().to_human(), "weekly");
+        assert_eq!("every day".parse::<Recurrence>().unwrap().to_human(), "daily");
+        assert_eq!(
+            "weekly on thu, mon, thu".parse::<Recurrence>().unwrap().to_string(),
+            "FREQ=WEEKLY;BYDAY=MO,TH"
+        );
+        assert_eq!(
+            "RRULE:freq=weekly;byday=mo".parse::<Recurrence>().unwrap().to_human(),
+            "weekly on mon"
+        );
+        assert_eq!("yearly on february 29".parse::<Recurrence>().unwrap().month, Some(2));
+
+        // Sérialisation JSON sous forme de RRULE
+        let recurrence: Recurrence = "every 2 weeks on mon,thu".parse().unwrap();
+        let json = serde_json::to_string(&recurrence).unwrap();
+        assert_eq!(json, "\"FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH\"");
+        assert_eq!(serde_json::from_str::<Recurrence>(&json).unwrap(), recurrence);
+    }
+
+    #[test]
+    fn test_invalid_recurrences() {
+        for value in [
+            "",
+            "sometimes",
+            "every fortnight",
+            "every -1 days",
+            "every 0 weeks",
+            "daily on mon",
+            "monthly on mon",
+            "weekly on someday",
+            "monthly on day 32",
+            "monthly on day 0",
+            "yearly on feb 30",
+            "yearly on day 3",
+            "FREQ=HOURLY",
+            "INTERVAL=2",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=WEEKLY;COUNT=3",
+            "FREQ=MONTHLY;BYMONTH=2;BYMONTHDAY=3",
+            "FREQ=YEARLY;BYMONTHDAY=3",
+            "FREQ=YEARLY;BYMONTH=13;BYMONTHDAY=1",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+        ] {
+            assert!(value.parse::<Recurrence>().is_err(), "{:?} accepted", value);
+        }
+        assert!(serde_json::from_str::<Recurrence>("\"FREQ=HOURLY\"").is_err());
+    }
+
+    #[test]
+    fn test_weekly_days_with_interval() {
+        let date = |d| Utc.with_ymd_and_hms(2024, 1, d, 9, 0, 0).unwrap();
+        // Lundi 1er janvier 2024, une semaine sur trois, lundi, mercredi et dimanche
+        let recurrence: Recurrence = "every 3 weeks on sun,mon,wed".parse().unwrap();
+        let mut due = date(1);
+        let mut dues = Vec::new();
+        for _ in 0..6 {
+            due = recurrence.next_after(due, &Utc).unwrap();
+            dues.push(due);
+        }
+        let february_12 = Utc.with_ymd_and_hms(2024, 2, 12, 9, 0, 0).unwrap();
+        assert_eq!(dues, vec![date(3), date(7), date(22), date(24), date(28), february_12]);
+
+        // Sans jour listé, le jour de l'échéance est repris
+        let every_two_weeks: Recurrence = "every 2 weeks".parse().unwrap();
+        assert_eq!(every_two_weeks.next_after(date(3), &Utc), Some(date(17)));
+    }
+
+    #[test]
+    fn test_interval_is_bounded() {
+        assert!("every 1000 days".parse::<Recurrence>().is_ok());
+        assert!("every 1001 days".parse::<Recurrence>().is_err());
+        assert!("every 4000000000 days".parse::<Recurrence>().is_err());
+        assert!("FREQ=YEARLY;INTERVAL=400000000".parse::<Recurrence>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=0".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn test_next_after_stops_at_the_end_of_time() {
+        let last = DateTime::<Utc>::MAX_UTC;
+        for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly] {
+            assert_eq!(Recurrence::new(frequency).next_after(last, &Utc), None);
+        }
+        let weekly_on_days: Recurrence = "weekly on mon,thu".parse().unwrap();
+        assert_eq!(weekly_on_days.next_after(last, &Utc), None);
+
+        // Intervalle posé directement, hors saisie
+        let huge = Recurrence {
+            interval: u32::MAX,
+            ..Recurrence::new(Frequency::Yearly)
+        };
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        assert_eq!(huge.next_after(start, &Utc), None);
+        let huge = Recurrence {
+            interval: u32::MAX,
+            ..Recurrence::new(Frequency::Daily)
+        };
+        assert_eq!(huge.next_after(start, &Utc), None);
+    }
+
+    #[test]
+    fn test_month_end_keeps_the_series_day() {
+        let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 10, 0, 0).unwrap();
+
+        let mut monthly: Recurrence = "monthly".parse().unwrap();
+        monthly.anchor(date(2024, 1, 31), &Utc);
+        assert_eq!(monthly.to_string(), "FREQ=MONTHLY;BYMONTHDAY=31");
+        let mut due = date(2024, 1, 31);
+        for expected in [date(2024, 2, 29), date(2024, 3, 31), date(2024, 4, 30), date(2024, 5, 31)] {
+            due = monthly.next_after(due, &Utc).unwrap();
+            assert_eq!(due, expected);
+        }
+
+        let mut yearly: Recurrence = "yearly".parse().unwrap();
+        yearly.anchor(date(2024, 2, 29), &Utc);
+        assert_eq!(yearly.to_human(), "yearly on feb 29");
+        let mut due = date(2024, 2, 29);
+        for expected in [date(2025, 2, 28), date(2026, 2, 28), date(2027, 2, 28), date(2028, 2, 29)] {
+            due = yearly.next_after(due, &Utc).unwrap();
+            assert_eq!(due, expected);
+        }
+
+        // Une échéance hors du jour de la série rejoint la série
+        let every_two_months: Recurrence = "every 2 months on day 15".parse().unwrap();
+        assert_eq!(every_two_months.next_after(date(2024, 1, 10), &Utc), Some(date(2024, 1, 15)));
+        assert_eq!(every_two_months.next_after(date(2024, 1, 15), &Utc), Some(date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn test_days_are_counted_in_the_user_time_zone() {
+        use chrono_tz::America::New_York;
+        use chrono_tz::Europe::Paris;
+        let local = |tz: chrono_tz::Tz, y, m, d, h, min| {
+            tz.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().with_timezone(&Utc)
+        };
+
+        // Lundi 23h59 à New York, déjà mardi en UTC
+        let recurrence: Recurrence = "every week on mon,thu".parse().unwrap();
+        let monday = local(New_York, 2024, 1, 8, 23, 59);
+        assert_eq!(recurrence.next_after(monday, &New_York), Some(local(New_York, 2024, 1, 11, 23, 59)));
+
+        // L'heure locale est conservée à travers le passage à l'heure d'été
+        let weekly: Recurrence = "weekly".parse().unwrap();
+        let before = local(Paris, 2024, 3, 25, 23, 59);
+        assert_eq!(weekly.next_after(before, &Paris), Some(local(Paris, 2024, 4, 1, 23, 59)));
+
+        // 2h30 n'existe pas le 31 mars à Paris : l'occurrence passe à 3h30
+        let daily: Recurrence = "daily".parse().unwrap();
+        let night = local(Paris, 2024, 3, 30, 2, 30);
+        assert_eq!(daily.next_after(night, &Paris), Some(local(Paris, 2024, 3, 31, 3, 30)));
+
+        // Le jour du mois est celui de l'échéance locale
+        let mut monthly: Recurrence = "monthly".parse().unwrap();
+        let end_of_january = local(New_York, 2024, 1, 31, 23, 0);
+        monthly.anchor(end_of_january, &New_York);
+        assert_eq!(monthly.month_day, Some(31));
+        assert_eq!(monthly.next_after(end_of_january, &New_York), Some(local(New_York, 2024, 2, 29, 23, 0)));
+    }
+
+    #[test]
+    fn test_next_occurrence_anchors_the_series() {
+        let mut task = Task::new("Loyer".to_string(), None, Priority::Medium, vec![], Uuid::new_v4());
+        task.due_date = Some(Utc.with_ymd_and_hms(2024, 1, 31, 10, 0, 0).unwrap());
+        task.recurrence = Some("monthly".parse().unwrap());
+        task.complete().unwrap();
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+        let mut next = task.next_occurrence(now, &Utc).unwrap();
+        assert_eq!(next.due_date, Some(Utc.with_ymd_and_hms(2024, 2, 29, 10, 0, 0).unwrap()));
+        next.complete().unwrap();
+        let next = next.next_occurrence(now, &Utc).unwrap();
+        assert_eq!(next.due_date, Some(Utc.with_ymd_and_hms(2024, 3, 31, 10, 0, 0).unwrap()));
+    }
+}
+
+/// Colonne texte contenant la RRULE
+#[cfg(feature = "database")]
+mod database {
+    use super::Recurrence;
+    use sqlx::encode::IsNull;
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+    use sqlx::{Decode, Encode, Postgres, Type};
+
+    impl Type<Postgres> for Recurrence {
+        fn type_info() -> PgTypeInfo {
+            <String as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <String as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl Encode<'_, Postgres> for Recurrence {
+        fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+            <String as Encode<Postgres>>::encode(self.to_string(), buf)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Recurrence {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+        }
+    }
+}
@@ -37,7 +37,7 @@ export const TaskProvider: React.FC<TaskProviderProps> = ({ children }) => {
     
     setIsLoading(true);
     try {
-      const fetchedTasks = await tasksAPI.getTasks();
+      const fetchedTasks = await tasksAPI.getAllTasks();
       setTasks(fetchedTasks);
     } catch (error) {
       console.error('Error fetching tasks:', error);
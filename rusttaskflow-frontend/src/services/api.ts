@@ -1,5 +1,5 @@
 import axios from 'axios';
-import type { Task, CreateTaskRequest, UpdateTaskRequest, LoginRequest, RegisterRequest, AuthResponse } from '../types';
+import type { Task, TaskPage, TaskListParams, CreateTaskRequest, UpdateTaskRequest, LoginRequest, RegisterRequest, AuthResponse } from '../types';
 
 // Configuration de l'URL de base selon l'environnement
 const API_BASE_URL = import.meta.env.VITE_API_BASE_URL || 'http://localhost:3000';
@@ -39,8 +39,25 @@ export const authAPI = {
 
 // Tasks API
 export const tasksAPI = {
-  getTasks: async (): Promise<Task[]> => {
-    const response = await api.get('/api/v1/tasks');
+  getTasks: async (params: TaskListParams = {}): Promise<TaskPage> => {
+    const response = await api.get('/api/v1/tasks', { params });
+    return response.data;
+  },
+
+  // Follows next_cursor until the last page
+  getAllTasks: async (params: TaskListParams = {}): Promise<Task[]> => {
+    const tasks: Task[] = [];
+    let cursor: string | undefined;
+    do {
+      const page = await tasksAPI.getTasks({ ...params, limit: 200, cursor });
+      tasks.push(...page.tasks);
+      cursor = page.next_cursor ?? undefined;
+    } while (cursor);
+    return tasks;
+  },
+
+  getSubtasks: async (id: string): Promise<Task[]> => {
+    const response = await api.get(`/api/v1/tasks/${id}/subtasks`);
     return response.data;
   },
 
@@ -57,6 +74,16 @@ export const tasksAPI = {
   deleteTask: async (id: string): Promise<void> => {
     await api.delete(`/api/v1/tasks/${id}`);
   },
+
+  addDependency: async (id: string, dependsOn: string): Promise<Task> => {
+    const response = await api.post(`/api/v1/tasks/${id}/dependencies`, { depends_on: dependsOn });
+    return response.data;
+  },
+
+  removeDependency: async (id: string, dependsOn: string): Promise<Task> => {
+    const response = await api.delete(`/api/v1/tasks/${id}/dependencies/${dependsOn}`);
+    return response.data;
+  },
 };
 
 export default api;
\ No newline at end of file
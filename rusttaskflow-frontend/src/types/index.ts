@@ -19,6 +19,13 @@ export interface Task {
   updated_at: string;
   started_at?: string;
   completed_at?: string;
+  cancelled_at?: string;
+  reopened_at?: string;
+  parent_id?: string;
+  depends_on: string[];
+  recurrence?: string; // RRULE, e.g. FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH
+  series_id?: string;
+  project_id?: string; // absent for a personal task
 }
 
 export type TaskStatus = 'Todo' | 'InProgress' | 'Completed' | 'Cancelled';
@@ -32,6 +39,10 @@ export interface CreateTaskRequest {
   tags: string[];
   due_date?: string;
   assigned_to?: string;
+  parent_id?: string;
+  depends_on?: string[];
+  recurrence?: string; // RRULE or "daily", "weekly", "every 2 weeks on mon,thu"...
+  project_id?: string; // defaults to the parent task's project
 }
 
 export interface UpdateTaskRequest {
@@ -44,6 +55,29 @@ export interface UpdateTaskRequest {
   assigned_to?: string;
 }
 
+export type TaskSort = 'created_at' | 'due_date' | 'priority' | 'updated_at';
+
+export interface TaskListParams {
+  status?: TaskStatus;
+  priority?: TaskPriority;
+  assigned_to?: string;
+  created_by?: string;
+  tags?: string; // comma-separated, a task must carry all of them
+  due_before?: string;
+  due_after?: string;
+  project_id?: string;
+  limit?: number; // 50 by default, 200 at most
+  cursor?: string; // next_cursor of the previous page
+  sort?: TaskSort;
+  direction?: 'asc' | 'desc';
+}
+
+export interface TaskPage {
+  tasks: Task[];
+  next_cursor: string | null; // null on the last page
+  total: number;
+}
+
 export interface LoginRequest {
   email: string;
   password: string;
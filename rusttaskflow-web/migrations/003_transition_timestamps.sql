@@ -0,0 +1,6 @@
+-- Record when a task was cancelled and when it last went back to Todo
+-- (reopen from Completed/Cancelled, or reset from InProgress)
+
+ALTER TABLE tasks
+    ADD COLUMN cancelled_at TIMESTAMPTZ,
+    ADD COLUMN reopened_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- Secret token of the read-only .ics feed (NULL = feed disabled)
+
+ALTER TABLE users
+    ADD COLUMN calendar_token VARCHAR UNIQUE;
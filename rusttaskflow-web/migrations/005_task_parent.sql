@@ -0,0 +1,6 @@
+-- Subtasks: a task may belong to a parent task
+
+ALTER TABLE tasks
+    ADD COLUMN parent_id VARCHAR REFERENCES tasks(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tasks_parent_id ON tasks(parent_id);
@@ -0,0 +1,11 @@
+-- "task_id depends on depends_on_id": depends_on_id must be completed first
+
+CREATE TABLE task_dependencies (
+    task_id VARCHAR NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    depends_on_id VARCHAR NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (task_id, depends_on_id),
+    CHECK (task_id <> depends_on_id)
+);
+
+CREATE INDEX idx_task_dependencies_depends_on_id ON task_dependencies(depends_on_id);
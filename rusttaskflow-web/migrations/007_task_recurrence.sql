@@ -0,0 +1,8 @@
+-- Recurring tasks: schedule as an RRULE (FREQ=WEEKLY;BYDAY=MO) and the
+-- first occurrence of the series
+
+ALTER TABLE tasks
+    ADD COLUMN recurrence VARCHAR,
+    ADD COLUMN series_id VARCHAR;
+
+CREATE INDEX idx_tasks_series_id ON tasks(series_id);
@@ -0,0 +1,53 @@
+-- Native column types: UUID ids, enums for status and priority, TEXT[] tags.
+-- status and priority used to hold JSON strings ('"Todo"') and tags a JSON
+-- array stored as text.
+
+CREATE TYPE task_status AS ENUM ('Todo', 'InProgress', 'Completed', 'Cancelled');
+-- Declared from lowest to highest, so ORDER BY priority ranks tasks
+CREATE TYPE task_priority AS ENUM ('Low', 'Medium', 'High', 'Critical');
+
+-- ALTER COLUMN ... USING cannot run a subquery, so unpack tags with a helper
+CREATE FUNCTION rtf_json_text_array(value TEXT) RETURNS TEXT[] AS $$
+    SELECT ARRAY(SELECT jsonb_array_elements_text(value::jsonb))
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- Foreign keys are recreated once both sides are UUID
+ALTER TABLE tasks
+    DROP CONSTRAINT tasks_assigned_to_fkey,
+    DROP CONSTRAINT tasks_created_by_fkey,
+    DROP CONSTRAINT tasks_parent_id_fkey;
+
+ALTER TABLE task_dependencies
+    DROP CONSTRAINT task_dependencies_task_id_fkey,
+    DROP CONSTRAINT task_dependencies_depends_on_id_fkey;
+
+ALTER TABLE users
+    ALTER COLUMN id TYPE UUID USING id::uuid;
+
+ALTER TABLE tasks
+    ALTER COLUMN id TYPE UUID USING id::uuid,
+    ALTER COLUMN assigned_to TYPE UUID USING assigned_to::uuid,
+    ALTER COLUMN created_by TYPE UUID USING created_by::uuid,
+    ALTER COLUMN parent_id TYPE UUID USING parent_id::uuid,
+    ALTER COLUMN series_id TYPE UUID USING series_id::uuid,
+    ALTER COLUMN status TYPE task_status USING (status::jsonb #>> '{}')::task_status,
+    ALTER COLUMN priority TYPE task_priority USING (priority::jsonb #>> '{}')::task_priority,
+    ALTER COLUMN tags TYPE TEXT[] USING rtf_json_text_array(tags);
+
+ALTER TABLE task_dependencies
+    ALTER COLUMN task_id TYPE UUID USING task_id::uuid,
+    ALTER COLUMN depends_on_id TYPE UUID USING depends_on_id::uuid;
+
+DROP FUNCTION rtf_json_text_array(TEXT);
+
+ALTER TABLE tasks
+    ADD CONSTRAINT tasks_assigned_to_fkey FOREIGN KEY (assigned_to) REFERENCES users(id),
+    ADD CONSTRAINT tasks_created_by_fkey FOREIGN KEY (created_by) REFERENCES users(id),
+    ADD CONSTRAINT tasks_parent_id_fkey FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE SET NULL;
+
+ALTER TABLE task_dependencies
+    ADD CONSTRAINT task_dependencies_task_id_fkey FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    ADD CONSTRAINT task_dependencies_depends_on_id_fkey FOREIGN KEY (depends_on_id) REFERENCES tasks(id) ON DELETE CASCADE;
+
+-- Tag containment (tags @> ARRAY['work'])
+CREATE INDEX idx_tasks_tags ON tasks USING GIN (tags);
@@ -1,8 +1,16 @@
-use rusttaskflow_core::{Task, User, TaskFilter, Result, TaskFlowError};
-use sqlx::{PgPool, Row};
+use rusttaskflow_core::{Project, ProjectMember, ProjectRole, Task, User, TaskFilter, Result, TaskFlowError};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use std::env;
 use uuid::Uuid;
 
+use crate::pagination::{Cursor, PageRequest, SortDirection, SortKey, TaskPage, TaskSort};
+
+/// Task columns plus the ids of the tasks each one depends on
+const SELECT_TASKS: &str = "SELECT tasks.*, ARRAY(\
+        SELECT depends_on_id FROM task_dependencies WHERE task_dependencies.task_id = tasks.id \
+        ORDER BY task_dependencies.created_at, depends_on_id\
+    ) AS depends_on FROM tasks";
+
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
@@ -30,7 +38,7 @@ impl Database {
     // User operations
     pub async fn create_user(&self, user: &User) -> Result<()> {
         sqlx::query("INSERT INTO users (id, username, email, password_hash, created_at, updated_at, is_active) VALUES ($1, $2, $3, $4, $5, $6, $7)")
-            .bind(user.id.to_string())
+            .bind(user.id)
             .bind(&user.username)
             .bind(&user.email)
             .bind(&user.password_hash)
@@ -45,126 +53,274 @@ impl Database {
     }
 
     pub async fn get_user_by_id(&self, id: Uuid) -> Result<User> {
-        let row = sqlx::query("SELECT * FROM users WHERE id = $1")
-            .bind(id.to_string())
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(id)
             .fetch_optional(&self.pool)
             .await
             .map_err(TaskFlowError::Database)?;
 
-        match row {
-            Some(row) => Ok(User {
-                id: Uuid::parse_str(row.get("id")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                username: row.get("username"),
-                email: row.get("email"),
-                password_hash: row.get("password_hash"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                is_active: row.get("is_active"),
-            }),
+        match user {
+            Some(user) => Ok(user),
             None => Err(TaskFlowError::UserNotFound { id: id.to_string() }),
         }
     }
 
     pub async fn get_user_by_email(&self, email: &str) -> Result<User> {
-        let row = sqlx::query("SELECT * FROM users WHERE email = $1")
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
             .bind(email)
             .fetch_optional(&self.pool)
             .await
             .map_err(TaskFlowError::Database)?;
 
-        match row {
-            Some(row) => Ok(User {
-                id: Uuid::parse_str(row.get("id")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                username: row.get("username"),
-                email: row.get("email"),
-                password_hash: row.get("password_hash"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                is_active: row.get("is_active"),
-            }),
+        match user {
+            Some(user) => Ok(user),
             None => Err(TaskFlowError::UserNotFound { id: email.to_string() }),
         }
     }
 
     pub async fn list_users(&self) -> Result<Vec<User>> {
-        let rows = sqlx::query("SELECT * FROM users WHERE is_active = true ORDER BY username")
+        let users = sqlx::query_as::<_, User>("SELECT * FROM users WHERE is_active = true ORDER BY username")
             .fetch_all(&self.pool)
             .await
             .map_err(TaskFlowError::Database)?;
 
-        let users = rows
-            .into_iter()
-            .map(|row| -> Result<User> {
-                Ok(User {
-                    id: Uuid::parse_str(row.get("id")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                    username: row.get("username"),
-                    email: row.get("email"),
-                    password_hash: row.get("password_hash"),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                    is_active: row.get("is_active"),
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-
         Ok(users)
     }
 
+    /// Sets (or clears, with `None`) the secret token of the user's calendar feed
+    pub async fn set_calendar_token(&self, user_id: Uuid, token: Option<&str>) -> Result<()> {
+        let result = sqlx::query("UPDATE users SET calendar_token = $1 WHERE id = $2")
+            .bind(token)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(TaskFlowError::UserNotFound { id: user_id.to_string() });
+        }
+
+        Ok(())
+    }
+
+    /// Active user owning a calendar feed token
+    pub async fn get_user_by_calendar_token(&self, token: &str) -> Result<User> {
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE calendar_token = $1 AND is_active = true")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        match user {
+            Some(user) => Ok(user),
+            // The token itself is a secret: never echo it back
+            None => Err(TaskFlowError::UserNotFound { id: "calendar feed".to_string() }),
+        }
+    }
+
+    // Project operations
+    /// Creates a project with its creator as owner
+    pub async fn create_project(&self, project: &Project) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(TaskFlowError::Database)?;
+
+        sqlx::query("INSERT INTO projects (id, name, description, created_by, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(project.id)
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(project.created_by)
+            .bind(project.created_at)
+            .bind(project.updated_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        sqlx::query("INSERT INTO project_members (project_id, user_id, role) VALUES ($1, $2, $3)")
+            .bind(project.id)
+            .bind(project.created_by)
+            .bind(ProjectRole::Owner)
+            .execute(&mut *tx)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        tx.commit().await.map_err(TaskFlowError::Database)?;
+
+        Ok(())
+    }
+
+    pub async fn get_project_by_id(&self, id: Uuid) -> Result<Project> {
+        sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?
+            .ok_or_else(|| TaskFlowError::ProjectNotFound { id: id.to_string() })
+    }
+
+    /// Projects the user is a member of, by name
+    pub async fn list_user_projects(&self, user_id: Uuid) -> Result<Vec<Project>> {
+        sqlx::query_as::<_, Project>(
+            "SELECT projects.* FROM projects JOIN project_members ON project_members.project_id = projects.id \
+             WHERE project_members.user_id = $1 ORDER BY projects.name",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(TaskFlowError::Database)
+    }
+
+    pub async fn update_project(&self, project: &Project) -> Result<()> {
+        sqlx::query("UPDATE projects SET name = $2, description = $3, updated_at = $4 WHERE id = $1")
+            .bind(project.id)
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(project.updated_at)
+            .execute(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        Ok(())
+    }
+
+    /// Deletes a project; its tasks become personal tasks again
+    pub async fn delete_project(&self, id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM projects WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(TaskFlowError::ProjectNotFound { id: id.to_string() });
+        }
+
+        Ok(())
+    }
+
+    /// Role of a user in a project, `None` if they are not a member
+    pub async fn get_project_role(&self, project_id: Uuid, user_id: Uuid) -> Result<Option<ProjectRole>> {
+        sqlx::query_scalar("SELECT role FROM project_members WHERE project_id = $1 AND user_id = $2")
+            .bind(project_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)
+    }
+
+    /// Members of a project, owners first
+    pub async fn list_project_members(&self, project_id: Uuid) -> Result<Vec<ProjectMember>> {
+        sqlx::query_as::<_, ProjectMember>(
+            "SELECT users.id AS user_id, users.username, users.email, project_members.role, project_members.joined_at \
+             FROM project_members JOIN users ON users.id = project_members.user_id \
+             WHERE project_members.project_id = $1 ORDER BY project_members.role, users.username",
+        )
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(TaskFlowError::Database)
+    }
+
+    /// Adds a member, or changes the role of an existing one
+    pub async fn set_project_member(&self, project_id: Uuid, user_id: Uuid, role: ProjectRole) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO project_members (project_id, user_id, role) VALUES ($1, $2, $3) \
+             ON CONFLICT (project_id, user_id) DO UPDATE SET role = EXCLUDED.role",
+        )
+        .bind(project_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(&self.pool)
+        .await
+        .map_err(TaskFlowError::Database)?;
+
+        Ok(())
+    }
+
+    pub async fn remove_project_member(&self, project_id: Uuid, user_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM project_members WHERE project_id = $1 AND user_id = $2")
+            .bind(project_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(TaskFlowError::UserNotFound { id: user_id.to_string() });
+        }
+
+        Ok(())
+    }
+
+    pub async fn count_project_owners(&self, project_id: Uuid) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM project_members WHERE project_id = $1 AND role = $2")
+            .bind(project_id)
+            .bind(ProjectRole::Owner)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)
+    }
+
     // Task operations
     pub async fn create_task(&self, task: &Task) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(TaskFlowError::Database)?;
+
         sqlx::query(
             r#"
             INSERT INTO tasks (
                 id, title, description, status, priority, tags, 
                 created_at, updated_at, started_at, completed_at, 
-                due_date, assigned_to, created_by
+                cancelled_at, reopened_at, due_date, assigned_to, created_by, parent_id,
+                recurrence, series_id, project_id
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
             "#
         )
-        .bind(task.id.to_string())
+        .bind(task.id)
         .bind(&task.title)
         .bind(&task.description)
-        .bind(serde_json::to_string(&task.status).map_err(TaskFlowError::Serialization)?)
-        .bind(serde_json::to_string(&task.priority).map_err(TaskFlowError::Serialization)?)
-        .bind(serde_json::to_string(&task.tags).map_err(TaskFlowError::Serialization)?)
+        .bind(&task.status)
+        .bind(&task.priority)
+        .bind(&task.tags)
         .bind(task.created_at)
         .bind(task.updated_at)
         .bind(task.started_at)
         .bind(task.completed_at)
+        .bind(task.cancelled_at)
+        .bind(task.reopened_at)
         .bind(task.due_date)
-        .bind(task.assigned_to.map(|id| id.to_string()))
-        .bind(task.created_by.to_string())
-        .execute(&self.pool)
+        .bind(task.assigned_to)
+        .bind(task.created_by)
+        .bind(task.parent_id)
+        .bind(&task.recurrence)
+        .bind(task.series_id)
+        .bind(task.project_id)
+        .execute(&mut *tx)
         .await
         .map_err(TaskFlowError::Database)?;
+
+        // A new task has no dependents yet, so its dependencies cannot form a cycle
+        for depends_on in &task.depends_on {
+            sqlx::query("INSERT INTO task_dependencies (task_id, depends_on_id) VALUES ($1, $2)")
+                .bind(task.id)
+                .bind(depends_on)
+                .execute(&mut *tx)
+                .await
+                .map_err(TaskFlowError::Database)?;
+        }
+
+        tx.commit().await.map_err(TaskFlowError::Database)?;
         
         Ok(())
     }
 
     pub async fn get_task_by_id(&self, id: Uuid) -> Result<Task> {
-        let row = sqlx::query("SELECT * FROM tasks WHERE id = $1")
-            .bind(id.to_string())
+        let task = sqlx::query_as::<_, Task>(&format!("{} WHERE id = $1", SELECT_TASKS))
+            .bind(id)
             .fetch_optional(&self.pool)
             .await
             .map_err(TaskFlowError::Database)?;
 
-        match row {
-            Some(row) => Ok(Task {
-                id: Uuid::parse_str(row.get("id")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                title: row.get("title"),
-                description: row.get("description"),
-                status: serde_json::from_str(&row.get::<String, _>("status")).map_err(TaskFlowError::Serialization)?,
-                priority: serde_json::from_str(&row.get::<String, _>("priority")).map_err(TaskFlowError::Serialization)?,
-                tags: serde_json::from_str(&row.get::<String, _>("tags")).map_err(TaskFlowError::Serialization)?,
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                started_at: row.get("started_at"),
-                completed_at: row.get("completed_at"),
-                due_date: row.get("due_date"),
-                assigned_to: row.get::<Option<String>, _>("assigned_to").map(|s| Uuid::parse_str(&s).ok()).flatten(),
-                created_by: Uuid::parse_str(row.get("created_by")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-            }),
+        match task {
+            Some(task) => Ok(task),
             None => Err(TaskFlowError::TaskNotFound { id: id.to_string() }),
         }
     }
@@ -175,21 +331,28 @@ impl Database {
             UPDATE tasks SET 
                 title = $2, description = $3, status = $4, priority = $5, 
                 tags = $6, updated_at = $7, started_at = $8, completed_at = $9,
-                due_date = $10, assigned_to = $11
+                cancelled_at = $10, reopened_at = $11, due_date = $12, assigned_to = $13,
+                parent_id = $14, recurrence = $15, series_id = $16, project_id = $17
             WHERE id = $1
             "#
         )
-        .bind(task.id.to_string())
+        .bind(task.id)
         .bind(&task.title)
         .bind(&task.description)
-        .bind(serde_json::to_string(&task.status).map_err(TaskFlowError::Serialization)?)
-        .bind(serde_json::to_string(&task.priority).map_err(TaskFlowError::Serialization)?)
-        .bind(serde_json::to_string(&task.tags).map_err(TaskFlowError::Serialization)?)
+        .bind(&task.status)
+        .bind(&task.priority)
+        .bind(&task.tags)
         .bind(task.updated_at)
         .bind(task.started_at)
         .bind(task.completed_at)
+        .bind(task.cancelled_at)
+        .bind(task.reopened_at)
         .bind(task.due_date)
-        .bind(task.assigned_to.map(|id| id.to_string()))
+        .bind(task.assigned_to)
+        .bind(task.parent_id)
+        .bind(&task.recurrence)
+        .bind(task.series_id)
+        .bind(task.project_id)
         .execute(&self.pool)
         .await
         .map_err(TaskFlowError::Database)?;
@@ -197,13 +360,24 @@ impl Database {
         Ok(())
     }
 
+    /// Deletes a task; its subtasks move up to the deleted task's parent
     pub async fn delete_task(&self, id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(TaskFlowError::Database)?;
+
+        sqlx::query("UPDATE tasks SET parent_id = (SELECT parent_id FROM tasks WHERE id = $1) WHERE parent_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
         let result = sqlx::query("DELETE FROM tasks WHERE id = $1")
-            .bind(id.to_string())
-            .execute(&self.pool)
+            .bind(id)
+            .execute(&mut *tx)
             .await
             .map_err(TaskFlowError::Database)?;
 
+        tx.commit().await.map_err(TaskFlowError::Database)?;
+
         if result.rows_affected() == 0 {
             return Err(TaskFlowError::TaskNotFound { id: id.to_string() });
         }
@@ -211,61 +385,518 @@ impl Database {
         Ok(())
     }
 
-    pub async fn list_tasks(&self, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
-        let mut query = "SELECT * FROM tasks WHERE 1=1".to_string();
-        let mut bind_count = 0;
+    /// One page of the tasks the user can see (see `push_task_filter`), narrowed by `filter`.
+    /// `due_before` is inclusive, `due_after` strict; both skip tasks without a due date.
+    pub async fn list_tasks(&self, user_id: Uuid, filter: &TaskFilter, page: &PageRequest) -> Result<TaskPage> {
+        let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM tasks");
+        push_task_filter(&mut count, user_id, filter)?;
+        let total: i64 = count
+            .build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
 
-        if let Some(filter) = filter {
-            if filter.status.is_some() {
-                bind_count += 1;
-                query.push_str(&format!(" AND status = ${}", bind_count));
-            }
-            
-            if filter.priority.is_some() {
-                bind_count += 1;
-                query.push_str(&format!(" AND priority = ${}", bind_count));
-            }
-            
-            if filter.assigned_to.is_some() {
-                bind_count += 1;
-                query.push_str(&format!(" AND assigned_to = ${}", bind_count));
-            }
-            
-            if filter.created_by.is_some() {
-                bind_count += 1;
-                query.push_str(&format!(" AND created_by = ${}", bind_count));
-            }
+        let key = sort_key_sql(page.sort, page.direction);
+        let mut query = QueryBuilder::<Postgres>::new(SELECT_TASKS);
+        push_task_filter(&mut query, user_id, filter)?;
+
+        if let Some(cursor) = &page.after {
+            query.push(format!(" AND ({}, id) {} (", key, page.direction.after_sql()));
+            match &cursor.key {
+                SortKey::Priority(priority) => query.push_bind(priority.clone()),
+                SortKey::Time(time) => query
+                    .push("COALESCE(")
+                    .push_bind(*time)
+                    .push(format!(", {})", undated_sql(page.direction))),
+            };
+            query.push(", ").push_bind(cursor.id).push(")");
         }
 
-        query.push_str(" ORDER BY created_at DESC");
+        let direction = page.direction.sql();
+        query
+            .push(format!(" ORDER BY {} {}, id {} LIMIT ", key, direction, direction))
+            // One extra row tells whether another page follows
+            .push_bind(page.limit + 1);
+
+        let mut tasks = query
+            .build_query_as::<Task>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        let next_cursor = if tasks.len() as i64 > page.limit {
+            tasks.truncate(page.limit as usize);
+            tasks
+                .last()
+                .map(|task| Cursor::after(task, page.sort, page.direction).encode())
+        } else {
+            None
+        };
+
+        Ok(TaskPage { tasks, next_cursor, total })
+    }
+
+    /// Tasks created by or assigned to a user, for the calendar feed; a
+    /// project task only while the user is still a member of the project
+    pub async fn list_user_tasks(&self, user_id: Uuid) -> Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(&format!(
+            "{} WHERE (created_by = $1 OR assigned_to = $1) \
+             AND (project_id IS NULL OR project_id IN (SELECT project_id FROM project_members WHERE user_id = $1)) \
+             ORDER BY created_at",
+            SELECT_TASKS
+        ))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)
+    }
+
+    /// Direct subtasks of a task that the user can see, oldest first
+    ///
+    /// A subtask may belong to another project than its parent, so being
+    /// allowed to read the parent says nothing about its children.
+    pub async fn list_subtasks(&self, user_id: Uuid, parent_id: Uuid) -> Result<Vec<Task>> {
+        let mut query = QueryBuilder::<Postgres>::new(SELECT_TASKS);
+        push_task_filter(&mut query, user_id, &TaskFilter::default())?;
+        query.push(" AND parent_id = ").push_bind(parent_id).push(" ORDER BY created_at");
+        query
+            .build_query_as::<Task>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskFlowError::Database)
+    }
 
-        // Pour simplifier, on utilise une requête basique pour l'instant
-        let rows = sqlx::query("SELECT * FROM tasks ORDER BY created_at DESC")
+    /// Every task of a recurring series, oldest first
+    pub async fn list_series_tasks(&self, series_id: Uuid) -> Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(&format!("{} WHERE series_id = $1 OR id = $1 ORDER BY created_at", SELECT_TASKS))
+            .bind(series_id)
             .fetch_all(&self.pool)
             .await
+            .map_err(TaskFlowError::Database)
+    }
+
+    /// Records that `task_id` depends on `depends_on_id`, unless that would
+    /// close a cycle (`depends_on_id` already depends on `task_id`, directly or not)
+    pub async fn add_dependency(&self, task_id: Uuid, depends_on_id: Uuid) -> Result<()> {
+        if task_id == depends_on_id {
+            return Err(TaskFlowError::Validation {
+                message: "A task cannot depend on itself".to_string(),
+            });
+        }
+
+        let mut tx = self.pool.begin().await.map_err(TaskFlowError::Database)?;
+
+        // Serialize concurrent dependency changes so two inserts cannot form a cycle together
+        sqlx::query("LOCK TABLE task_dependencies IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        let creates_cycle: bool = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE chain(id) AS (
+                SELECT depends_on_id FROM task_dependencies WHERE task_id = $1
+                UNION
+                SELECT d.depends_on_id FROM task_dependencies d JOIN chain ON d.task_id = chain.id
+            )
+            SELECT EXISTS (SELECT 1 FROM chain WHERE id = $2)
+            "#,
+        )
+        .bind(depends_on_id)
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(TaskFlowError::Database)?;
+
+        if creates_cycle {
+            return Err(TaskFlowError::Validation {
+                message: "This dependency would create a cycle".to_string(),
+            });
+        }
+
+        sqlx::query("INSERT INTO task_dependencies (task_id, depends_on_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(task_id)
+            .bind(depends_on_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(TaskFlowError::Database)?;
+
+        tx.commit().await.map_err(TaskFlowError::Database)?;
+
+        Ok(())
+    }
+
+    pub async fn remove_dependency(&self, task_id: Uuid, depends_on_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_id = $2")
+            .bind(task_id)
+            .bind(depends_on_id)
+            .execute(&self.pool)
+            .await
             .map_err(TaskFlowError::Database)?;
 
-        let tasks = rows
+        if result.rows_affected() == 0 {
+            return Err(TaskFlowError::TaskNotFound { id: depends_on_id.to_string() });
+        }
+
+        Ok(())
+    }
+}
+
+/// Restricts a task query to the tasks the user can see, matching `filter`:
+/// personal tasks they created or are assigned to, and the tasks of their projects
+fn push_task_filter(query: &mut QueryBuilder<'_, Postgres>, user_id: Uuid, filter: &TaskFilter) -> Result<()> {
+    query
+        .push(" WHERE ((project_id IS NULL AND (created_by = ")
+        .push_bind(user_id)
+        .push(" OR assigned_to = ")
+        .push_bind(user_id)
+        .push(")) OR project_id IN (SELECT project_id FROM project_members WHERE user_id = ")
+        .push_bind(user_id)
+        .push("))");
+
+    if let Some(project_id) = filter.project_id {
+        query.push(" AND project_id = ").push_bind(project_id);
+    }
+
+    if let Some(status) = &filter.status {
+        query.push(" AND status = ").push_bind(status.clone());
+    }
+
+    if let Some(priority) = &filter.priority {
+        query.push(" AND priority = ").push_bind(priority.clone());
+    }
+
+    if let Some(assigned_to) = filter.assigned_to {
+        query.push(" AND assigned_to = ").push_bind(assigned_to);
+    }
+
+    if let Some(created_by) = filter.created_by {
+        query.push(" AND created_by = ").push_bind(created_by);
+    }
+
+    // The task must carry every requested tag
+    if let Some(tags) = &filter.tags {
+        query.push(" AND tags @> ").push_bind(tags.clone());
+    }
+
+    if let Some(due_before) = filter.due_before {
+        query.push(" AND due_date <= ").push_bind(due_before);
+    }
+
+    if let Some(due_after) = filter.due_after {
+        query.push(" AND due_date > ").push_bind(due_after);
+    }
+
+    Ok(())
+}
+
+/// Stand-in for a missing due date, so undated tasks come last in both directions
+fn undated_sql(direction: SortDirection) -> &'static str {
+    match direction {
+        SortDirection::Asc => "'infinity'::timestamptz",
+        SortDirection::Desc => "'-infinity'::timestamptz",
+    }
+}
+
+/// SQL expression of a sort key, matching `TaskSort::key`
+fn sort_key_sql(sort: TaskSort, direction: SortDirection) -> String {
+    match sort {
+        TaskSort::CreatedAt => "created_at".to_string(),
+        TaskSort::DueDate => format!("COALESCE(due_date, {})", undated_sql(direction)),
+        // The task_priority enum is declared from Low to Critical
+        TaskSort::Priority => "priority".to_string(),
+        TaskSort::UpdatedAt => "updated_at".to_string(),
+    }
+}
+
+/// These tests need a PostgreSQL database: they run against `DATABASE_URL`
+/// and are skipped when it is not set. Each test creates its own users, so
+/// they can share a database with other runs.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use rusttaskflow_core::{Priority, TaskStatus};
+    use crate::pagination::{PageRequest, SortDirection, TaskSort};
+
+    async fn test_database() -> Option<Database> {
+        if env::var("DATABASE_URL").is_err() {
+            eprintln!("DATABASE_URL not set, skipping PostgreSQL test");
+            return None;
+        }
+        let database = Database::new().await.expect("connect to DATABASE_URL");
+        database.migrate().await.expect("run migrations");
+        Some(database)
+    }
+
+    async fn create_user(database: &Database) -> User {
+        let name = Uuid::new_v4().simple().to_string();
+        let user = User::new(name.clone(), format!("{}@example.com", name), "hash".to_string());
+        database.create_user(&user).await.unwrap();
+        user
+    }
+
+    async fn create_task(database: &Database, title: &str, created_by: &User, tags: &[&str]) -> Task {
+        let tags = tags.iter().map(|t| t.to_string()).collect();
+        let task = Task::new(title.to_string(), None, Priority::Medium, tags, created_by.id);
+        database.create_task(&task).await.unwrap();
+        task
+    }
+
+    async fn titles(database: &Database, user: &User, filter: &TaskFilter) -> Vec<String> {
+        let mut titles: Vec<String> = database
+            .list_tasks(user.id, filter, &PageRequest::default())
+            .await
+            .unwrap()
+            .tasks
             .into_iter()
-            .map(|row| -> Result<Task> {
-                Ok(Task {
-                    id: Uuid::parse_str(row.get("id")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                    title: row.get("title"),
-                    description: row.get("description"),
-                    status: serde_json::from_str(&row.get::<String, _>("status")).map_err(TaskFlowError::Serialization)?,
-                    priority: serde_json::from_str(&row.get::<String, _>("priority")).map_err(TaskFlowError::Serialization)?,
-                    tags: serde_json::from_str(&row.get::<String, _>("tags")).map_err(TaskFlowError::Serialization)?,
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                    started_at: row.get("started_at"),
-                    completed_at: row.get("completed_at"),
-                    due_date: row.get("due_date"),
-                    assigned_to: row.get::<Option<String>, _>("assigned_to").map(|s| Uuid::parse_str(&s).ok()).flatten(),
-                    created_by: Uuid::parse_str(row.get("created_by")).map_err(|e| TaskFlowError::Internal(anyhow::anyhow!("Invalid UUID: {}", e)))?,
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(tasks)
-    }
-}
\ No newline at end of file
+            .map(|t| t.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn test_list_tasks_only_returns_the_users_tasks() {
+        let Some(database) = test_database().await else { return };
+        let alice = create_user(&database).await;
+        let bob = create_user(&database).await;
+
+        create_task(&database, "alice own", &alice, &[]).await;
+        create_task(&database, "bob own", &bob, &[]).await;
+        let mut shared = create_task(&database, "bob for alice", &bob, &[]).await;
+        shared.assigned_to = Some(alice.id);
+        database.update_task(&shared).await.unwrap();
+
+        let all = TaskFilter::default();
+        assert_eq!(titles(&database, &alice, &all).await, ["alice own", "bob for alice"]);
+        assert_eq!(titles(&database, &bob, &all).await, ["bob for alice", "bob own"]);
+
+        // Filtering on someone else cannot widen the scope
+        let by_bob = TaskFilter { created_by: Some(bob.id), ..Default::default() };
+        assert_eq!(titles(&database, &alice, &by_bob).await, ["bob for alice"]);
+        let to_bob = TaskFilter { assigned_to: Some(bob.id), ..Default::default() };
+        assert!(titles(&database, &alice, &to_bob).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_tasks_applies_the_filter() {
+        let Some(database) = test_database().await else { return };
+        let user = create_user(&database).await;
+        let now = Utc::now();
+
+        let mut urgent = create_task(&database, "urgent", &user, &["work", "urgent"]).await;
+        urgent.priority = Priority::Critical;
+        urgent.due_date = Some(now + Duration::days(1));
+        database.update_task(&urgent).await.unwrap();
+        let mut later = create_task(&database, "later", &user, &["work"]).await;
+        later.due_date = Some(now + Duration::days(10));
+        later.status = TaskStatus::InProgress;
+        database.update_task(&later).await.unwrap();
+        create_task(&database, "someday", &user, &["home"]).await;
+
+        let status = TaskFilter { status: Some(TaskStatus::InProgress), ..Default::default() };
+        assert_eq!(titles(&database, &user, &status).await, ["later"]);
+        let priority = TaskFilter { priority: Some(Priority::Critical), ..Default::default() };
+        assert_eq!(titles(&database, &user, &priority).await, ["urgent"]);
+
+        let work = TaskFilter { tags: Some(vec!["work".to_string()]), ..Default::default() };
+        assert_eq!(titles(&database, &user, &work).await, ["later", "urgent"]);
+        let both = TaskFilter {
+            tags: Some(vec!["urgent".to_string(), "work".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(titles(&database, &user, &both).await, ["urgent"]);
+
+        let before = TaskFilter { due_before: Some(now + Duration::days(2)), ..Default::default() };
+        assert_eq!(titles(&database, &user, &before).await, ["urgent"]);
+        let after = TaskFilter { due_after: Some(now + Duration::days(2)), ..Default::default() };
+        assert_eq!(titles(&database, &user, &after).await, ["later"]);
+        let inclusive = TaskFilter { due_before: urgent.due_date, ..Default::default() };
+        assert_eq!(titles(&database, &user, &inclusive).await, ["urgent"]);
+    }
+
+    #[tokio::test]
+    async fn test_project_tasks_follow_membership() {
+        let Some(database) = test_database().await else { return };
+        let owner = create_user(&database).await;
+        let viewer = create_user(&database).await;
+        let outsider = create_user(&database).await;
+
+        let project = Project::new("Launch".to_string(), None, owner.id);
+        database.create_project(&project).await.unwrap();
+        database.set_project_member(project.id, viewer.id, ProjectRole::Viewer).await.unwrap();
+        assert_eq!(database.get_project_role(project.id, owner.id).await.unwrap(), Some(ProjectRole::Owner));
+        assert_eq!(database.count_project_owners(project.id).await.unwrap(), 1);
+        let members = database.list_project_members(project.id).await.unwrap();
+        assert_eq!(members.iter().map(|m| m.role).collect::<Vec<_>>(), [ProjectRole::Owner, ProjectRole::Viewer]);
+
+        let mut shared = Task::new("shared".to_string(), None, Priority::Medium, vec![], owner.id);
+        shared.project_id = Some(project.id);
+        shared.assigned_to = Some(outsider.id);
+        database.create_task(&shared).await.unwrap();
+        create_task(&database, "private", &owner, &[]).await;
+
+        let all = TaskFilter::default();
+        assert_eq!(titles(&database, &owner, &all).await, ["private", "shared"]);
+        assert_eq!(titles(&database, &viewer, &all).await, ["shared"]);
+        // Being assigned is not enough without membership
+        assert!(titles(&database, &outsider, &all).await.is_empty());
+        assert!(database.list_user_tasks(outsider.id).await.unwrap().is_empty());
+
+        let in_project = TaskFilter { project_id: Some(project.id), ..Default::default() };
+        assert_eq!(titles(&database, &owner, &in_project).await, ["shared"]);
+
+        database.remove_project_member(project.id, viewer.id).await.unwrap();
+        assert!(titles(&database, &viewer, &all).await.is_empty());
+        assert_eq!(database.list_user_projects(viewer.id).await.unwrap().len(), 0);
+
+        // The tasks of a deleted project become personal again
+        database.delete_project(project.id).await.unwrap();
+        assert_eq!(database.get_task_by_id(shared.id).await.unwrap().project_id, None);
+        assert_eq!(titles(&database, &outsider, &all).await, ["shared"]);
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_follow_their_own_project() {
+        let Some(database) = test_database().await else { return };
+        let owner = create_user(&database).await;
+        let editor = create_user(&database).await;
+
+        let project = Project::new("Hidden".to_string(), None, editor.id);
+        database.create_project(&project).await.unwrap();
+
+        // The editor can see the personal parent as its assignee
+        let mut parent = Task::new("parent".to_string(), None, Priority::Medium, vec![], owner.id);
+        parent.assigned_to = Some(editor.id);
+        database.create_task(&parent).await.unwrap();
+
+        let mut personal = Task::new("personal".to_string(), None, Priority::Medium, vec![], owner.id);
+        personal.parent_id = Some(parent.id);
+        database.create_task(&personal).await.unwrap();
+        let mut hidden = Task::new("hidden".to_string(), None, Priority::Medium, vec![], editor.id);
+        hidden.parent_id = Some(parent.id);
+        hidden.project_id = Some(project.id);
+        database.create_task(&hidden).await.unwrap();
+
+        let subtasks = |user: &User| {
+            let user_id = user.id;
+            let database = &database;
+            async move {
+                database
+                    .list_subtasks(user_id, parent.id)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(subtasks(&owner).await, ["personal"]);
+        assert_eq!(subtasks(&editor).await, ["hidden"]);
+
+        database.set_project_member(project.id, owner.id, ProjectRole::Viewer).await.unwrap();
+        assert_eq!(subtasks(&owner).await, ["personal", "hidden"]);
+    }
+
+    #[tokio::test]
+    async fn test_task_round_trip() {
+        let Some(database) = test_database().await else { return };
+        let user = create_user(&database).await;
+        let parent = create_task(&database, "parent", &user, &[]).await;
+        let blocker = create_task(&database, "blocker", &user, &[]).await;
+
+        let mut task = Task::new(
+            "child".to_string(),
+            Some("details".to_string()),
+            Priority::Critical,
+            vec!["work".to_string(), "two words".to_string()],
+            user.id,
+        );
+        task.status = TaskStatus::InProgress;
+        task.assigned_to = Some(user.id);
+        task.parent_id = Some(parent.id);
+        task.depends_on = vec![blocker.id];
+        task.recurrence = Some("every 2 weeks on mon,thu".parse().unwrap());
+        task.series_id = Some(parent.id);
+        database.create_task(&task).await.unwrap();
+
+        let stored = database.get_task_by_id(task.id).await.unwrap();
+        assert_eq!(stored.status, TaskStatus::InProgress);
+        assert_eq!(stored.priority, Priority::Critical);
+        assert_eq!(stored.tags, task.tags);
+        assert_eq!(stored.assigned_to, Some(user.id));
+        assert_eq!(stored.parent_id, Some(parent.id));
+        assert_eq!(stored.depends_on, vec![blocker.id]);
+        assert_eq!(stored.recurrence, task.recurrence);
+        assert_eq!(stored.series_id, Some(parent.id));
+        assert_eq!(database.get_user_by_id(user.id).await.unwrap().email, user.email);
+    }
+
+    /// Follows `next_cursor` until the last page, checking `total` on each page
+    async fn all_pages(database: &Database, user: &User, filter: &TaskFilter, sort: TaskSort, direction: SortDirection) -> Vec<String> {
+        let mut titles = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = PageRequest::new(Some(2), Some(sort), Some(direction), cursor.as_deref()).unwrap();
+            let page = database.list_tasks(user.id, filter, &page).await.unwrap();
+            assert!(page.tasks.len() <= 2);
+            titles.extend(page.tasks.into_iter().map(|t| t.title));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return titles,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_tasks_pages_through_every_sort() {
+        let Some(database) = test_database().await else { return };
+        let user = create_user(&database).await;
+        let now = Utc::now();
+
+        for (title, priority, due_in) in [
+            ("a", Priority::Low, Some(3)),
+            ("b", Priority::Critical, None),
+            ("c", Priority::High, Some(1)),
+            ("d", Priority::Medium, None),
+            ("e", Priority::High, Some(2)),
+        ] {
+            let mut task = create_task(&database, title, &user, &["paged"]).await;
+            task.priority = priority;
+            task.due_date = due_in.map(|days| now + Duration::days(days));
+            // Distinct creation times, in title order
+            task.created_at = now - Duration::minutes(10) + Duration::seconds(title.as_bytes()[0].into());
+            database.update_task(&task).await.unwrap();
+            sqlx::query("UPDATE tasks SET created_at = $2 WHERE id = $1")
+                .bind(task.id)
+                .bind(task.created_at)
+                .execute(&database.pool)
+                .await
+                .unwrap();
+        }
+
+        let filter = TaskFilter::default();
+        let page = database.list_tasks(user.id, &filter, &PageRequest::new(Some(2), None, None, None).unwrap()).await.unwrap();
+        assert_eq!(page.total, 5);
+        assert!(page.next_cursor.is_some());
+
+        let pages = |sort, direction| all_pages(&database, &user, &filter, sort, direction);
+        assert_eq!(pages(TaskSort::CreatedAt, SortDirection::Desc).await, ["e", "d", "c", "b", "a"]);
+        assert_eq!(pages(TaskSort::CreatedAt, SortDirection::Asc).await, ["a", "b", "c", "d", "e"]);
+        // Undated tasks come last either way
+        let by_due = pages(TaskSort::DueDate, SortDirection::Asc).await;
+        assert_eq!(by_due[..3], ["c", "e", "a"]);
+        let by_due = pages(TaskSort::DueDate, SortDirection::Desc).await;
+        assert_eq!(by_due[..3], ["a", "e", "c"]);
+        let by_priority = pages(TaskSort::Priority, SortDirection::Desc).await;
+        assert_eq!(by_priority[0], "b");
+        assert_eq!(by_priority[3..], ["d", "a"]);
+        assert_eq!(pages(TaskSort::UpdatedAt, SortDirection::Asc).await.len(), 5);
+
+        let high = TaskFilter { priority: Some(Priority::High), ..Default::default() };
+        let page = database.list_tasks(user.id, &high, &PageRequest::new(Some(1), None, None, None).unwrap()).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.tasks.len(), 1);
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+};
+use rusttaskflow_core::{ical, Task};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::handlers::AppResult;
+use crate::AppState;
+
+/// Creates the user's calendar feed, or rotates its token if it already exists
+pub async fn enable_feed(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Value>> {
+    let token = Uuid::new_v4().simple().to_string();
+    app_state.db.set_calendar_token(user.user_id, Some(&token)).await?;
+
+    Ok(Json(json!({
+        "token": token,
+        "path": format!("/calendar/{}.ics", token),
+    })))
+}
+
+/// Disables the user's calendar feed; the previous URL stops working
+pub async fn disable_feed(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<StatusCode> {
+    app_state.db.set_calendar_token(user.user_id, None).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Public read-only iCalendar feed: the secret token in the URL replaces
+/// the bearer token, which calendar applications cannot send
+pub async fn feed(
+    State(app_state): State<AppState>,
+    Path(token): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+    let user = app_state.db.get_user_by_calendar_token(token).await?;
+    let tasks = app_state.db.list_user_tasks(user.id).await?;
+
+    let tasks: Vec<&Task> = tasks.iter().collect();
+    let body = ical::write_calendar(&tasks, &format!("RustTaskFlow - {}", user.username));
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        body,
+    ))
+}
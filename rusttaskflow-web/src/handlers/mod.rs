@@ -1,4 +1,6 @@
 pub mod auth;
+pub mod calendar;
+pub mod projects;
 pub mod tasks;
 pub mod users;
 
@@ -17,6 +19,7 @@ impl IntoResponse for AppError {
         let (status, error_message) = match self.0 {
             TaskFlowError::TaskNotFound { .. } => (StatusCode::NOT_FOUND, self.0.to_string()),
             TaskFlowError::UserNotFound { .. } => (StatusCode::NOT_FOUND, self.0.to_string()),
+            TaskFlowError::ProjectNotFound { .. } => (StatusCode::NOT_FOUND, self.0.to_string()),
             TaskFlowError::Authentication { .. } => (StatusCode::UNAUTHORIZED, self.0.to_string()),
             TaskFlowError::Authorization { .. } => (StatusCode::FORBIDDEN, self.0.to_string()),
             TaskFlowError::Validation { .. } => (StatusCode::BAD_REQUEST, self.0.to_string()),
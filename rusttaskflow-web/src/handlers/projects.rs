@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::Utc;
+use rusttaskflow_core::{
+    AddProjectMemberRequest, CreateProjectRequest, Project, ProjectMember, ProjectRole, TaskFlowError,
+    UpdateProjectMemberRequest, UpdateProjectRequest,
+};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::handlers::{AppError, AppResult};
+use crate::policy;
+use crate::AppState;
+
+fn validate_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError(TaskFlowError::Validation {
+            message: "Project name cannot be empty".to_string(),
+        }));
+    }
+    Ok(())
+}
+
+/// Refuses to leave a project without owner when `user_id` stops being one
+async fn keep_an_owner(app_state: &AppState, project_id: Uuid, user_id: Uuid) -> AppResult<()> {
+    let is_owner = app_state.db.get_project_role(project_id, user_id).await? == Some(ProjectRole::Owner);
+    if is_owner && app_state.db.count_project_owners(project_id).await? <= 1 {
+        return Err(AppError(TaskFlowError::Validation {
+            message: "A project must keep at least one owner".to_string(),
+        }));
+    }
+    Ok(())
+}
+
+pub async fn create_project(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateProjectRequest>,
+) -> AppResult<Json<Project>> {
+    validate_name(&payload.name)?;
+
+    let project = Project::new(payload.name, payload.description, user.user_id);
+    app_state.db.create_project(&project).await?;
+    tracing::debug!("Project {} created by {}", project.id, user.username);
+
+    Ok(Json(project))
+}
+
+pub async fn list_projects(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<Project>>> {
+    let projects = app_state.db.list_user_projects(user.user_id).await?;
+    Ok(Json(projects))
+}
+
+pub async fn get_project(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Project>> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Viewer).await?;
+    let project = app_state.db.get_project_by_id(id).await?;
+    Ok(Json(project))
+}
+
+pub async fn update_project(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateProjectRequest>,
+) -> AppResult<Json<Project>> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Owner).await?;
+    let mut project = app_state.db.get_project_by_id(id).await?;
+
+    if let Some(name) = payload.name {
+        validate_name(&name)?;
+        project.name = name;
+    }
+
+    if let Some(description) = payload.description {
+        project.description = Some(description);
+    }
+
+    project.updated_at = Utc::now();
+    app_state.db.update_project(&project).await?;
+
+    Ok(Json(project))
+}
+
+pub async fn delete_project(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<StatusCode> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Owner).await?;
+    app_state.db.delete_project(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_members(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Vec<ProjectMember>>> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Viewer).await?;
+    let members = app_state.db.list_project_members(id).await?;
+    Ok(Json(members))
+}
+
+pub async fn add_member(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddProjectMemberRequest>,
+) -> AppResult<Json<Vec<ProjectMember>>> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Owner).await?;
+
+    // Fails with 404 for an unknown user
+    app_state.db.get_user_by_id(payload.user_id).await?;
+    if app_state.db.get_project_role(id, payload.user_id).await?.is_some() {
+        return Err(AppError(TaskFlowError::Validation {
+            message: "This user is already a member of the project".to_string(),
+        }));
+    }
+
+    app_state.db.set_project_member(id, payload.user_id, payload.role).await?;
+    let members = app_state.db.list_project_members(id).await?;
+    Ok(Json(members))
+}
+
+pub async fn update_member(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, member_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateProjectMemberRequest>,
+) -> AppResult<Json<Vec<ProjectMember>>> {
+    policy::authorize_project(&app_state, &user, id, ProjectRole::Owner).await?;
+
+    if app_state.db.get_project_role(id, member_id).await?.is_none() {
+        return Err(AppError(TaskFlowError::UserNotFound { id: member_id.to_string() }));
+    }
+    if payload.role != ProjectRole::Owner {
+        keep_an_owner(&app_state, id, member_id).await?;
+    }
+
+    app_state.db.set_project_member(id, member_id, payload.role).await?;
+    let members = app_state.db.list_project_members(id).await?;
+    Ok(Json(members))
+}
+
+/// Owners remove anyone; any member may leave the project
+pub async fn remove_member(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, member_id)): Path<(Uuid, Uuid)>,
+) -> AppResult<StatusCode> {
+    let required = if member_id == user.user_id { ProjectRole::Viewer } else { ProjectRole::Owner };
+    policy::authorize_project(&app_state, &user, id, required).await?;
+
+    keep_an_owner(&app_state, id, member_id).await?;
+    app_state.db.remove_project_member(id, member_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
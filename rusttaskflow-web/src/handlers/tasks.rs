@@ -4,15 +4,17 @@ use axum::{
     response::Json,
 };
 use rusttaskflow_core::{
-    CreateTaskRequest, Task, TaskFilter, UpdateTaskRequest,
+    CreateTaskRequest, ProjectRole, Task, TaskFilter, TaskStatus, UpdateTaskRequest,
 };
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::handlers::{AppError, AppResult};
 use crate::auth::AuthUser;
+use crate::policy::{self, Operation};
+use crate::pagination::{PageRequest, SortDirection, TaskPage, TaskSort};
 use crate::AppState;
 
 #[derive(Deserialize)]
@@ -20,7 +22,37 @@ pub struct TaskQuery {
     status: Option<String>,
     priority: Option<String>,
     assigned_to: Option<Uuid>,
+    created_by: Option<Uuid>,
+    /// Comma-separated; a task must carry all of them
     tags: Option<String>,
+    due_before: Option<DateTime<Utc>>,
+    due_after: Option<DateTime<Utc>>,
+    project_id: Option<Uuid>,
+    limit: Option<i64>,
+    /// `next_cursor` of the previous page
+    cursor: Option<String>,
+    sort: Option<TaskSort>,
+    direction: Option<SortDirection>,
+}
+
+/// Parses a `status` or `priority` query value by its variant name
+fn parse_query_value<T: DeserializeOwned>(field: &str, value: &str) -> AppResult<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).map_err(|_| {
+        AppError(rusttaskflow_core::TaskFlowError::Validation {
+            message: format!("Invalid {} '{}'", field, value),
+        })
+    })
+}
+
+#[derive(Deserialize)]
+pub struct StartQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct AddDependencyRequest {
+    depends_on: Uuid,
 }
 
 pub async fn create_task(
@@ -48,6 +80,29 @@ pub async fn create_task(
     task.due_date = payload.due_date;
     task.assigned_to = payload.assigned_to;
 
+    // A subtask can only be attached to a task the user can see, and
+    // belongs to the parent's project unless told otherwise
+    task.project_id = payload.project_id;
+    if let Some(parent_id) = payload.parent_id {
+        let parent = policy::authorize_task(&app_state, &user, parent_id, Operation::Read).await?;
+        task.parent_id = Some(parent_id);
+        task.project_id = task.project_id.or(parent.project_id);
+    }
+
+    if let Some(project_id) = task.project_id {
+        policy::authorize_project(&app_state, &user, project_id, ProjectRole::Editor).await?;
+    }
+    policy::check_assignee(&app_state, &task).await?;
+
+    task.recurrence = payload.recurrence;
+
+    for depends_on in payload.depends_on {
+        policy::authorize_task(&app_state, &user, depends_on, Operation::Read).await?;
+        if !task.depends_on.contains(&depends_on) {
+            task.depends_on.push(depends_on);
+        }
+    }
+
     // Save to database
     app_state.db.create_task(&task).await?;
 
@@ -65,20 +120,27 @@ pub async fn list_tasks(
     State(app_state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Query(query): Query<TaskQuery>,
-) -> AppResult<Json<Vec<Task>>> {
-    // Build filter
+) -> AppResult<Json<TaskPage>> {
+    let page = PageRequest::new(query.limit, query.sort, query.direction, query.cursor.as_deref())?;
     let filter = TaskFilter {
-        status: query.status.and_then(|s| serde_json::from_str(&format!("\"{}\"", s)).ok()),
-        priority: query.priority.and_then(|p| serde_json::from_str(&format!("\"{}\"", p)).ok()),
+        status: query.status.as_deref().map(|s| parse_query_value("status", s)).transpose()?,
+        priority: query.priority.as_deref().map(|p| parse_query_value("priority", p)).transpose()?,
         assigned_to: query.assigned_to,
-        created_by: Some(user.user_id), // Only show user's tasks for now
-        tags: query.tags.map(|t| t.split(',').map(|s| s.trim().to_string()).collect()),
-        due_before: None,
-        due_after: None,
+        created_by: query.created_by,
+        tags: query.tags.map(|t| {
+            t.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        }),
+        due_before: query.due_before,
+        due_after: query.due_after,
+        project_id: query.project_id,
     };
 
-    let tasks = app_state.db.list_tasks(Some(filter)).await?;
-    Ok(Json(tasks))
+    // Only tasks the user can see, whatever the filter
+    let page = app_state.db.list_tasks(user.user_id, &filter, &page).await?;
+    Ok(Json(page))
 }
 
 pub async fn get_task(
@@ -86,14 +148,59 @@ pub async fn get_task(
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Task>> {
+    let task = policy::authorize_task(&app_state, &user, id, Operation::Read).await?;
+    Ok(Json(task))
+}
+
+pub async fn get_subtasks(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Vec<Task>>> {
+    policy::authorize_task(&app_state, &user, id, Operation::Read).await?;
+
+    let subtasks = app_state.db.list_subtasks(user.user_id, id).await?;
+    Ok(Json(subtasks))
+}
+
+pub async fn add_dependency(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddDependencyRequest>,
+) -> AppResult<Json<Task>> {
+    policy::authorize_task(&app_state, &user, id, Operation::Update).await?;
+    policy::authorize_task(&app_state, &user, payload.depends_on, Operation::Read).await?;
+
+    app_state.db.add_dependency(id, payload.depends_on).await?;
     let task = app_state.db.get_task_by_id(id).await?;
-    
-    // Check if user has access to this task
-    if task.created_by != user.user_id && task.assigned_to != Some(user.user_id) {
-        return Err(AppError(rusttaskflow_core::TaskFlowError::Authorization {
-            message: "You don't have access to this task".to_string(),
-        }));
-    }
+
+    // Send WebSocket notification
+    let ws_message = crate::websocket::WebSocketMessage::TaskUpdated {
+        task: task.clone(),
+        user_id: user.user_id,
+    };
+    let _ = app_state.websocket_tx.send(ws_message);
+
+    Ok(Json(task))
+}
+
+pub async fn remove_dependency(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, depends_on)): Path<(Uuid, Uuid)>,
+) -> AppResult<Json<Task>> {
+    policy::authorize_task(&app_state, &user, id, Operation::Update).await?;
+
+    app_state.db.remove_dependency(id, depends_on).await?;
+    let task = app_state.db.get_task_by_id(id).await?;
+
+    // Send WebSocket notification
+    let ws_message = crate::websocket::WebSocketMessage::TaskUpdated {
+        task: task.clone(),
+        user_id: user.user_id,
+    };
+    let _ = app_state.websocket_tx.send(ws_message);
 
     Ok(Json(task))
 }
@@ -104,11 +211,23 @@ pub async fn update_task(
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateTaskRequest>,
 ) -> AppResult<Json<Task>> {
-    let mut task = app_state.db.get_task_by_id(id).await?;
-    
-    // For now, allow all authenticated users to modify tasks
-    // TODO: Implement proper project-based authorization
-    // This is a temporary solution until project membership is implemented
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Read).await?;
+
+    // Reassigning is a right of its own; the other fields need Update
+    let edits_fields = payload.title.is_some()
+        || payload.description.is_some()
+        || payload.priority.is_some()
+        || payload.tags.is_some()
+        || payload.due_date.is_some();
+    if edits_fields {
+        policy::authorize(&app_state, &user, &task, Operation::Update).await?;
+    }
+    if payload.assigned_to.is_some() {
+        policy::authorize(&app_state, &user, &task, Operation::Assign).await?;
+    } else if !edits_fields {
+        // Nothing to change: a reader must not be able to bump `updated_at`
+        return Ok(Json(task));
+    }
 
     // Update fields
     if let Some(title) = payload.title {
@@ -138,6 +257,7 @@ pub async fn update_task(
     
     if let Some(assigned_to) = payload.assigned_to {
         task.assigned_to = Some(assigned_to);
+        policy::check_assignee(&app_state, &task).await?;
     }
 
     task.updated_at = Utc::now();
@@ -160,14 +280,7 @@ pub async fn delete_task(
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> AppResult<StatusCode> {
-    let task = app_state.db.get_task_by_id(id).await?;
-    
-    // Check if user has access to this task
-    if task.created_by != user.user_id {
-        return Err(AppError(rusttaskflow_core::TaskFlowError::Authorization {
-            message: "You can only delete tasks you created".to_string(),
-        }));
-    }
+    let task = policy::authorize_task(&app_state, &user, id, Operation::Delete).await?;
 
     app_state.db.delete_task(id).await?;
     
@@ -175,6 +288,7 @@ pub async fn delete_task(
     let ws_message = crate::websocket::WebSocketMessage::TaskDeleted {
         task_id: id,
         user_id: user.user_id,
+        task: Some(task),
     };
     let _ = app_state.websocket_tx.send(ws_message);
     
@@ -185,14 +299,20 @@ pub async fn start_task(
     State(app_state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
+    Query(query): Query<StartQuery>,
 ) -> AppResult<Json<Task>> {
-    let mut task = app_state.db.get_task_by_id(id).await?;
-    
-    // Check if user has access to this task
-    if task.created_by != user.user_id && task.assigned_to != Some(user.user_id) {
-        return Err(AppError(rusttaskflow_core::TaskFlowError::Authorization {
-            message: "You don't have access to this task".to_string(),
-        }));
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Transition).await?;
+
+    // A task waiting on unfinished dependencies only starts with ?force=true
+    if !query.force {
+        for depends_on in &task.depends_on {
+            let dependency = app_state.db.get_task_by_id(*depends_on).await?;
+            if matches!(dependency.status, TaskStatus::Todo | TaskStatus::InProgress) {
+                return Err(AppError(rusttaskflow_core::TaskFlowError::Validation {
+                    message: format!("Task is blocked by unfinished dependency {}", dependency.id),
+                }));
+            }
+        }
     }
 
     task.start()?;
@@ -213,16 +333,18 @@ pub async fn complete_task(
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Task>> {
-    let mut task = app_state.db.get_task_by_id(id).await?;
-    
-    // Check if user has access to this task
-    if task.created_by != user.user_id && task.assigned_to != Some(user.user_id) {
-        return Err(AppError(rusttaskflow_core::TaskFlowError::Authorization {
-            message: "You don't have access to this task".to_string(),
-        }));
-    }
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Transition).await?;
 
     task.complete()?;
+
+    // A recurring task spawns its next occurrence, unless the series already
+    // has an open one (the task was reopened and completed again)
+    let series = app_state.db.list_series_tasks(task.series_id.unwrap_or(task.id)).await?;
+    let next_exists = series.iter().any(|t| {
+        t.id != task.id && matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress)
+    });
+    let next = if next_exists { None } else { task.next_occurrence(Utc::now(), &Utc) };
+
     app_state.db.update_task(&task).await?;
 
     // Send WebSocket notification
@@ -232,6 +354,17 @@ pub async fn complete_task(
     };
     let _ = app_state.websocket_tx.send(ws_message);
 
+    if let Some(next) = next {
+        app_state.db.create_task(&next).await?;
+        tracing::debug!("Task {} spawned next occurrence {}", task.id, next.id);
+
+        let ws_message = crate::websocket::WebSocketMessage::TaskCreated {
+            task: next,
+            user_id: user.user_id,
+        };
+        let _ = app_state.websocket_tx.send(ws_message);
+    }
+
     Ok(Json(task))
 }
 
@@ -240,14 +373,7 @@ pub async fn cancel_task(
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> AppResult<Json<Task>> {
-    let mut task = app_state.db.get_task_by_id(id).await?;
-    
-    // Check if user has access to this task
-    if task.created_by != user.user_id && task.assigned_to != Some(user.user_id) {
-        return Err(AppError(rusttaskflow_core::TaskFlowError::Authorization {
-            message: "You don't have access to this task".to_string(),
-        }));
-    }
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Transition).await?;
 
     task.cancel()?;
     app_state.db.update_task(&task).await?;
@@ -260,4 +386,44 @@ pub async fn cancel_task(
     let _ = app_state.websocket_tx.send(ws_message);
 
     Ok(Json(task))
-}
\ No newline at end of file
+}
+
+pub async fn reopen_task(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Task>> {
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Transition).await?;
+
+    task.reopen()?;
+    app_state.db.update_task(&task).await?;
+
+    // Send WebSocket notification
+    let ws_message = crate::websocket::WebSocketMessage::TaskUpdated {
+        task: task.clone(),
+        user_id: user.user_id,
+    };
+    let _ = app_state.websocket_tx.send(ws_message);
+
+    Ok(Json(task))
+}
+
+pub async fn reset_task(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<Task>> {
+    let mut task = policy::authorize_task(&app_state, &user, id, Operation::Transition).await?;
+
+    task.reset()?;
+    app_state.db.update_task(&task).await?;
+
+    // Send WebSocket notification
+    let ws_message = crate::websocket::WebSocketMessage::TaskUpdated {
+        task: task.clone(),
+        user_id: user.user_id,
+    };
+    let _ = app_state.websocket_tx.send(ws_message);
+
+    Ok(Json(task))
+}
@@ -18,6 +18,8 @@ mod auth;
 mod database;
 mod handlers;
 mod middleware;
+mod pagination;
+mod policy;
 mod websocket;
 
 use auth::AuthService;
@@ -105,6 +107,7 @@ async fn create_app(database: Database) -> Router {
         .route("/health", get(health_check))
         .nest("/api/v1", api_routes())
         .route("/ws", get(websocket::websocket_handler))
+        .route("/calendar/:token", get(handlers::calendar::feed))
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
@@ -118,6 +121,7 @@ fn api_routes() -> Router<AppState> {
     Router::new()
         .nest("/auth", auth_routes())
         .nest("/tasks", task_routes())
+        .nest("/projects", project_routes())
         .nest("/users", user_routes())
 }
 
@@ -135,9 +139,28 @@ fn task_routes() -> Router<AppState> {
         .route("/:id", get(handlers::tasks::get_task))
         .route("/:id", put(handlers::tasks::update_task))
         .route("/:id", delete(handlers::tasks::delete_task))
+        .route("/:id/subtasks", get(handlers::tasks::get_subtasks))
+        .route("/:id/dependencies", post(handlers::tasks::add_dependency))
+        .route("/:id/dependencies/:depends_on", delete(handlers::tasks::remove_dependency))
         .route("/:id/start", post(handlers::tasks::start_task))
         .route("/:id/complete", post(handlers::tasks::complete_task))
         .route("/:id/cancel", post(handlers::tasks::cancel_task))
+        .route("/:id/reopen", post(handlers::tasks::reopen_task))
+        .route("/:id/reset", post(handlers::tasks::reset_task))
+        .layer(axum::middleware::from_fn(middleware::auth::auth_middleware))
+}
+
+fn project_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(handlers::projects::create_project))
+        .route("/", get(handlers::projects::list_projects))
+        .route("/:id", get(handlers::projects::get_project))
+        .route("/:id", put(handlers::projects::update_project))
+        .route("/:id", delete(handlers::projects::delete_project))
+        .route("/:id/members", get(handlers::projects::list_members))
+        .route("/:id/members", post(handlers::projects::add_member))
+        .route("/:id/members/:user_id", put(handlers::projects::update_member))
+        .route("/:id/members/:user_id", delete(handlers::projects::remove_member))
         .layer(axum::middleware::from_fn(middleware::auth::auth_middleware))
 }
 
@@ -145,6 +168,8 @@ fn user_routes() -> Router<AppState> {
     Router::new()
         .route("/", get(handlers::users::list_users))
         .route("/:id", get(handlers::users::get_user))
+        .route("/me/calendar", post(handlers::calendar::enable_feed))
+        .route("/me/calendar", delete(handlers::calendar::disable_feed))
         .layer(axum::middleware::from_fn(middleware::auth::auth_middleware))
 }
 
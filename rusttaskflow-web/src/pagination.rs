@@ -0,0 +1,225 @@
+//! Keyset pagination and sorting for task lists
+//!
+//! A page ends with an opaque cursor holding the sort key and id of its last
+//! task; the next page starts strictly after that position, so inserts and
+//! deletes between requests never shift or repeat tasks.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use rusttaskflow_core::{Priority, Result, Task, TaskFlowError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    #[default]
+    CreatedAt,
+    DueDate,
+    Priority,
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    /// Comparison selecting the rows that come after a cursor
+    pub fn after_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        }
+    }
+}
+
+/// Sort key of a task, as stored in a cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SortKey {
+    Priority(Priority),
+    /// `None` for a task without a due date
+    Time(Option<DateTime<Utc>>),
+}
+
+impl TaskSort {
+    /// Whether `key` has the type of this sort's column
+    pub fn accepts(&self, key: &SortKey) -> bool {
+        matches!(
+            (self, key),
+            (TaskSort::Priority, SortKey::Priority(_))
+                | (TaskSort::CreatedAt | TaskSort::UpdatedAt, SortKey::Time(Some(_)))
+                | (TaskSort::DueDate, SortKey::Time(_))
+        )
+    }
+
+    pub fn key(&self, task: &Task) -> SortKey {
+        match self {
+            TaskSort::CreatedAt => SortKey::Time(Some(task.created_at)),
+            TaskSort::DueDate => SortKey::Time(task.due_date),
+            TaskSort::Priority => SortKey::Priority(task.priority.clone()),
+            TaskSort::UpdatedAt => SortKey::Time(Some(task.updated_at)),
+        }
+    }
+}
+
+/// Position of the last task of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub sort: TaskSort,
+    pub direction: SortDirection,
+    pub key: SortKey,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn after(task: &Task, sort: TaskSort, direction: SortDirection) -> Self {
+        Self {
+            sort,
+            direction,
+            key: sort.key(task),
+            id: task.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).expect("a cursor always serializes"))
+    }
+
+    pub fn decode(value: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| TaskFlowError::Validation {
+                message: "Invalid cursor".to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+    pub limit: i64,
+    pub sort: TaskSort,
+    pub direction: SortDirection,
+    pub after: Option<Cursor>,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            sort: TaskSort::default(),
+            direction: SortDirection::default(),
+            after: None,
+        }
+    }
+}
+
+impl PageRequest {
+    /// Validates the query parameters; a cursor only continues the sort it was issued for
+    pub fn new(
+        limit: Option<i64>,
+        sort: Option<TaskSort>,
+        direction: Option<SortDirection>,
+        cursor: Option<&str>,
+    ) -> Result<Self> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(TaskFlowError::Validation {
+                message: format!("limit must be between 1 and {}", MAX_LIMIT),
+            });
+        }
+
+        let sort = sort.unwrap_or_default();
+        let direction = direction.unwrap_or_default();
+        let after = cursor.map(Cursor::decode).transpose()?;
+        if let Some(cursor) = &after {
+            if cursor.sort != sort || cursor.direction != direction {
+                return Err(TaskFlowError::Validation {
+                    message: "This cursor belongs to a different sort order".to_string(),
+                });
+            }
+            // A tampered key would be bound against a column of another type
+            if !sort.accepts(&cursor.key) {
+                return Err(TaskFlowError::Validation {
+                    message: "Invalid cursor".to_string(),
+                });
+            }
+        }
+
+        Ok(Self { limit, sort, direction, after })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    /// Cursor of the following page, `None` on the last one
+    pub next_cursor: Option<String>,
+    /// Number of tasks matching the filter, across all pages
+    pub total: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let mut task = Task::new("Ship".to_string(), None, Priority::High, vec![], Uuid::new_v4());
+        for sort in [TaskSort::CreatedAt, TaskSort::DueDate, TaskSort::Priority, TaskSort::UpdatedAt] {
+            let cursor = Cursor::after(&task, sort, SortDirection::Asc);
+            assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        }
+        task.due_date = Some(Utc::now());
+        let cursor = Cursor::after(&task, TaskSort::DueDate, SortDirection::Desc);
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        assert!(Cursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_page_request_validation() {
+        assert_eq!(PageRequest::new(None, None, None, None).unwrap(), PageRequest::default());
+        assert!(PageRequest::new(Some(0), None, None, None).is_err());
+        assert!(PageRequest::new(Some(MAX_LIMIT + 1), None, None, None).is_err());
+
+        let task = Task::new("Ship".to_string(), None, Priority::High, vec![], Uuid::new_v4());
+        let cursor = Cursor::after(&task, TaskSort::Priority, SortDirection::Asc).encode();
+        let page = PageRequest::new(Some(10), Some(TaskSort::Priority), Some(SortDirection::Asc), Some(&cursor)).unwrap();
+        assert_eq!(page.after.unwrap().id, task.id);
+        assert!(PageRequest::new(None, Some(TaskSort::Priority), None, Some(&cursor)).is_err());
+
+        // A key that does not match the sort column is rejected
+        let tampered = Cursor {
+            key: SortKey::Time(Some(Utc::now())),
+            ..Cursor::after(&task, TaskSort::Priority, SortDirection::Desc)
+        };
+        assert!(PageRequest::new(None, Some(TaskSort::Priority), None, Some(&tampered.encode())).is_err());
+        let tampered = Cursor {
+            key: SortKey::Time(None),
+            ..Cursor::after(&task, TaskSort::CreatedAt, SortDirection::Desc)
+        };
+        assert!(PageRequest::new(None, None, None, Some(&tampered.encode())).is_err());
+        let tampered = Cursor {
+            key: SortKey::Priority(Priority::Low),
+            ..Cursor::after(&task, TaskSort::DueDate, SortDirection::Desc)
+        };
+        assert!(PageRequest::new(None, Some(TaskSort::DueDate), None, Some(&tampered.encode())).is_err());
+    }
+}
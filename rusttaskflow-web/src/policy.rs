@@ -0,0 +1,231 @@
+//! Authorization policy for tasks and projects
+//!
+//! Every task check goes through `evaluate`, a pure function of the actor,
+//! the task and the operation. A personal task (without project) belongs to
+//! its creator, who shares it with the assignee. A project task follows the
+//! actor's role in the project: viewers read (and move the tasks assigned to
+//! them), editors work on every task, owners and the task's creator delete.
+//!
+//! Denials are logged with their reason.
+
+use rusttaskflow_core::{ProjectRole, Task, TaskFlowError};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::handlers::{AppError, AppResult};
+use crate::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// View the task and its subtasks, depend on it, receive its updates
+    Read,
+    /// Change its fields and dependencies
+    Update,
+    Delete,
+    /// Start, complete, cancel, reopen or reset it
+    Transition,
+    /// Change who it is assigned to
+    Assign,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Read => "read",
+            Operation::Update => "update",
+            Operation::Delete => "delete",
+            Operation::Transition => "change the status of",
+            Operation::Assign => "assign",
+        })
+    }
+}
+
+/// The user acting on a task, with their role in the task's project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Actor {
+    pub user_id: Uuid,
+    pub role: Option<ProjectRole>,
+}
+
+impl Actor {
+    pub async fn for_task(app_state: &AppState, user_id: Uuid, task: &Task) -> AppResult<Self> {
+        let role = match task.project_id {
+            Some(project_id) => app_state.db.get_project_role(project_id, user_id).await?,
+            None => None,
+        };
+        Ok(Self { user_id, role })
+    }
+}
+
+/// Allows `operation`, or gives the reason of the denial
+pub fn evaluate(actor: &Actor, task: &Task, operation: Operation) -> Result<(), &'static str> {
+    let is_creator = task.created_by == actor.user_id;
+    let is_assignee = task.assigned_to == Some(actor.user_id);
+
+    if task.project_id.is_none() {
+        return match operation {
+            Operation::Read | Operation::Update | Operation::Transition if is_creator || is_assignee => Ok(()),
+            Operation::Delete | Operation::Assign if is_creator => Ok(()),
+            Operation::Delete | Operation::Assign if is_assignee => {
+                Err("only the creator of a personal task can do this")
+            }
+            _ => Err("personal tasks are only shared between their creator and assignee"),
+        };
+    }
+
+    let Some(role) = actor.role else {
+        return Err("not a member of the task's project");
+    };
+    match operation {
+        Operation::Read => Ok(()),
+        Operation::Update | Operation::Assign if role.includes(ProjectRole::Editor) => Ok(()),
+        Operation::Transition if role.includes(ProjectRole::Editor) || is_assignee => Ok(()),
+        Operation::Transition => Err("viewers can only change the status of tasks assigned to them"),
+        Operation::Delete if role == ProjectRole::Owner => Ok(()),
+        Operation::Delete if role == ProjectRole::Editor && is_creator => Ok(()),
+        Operation::Delete => Err("only project owners and the task's creator can delete it"),
+        Operation::Update | Operation::Assign => Err("viewers cannot modify project tasks"),
+    }
+}
+
+/// Checks `operation` on a loaded task, logging a denial
+pub async fn authorize(app_state: &AppState, user: &AuthUser, task: &Task, operation: Operation) -> AppResult<()> {
+    let actor = Actor::for_task(app_state, user.user_id, task).await?;
+    evaluate(&actor, task, operation).map_err(|reason| {
+        tracing::warn!(
+            user_id = %user.user_id,
+            task_id = %task.id,
+            %operation,
+            reason,
+            "Task access denied"
+        );
+        AppError(TaskFlowError::Authorization {
+            message: format!("You cannot {} this task: {}", operation, reason),
+        })
+    })
+}
+
+/// Loads a task and checks `operation` on it
+pub async fn authorize_task(app_state: &AppState, user: &AuthUser, id: Uuid, operation: Operation) -> AppResult<Task> {
+    let task = app_state.db.get_task_by_id(id).await?;
+    authorize(app_state, user, &task, operation).await?;
+    Ok(task)
+}
+
+/// Checks the user's role in an existing project includes `required`, and returns it
+pub async fn authorize_project(
+    app_state: &AppState,
+    user: &AuthUser,
+    project_id: Uuid,
+    required: ProjectRole,
+) -> AppResult<ProjectRole> {
+    app_state.db.get_project_by_id(project_id).await?;
+
+    let role = app_state.db.get_project_role(project_id, user.user_id).await?;
+    let reason = match role {
+        Some(role) if role.includes(required) => return Ok(role),
+        Some(_) => "your role in this project does not allow this",
+        None => "not a member of this project",
+    };
+
+    tracing::warn!(
+        user_id = %user.user_id,
+        %project_id,
+        required = ?required,
+        reason,
+        "Project access denied"
+    );
+    Err(AppError(TaskFlowError::Authorization {
+        message: format!("Access to this project denied: {}", reason),
+    }))
+}
+
+/// A project task can only be assigned to a member of the project
+pub async fn check_assignee(app_state: &AppState, task: &Task) -> AppResult<()> {
+    if let (Some(project_id), Some(assignee)) = (task.project_id, task.assigned_to) {
+        if app_state.db.get_project_role(project_id, assignee).await?.is_none() {
+            return Err(AppError(TaskFlowError::Validation {
+                message: "The assignee is not a member of this project".to_string(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusttaskflow_core::Priority;
+
+    const OPERATIONS: [Operation; 5] = [
+        Operation::Read,
+        Operation::Update,
+        Operation::Delete,
+        Operation::Transition,
+        Operation::Assign,
+    ];
+
+    #[derive(Clone, Copy)]
+    enum Relation {
+        Creator,
+        Assignee,
+        Other,
+    }
+
+    #[test]
+    fn test_policy_truth_table() {
+        use ProjectRole::*;
+        use Relation::*;
+
+        // Allowed operations in the order Read, Update, Delete, Transition, Assign
+        let table: [(bool, Relation, Option<ProjectRole>, &str); 15] = [
+            // Personal tasks: project roles play no part
+            (false, Creator, None, "RUDTA"),
+            (false, Assignee, None, "RU.T."),
+            (false, Other, None, "....."),
+            (false, Other, Some(Owner), "....."),
+            // Project tasks: only the role, plus the creator or assignee within it
+            (true, Other, Some(Owner), "RUDTA"),
+            (true, Creator, Some(Owner), "RUDTA"),
+            (true, Creator, Some(Editor), "RUDTA"),
+            (true, Assignee, Some(Editor), "RU.TA"),
+            (true, Other, Some(Editor), "RU.TA"),
+            (true, Creator, Some(Viewer), "R...."),
+            (true, Assignee, Some(Viewer), "R..T."),
+            (true, Other, Some(Viewer), "R...."),
+            (true, Creator, None, "....."),
+            (true, Assignee, None, "....."),
+            (true, Other, None, "....."),
+        ];
+
+        let (creator, assignee, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut task = Task::new("Ship".to_string(), None, Priority::Medium, vec![], creator);
+        task.assigned_to = Some(assignee);
+
+        for (in_project, relation, role, expected) in table {
+            task.project_id = in_project.then(Uuid::nil);
+            let user_id = match relation {
+                Creator => creator,
+                Assignee => assignee,
+                Other => other,
+            };
+            let actor = Actor { user_id, role };
+            let allowed: String = OPERATIONS
+                .iter()
+                .zip("RUDTA".chars())
+                .map(|(operation, letter)| if evaluate(&actor, &task, *operation).is_ok() { letter } else { '.' })
+                .collect();
+            assert_eq!(allowed, expected, "project: {}, role: {:?}", in_project, role);
+        }
+    }
+
+    #[test]
+    fn test_denials_have_a_reason() {
+        let task = Task::new("Ship".to_string(), None, Priority::Medium, vec![], Uuid::new_v4());
+        let stranger = Actor { user_id: Uuid::new_v4(), role: None };
+        for operation in OPERATIONS {
+            assert!(!evaluate(&stranger, &task, operation).unwrap_err().is_empty());
+        }
+    }
+}
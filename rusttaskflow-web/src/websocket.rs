@@ -1,19 +1,28 @@
 use axum::{
-    extract::{ws::{WebSocket, Message}, WebSocketUpgrade, State},
+    extract::{ws::{WebSocket, Message}, Query, WebSocketUpgrade, State},
     response::Response,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 use uuid::Uuid;
 use rusttaskflow_core::models::Task;
 
+use crate::policy::{self, Actor, Operation};
+
 // Types de messages WebSocket
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum WebSocketMessage {
     TaskCreated { task: Task, user_id: Uuid },
     TaskUpdated { task: Task, user_id: Uuid },
-    TaskDeleted { task_id: Uuid, user_id: Uuid },
+    TaskDeleted {
+        task_id: Uuid,
+        user_id: Uuid,
+        /// Tâche supprimée, pour n'avertir que ceux qui pouvaient la lire
+        #[serde(skip)]
+        task: Option<Task>,
+    },
     UserConnected { user_id: Uuid, username: String },
     UserDisconnected { user_id: Uuid },
     Notification { message: String, notification_type: String, user_id: Option<Uuid> },
@@ -29,30 +38,89 @@ pub enum ClientMessage {
     Authenticate { token: String },
 }
 
-// Handler pour la mise à niveau WebSocket
+#[derive(Debug, Deserialize)]
+pub struct ConnectQuery {
+    token: Option<String>,
+}
+
+/// Utilisateur authentifié par un token JWT
+#[derive(Debug, Clone)]
+struct ConnectedUser {
+    user_id: Uuid,
+    username: String,
+}
+
+fn authenticate(app_state: &crate::AppState, token: &str) -> Option<ConnectedUser> {
+    let claims = app_state.auth_service.verify_token(token).ok()?;
+    let user_id = Uuid::parse_str(&claims.sub).ok()?;
+    Some(ConnectedUser { user_id, username: claims.username })
+}
+
+// Handler pour la mise à niveau WebSocket ; le client peut s'authentifier
+// dès la connexion avec ?token=
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(app_state): State<crate::AppState>,
+    Query(query): Query<ConnectQuery>,
 ) -> Response {
-    ws.on_upgrade(move |socket| websocket_connection(socket, app_state))
+    let user = query.token.and_then(|token| authenticate(&app_state, &token));
+    ws.on_upgrade(move |socket| websocket_connection(socket, app_state, user))
+}
+
+/// Décide si un message broadcast part vers l'utilisateur de la connexion.
+/// Les messages de tâche suivent la politique de lecture.
+async fn visible_to(app_state: &crate::AppState, user_id: Option<Uuid>, msg: &WebSocketMessage) -> bool {
+    let task = match msg {
+        WebSocketMessage::TaskCreated { task, .. } | WebSocketMessage::TaskUpdated { task, .. } => Some(task),
+        WebSocketMessage::TaskDeleted { task, .. } => match task {
+            Some(task) => Some(task),
+            None => return false,
+        },
+        WebSocketMessage::Notification { user_id: Some(target), .. } => return user_id == Some(*target),
+        _ => None,
+    };
+    let Some(task) = task else {
+        return true;
+    };
+    let Some(user_id) = user_id else {
+        return false;
+    };
+
+    let actor = match Actor::for_task(app_state, user_id, task).await {
+        Ok(actor) => actor,
+        Err(e) => {
+            tracing::error!("Cannot check access to task {} for {}: {:?}", task.id, user_id, e.0);
+            return false;
+        }
+    };
+    match policy::evaluate(&actor, task, Operation::Read) {
+        Ok(()) => true,
+        Err(reason) => {
+            tracing::debug!(%user_id, task_id = %task.id, reason, "Task event filtered");
+            false
+        }
+    }
 }
 
 // Gestion d'une connexion WebSocket individuelle
-async fn websocket_connection(socket: WebSocket, app_state: crate::AppState) {
-    let _connection_id = Uuid::new_v4().to_string();
+async fn websocket_connection(socket: WebSocket, app_state: crate::AppState, user: Option<ConnectedUser>) {
     let mut receiver = app_state.websocket_tx.subscribe();
     
     let (mut sender, mut receiver_ws) = socket.split();
     
-    // Pour l'instant, pas d'authentification automatique
-    // L'authentification se fera via les messages ClientMessage::Authenticate
-    let mut user_id: Option<Uuid> = None;
-    let mut username: Option<String> = None;
+    // L'utilisateur de la connexion peut aussi venir plus tard d'un
+    // ClientMessage::Authenticate
+    let (user_tx, user_rx) = watch::channel(user.clone());
+    let connection_user = user_rx.clone();
     
-    // Task pour envoyer les messages broadcast
-    let _app_state_clone = app_state.clone();
+    // Task pour envoyer les messages broadcast visibles par l'utilisateur
+    let app_state_clone = app_state.clone();
     let send_task = tokio::spawn(async move {
         while let Ok(msg) = receiver.recv().await {
+            let user_id = user_rx.borrow().as_ref().map(|user| user.user_id);
+            if !visible_to(&app_state_clone, user_id, &msg).await {
+                continue;
+            }
             let json_msg = serde_json::to_string(&msg).unwrap_or_default();
             if sender.send(Message::Text(json_msg)).await.is_err() {
                 break;
@@ -68,7 +136,7 @@ async fn websocket_connection(socket: WebSocket, app_state: crate::AppState) {
                 match msg {
                     Message::Text(text) => {
                         if let Ok(client_msg) = serde_json::from_str::<ClientMessage>(&text) {
-                            handle_client_message(client_msg, &app_state_clone2).await;
+                            handle_client_message(client_msg, &app_state_clone2, &user_tx).await;
                         }
                     }
                     Message::Close(_) => break,
@@ -81,10 +149,10 @@ async fn websocket_connection(socket: WebSocket, app_state: crate::AppState) {
     });
     
     // Envoyer une notification de connexion si l'utilisateur est authentifié
-    if let (Some(uid), Some(uname)) = (user_id, &username) {
+    if let Some(user) = user {
         let _ = app_state.websocket_tx.send(WebSocketMessage::UserConnected {
-            user_id: uid,
-            username: uname.clone(),
+            user_id: user.user_id,
+            username: user.username,
         });
     }
     
@@ -95,9 +163,10 @@ async fn websocket_connection(socket: WebSocket, app_state: crate::AppState) {
     }
     
     // Envoyer une notification de déconnexion si l'utilisateur était authentifié
-    if let Some(uid) = user_id {
+    let user = connection_user.borrow().clone();
+    if let Some(user) = user {
         let _ = app_state.websocket_tx.send(WebSocketMessage::UserDisconnected {
-            user_id: uid,
+            user_id: user.user_id,
         });
     }
 }
@@ -106,17 +175,17 @@ async fn websocket_connection(socket: WebSocket, app_state: crate::AppState) {
 async fn handle_client_message(
     message: ClientMessage,
     app_state: &crate::AppState,
+    user_tx: &watch::Sender<Option<ConnectedUser>>,
 ) {
     match message {
         ClientMessage::Authenticate { token } => {
             // Valider le token JWT et associer l'utilisateur à la connexion
-            if let Ok(claims) = app_state.auth_service.verify_token(&token) {
-                if let Ok(user_id) = Uuid::parse_str(&claims.sub) {
-                    let username = format!("user_{}", claims.sub);
-                    
-                    // Notifier les autres utilisateurs
-                    let _ = app_state.websocket_tx.send(WebSocketMessage::UserConnected { user_id, username });
-                }
+            if let Some(user) = authenticate(app_state, &token) {
+                let (user_id, username) = (user.user_id, user.username.clone());
+                user_tx.send_replace(Some(user));
+                
+                // Notifier les autres utilisateurs
+                let _ = app_state.websocket_tx.send(WebSocketMessage::UserConnected { user_id, username });
             }
         }
         ClientMessage::Ping => {